@@ -0,0 +1,118 @@
+//! Derive macros for `nina`.
+//!
+//! Enable the `derive` feature of `nina` to use them through
+//! `nina::storage::Bundle` instead of depending on this crate directly.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, parse_quote_spanned, spanned::Spanned, Data, DeriveInput, Error, Fields};
+
+/// Implements `Bundle` for a struct with named fields.
+///
+/// Every field must be `EcsData`. Components are stored in declaration order.
+/// Fields marked `#[bundle]` must themselves be a `Bundle` and are flattened
+/// into the outer bundle.
+///
+/// ```ignore
+/// #[derive(Bundle)]
+/// struct PlayerBundle {
+///   health:Health,
+///   #[bundle]
+///   motion:MotionBundle
+/// }
+/// ```
+#[proc_macro_derive(Bundle, attributes(bundle))]
+pub fn derive_bundle(input:TokenStream) -> TokenStream {
+  let input = parse_macro_input!(input as DeriveInput);
+  match bundle_impl(&input) {
+    Ok(tokens) => tokens.into(),
+    Err(err) => err.to_compile_error().into()
+  }
+}
+
+fn bundle_impl(input:&DeriveInput) -> syn::Result<TokenStream2> {
+  if let Some(lifetime) = input.generics.lifetimes().next() {
+    return Err(Error::new(
+      lifetime.span(),
+      "Bundle fields must be 'static. Components cannot borrow data with a non-'static lifetime"
+    ));
+  }
+
+  let fields = match &input.data {
+    Data::Struct(data) => match &data.fields {
+      Fields::Named(fields) => &fields.named,
+      _ => return Err(Error::new(input.ident.span(), "Bundle can only be derived for structs with named fields"))
+    },
+    _ => return Err(Error::new(input.ident.span(), "Bundle can only be derived for structs with named fields"))
+  };
+
+  let mut names = Vec::new();
+  let mut lengths = Vec::new();
+  let mut puts = Vec::new();
+  let mut takes = Vec::new();
+  let mut types = Vec::new();
+  let mut generics = input.generics.clone();
+  let bounds = generics.make_where_clause();
+
+  for field in fields {
+    let name = field.ident.as_ref().unwrap();
+    let ty = &field.ty;
+    let nested = field.attrs.iter().any(|attr| attr.path().is_ident("bundle"));
+    names.push(name);
+
+    if nested {
+      bounds.predicates.push(parse_quote_spanned!(ty.span()=> #ty: ::nina::storage::Bundle));
+      lengths.push(quote!(<#ty as ::nina::storage::Bundle>::LENGTH));
+      puts.push(quote!(<#ty as ::nina::storage::Bundle>::put(#name, &mut f)?;));
+      takes.push(quote!(let #name = <#ty as ::nina::storage::Bundle>::take(&mut f)?;));
+      types.push(quote!(types.extend(<#ty as ::nina::storage::Bundle>::types());));
+    } else {
+      bounds.predicates.push(parse_quote_spanned!(ty.span()=> #ty: ::nina::storage::EcsData));
+      lengths.push(quote!(1));
+      puts.push(quote! {
+        let mut #name = ::core::mem::ManuallyDrop::new(#name);
+        f((&mut *#name as *mut #ty).cast::<u8>(), ::nina::storage::TypeInfo::of::<#ty>())?;
+      });
+      takes.push(quote! {
+        let mut #name = ::core::mem::MaybeUninit::<#ty>::uninit();
+        f(#name.as_mut_ptr().cast::<u8>(), ::nina::storage::TypeInfo::of::<#ty>())?;
+        let #name = #name.assume_init();
+      });
+      types.push(quote!(types.push(::nina::storage::TypeInfo::of::<#ty>());));
+    }
+  }
+
+  let ident = &input.ident;
+  let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+  Ok(quote! {
+    impl #impl_generics ::nina::storage::Bundle for #ident #ty_generics #where_clause {
+      const LENGTH:usize = 0 #(+ #lengths)*;
+
+      #[allow(unused_mut)]
+      unsafe fn put(
+        self,
+        mut f:impl FnMut(*mut u8, ::nina::storage::TypeInfo) -> ::nina::__private::Result<()>
+      ) -> ::nina::__private::Result<()> {
+        let Self { #(#names),* } = self;
+        #(#puts)*
+        ::core::result::Result::Ok(())
+      }
+
+      #[allow(unused_mut)]
+      unsafe fn take(
+        mut f:impl FnMut(*mut u8, ::nina::storage::TypeInfo) -> ::nina::__private::Result<()>
+      ) -> ::nina::__private::Result<Self> {
+        #(#takes)*
+        ::core::result::Result::Ok(Self { #(#names),* })
+      }
+
+      fn types() -> ::std::vec::Vec<::nina::storage::TypeInfo> {
+        let mut types = ::std::vec::Vec::with_capacity(<Self as ::nina::storage::Bundle>::LENGTH);
+        #(#types)*
+        types
+      }
+    }
+  })
+}
@@ -1,8 +1,23 @@
 use thiserror::Error;
 
 // Refactor:
-// -Should CreateComponentNeverCalled and ComponentNotRegistered be merged? If
-// not ensure ComponentNotRegistered also shows the component type.
+// -Should CreateComponentNeverCalled and ComponentNotRegistered be merged?
+
+/// A [`Result`](std::result::Result) whose error defaults to [`NinaError`].
+pub type Result<T, E = NinaError> = std::result::Result<T, E>;
+
+/// Every error returned by the crate's fallible APIs.
+///
+/// Match on the wrapped enums to handle specific failures.
+#[derive(Debug, Error)]
+pub enum NinaError {
+  #[error(transparent)]
+  TypeInfo(#[from] TypeInfoErrors),
+  #[error(transparent)]
+  ErasedVec(#[from] ErasedVecErrors),
+  #[error(transparent)]
+  Ecs(#[from] EcsErrors)
+}
 
 #[derive(Error, Debug)]
 pub enum TypeInfoErrors {
@@ -16,8 +31,16 @@ pub enum ErasedVecErrors {
   DoesNotContainType(String),
   #[error("Cannot insert type {insert_type:?} into vector of type {vec_type:?}.")]
   IncorrectTypeInsertion { insert_type:String, vec_type:String },
-  #[error("Vector len is {len:?}. Cannot insert into {index:?}.")]
+  #[error("Vector len is {len:?}. Cannot access index {index:?} past the end.")]
   IndexOutOfBounds { len:usize, index:usize },
+  #[error("Vector len is {len:?}. Cannot insert into {index:?} past the end.")]
+  InsertOutOfBounds { len:usize, index:usize },
+  #[error("Vector of type {ty:?} has an unfilled slot at {index:?}. Sparse columns cannot be viewed as a slice.")]
+  SparseColumn { ty:String, index:usize },
+  #[error("Vector of type {0:?} cannot be cloned. Register it as cloneable.")]
+  NotCloneable(String),
+  #[error("Slot {index:?} holds no value.")]
+  UnfilledSlot { index:usize },
   #[error("Allocation too large")]
   ErasedVecAllocError,
   #[error("Capacity overflow")]
@@ -28,14 +51,36 @@ pub enum ErasedVecErrors {
 pub enum EcsErrors {
   #[error("Attempting to add {component:?} to an entitity without registering it first!")]
   CreateComponentNeverCalled { component:String },
-  #[error("Attempted to use an unregisted component")]
-  ComponentNotRegistered,
-  #[error("Attempted to reference an entity that does not exist")]
-  EntityDoesNotExist,
+  #[error("Attempted to use the unregistered component {component:?}")]
+  ComponentNotRegistered { component:String },
+  #[error("Attempted to reference entity \"{entity}\" which does not exist")]
+  EntityDoesNotExist { entity:usize },
   #[error("Attempted to access {component:?} which does not exist")]
   ResourceDataDoesNotExist { component:String },
   #[error("Attempted to use component data that does not exist. Entity \"{entity}\" does not contain a component of type \"{ty}\".")]
   ComponentDataDoesNotExist { entity:usize, ty:String },
+  #[error("Conflicting borrows of \"{ty}\". A mutable borrow cannot be held alongside any other borrow of the same type")]
+  ConflictingAccess { ty:String },
+  #[error("Entity \"{entity}\"'s bitmask and the storage for \"{ty}\" disagree on whether it holds the component")]
+  InconsistentStorage { entity:usize, ty:String },
+  #[error(
+    "Component \"{component}\" is not stored densely. Register it with `World::register_component_dense` or `World::register_component_sparse`"
+  )]
+  NotDense { component:String },
+  #[error("Expected exactly one entity with \"{component}\" but found {count}")]
+  ExpectedSingleEntity { component:String, count:usize },
+  #[error("Component \"{component}\" is not registered for serialization. Register it with `World::register_serde`")]
+  SerdeNotRegistered { component:String },
+  #[error("Entity {entity} has the unknown component \"{name}\". Register it with `World::register_serde`")]
+  UnknownComponentName { name:String, entity:usize },
+  #[error("The diffed bytes of \"{name}\" do not match a registered Copy component of the same size")]
+  InvalidComponentDiff { name:String },
+  #[error("Invalid scene: {message}")]
+  InvalidScene { message:String },
+  #[error("No prefab named \"{name}\" was registered. Register it with `World::register_prefab`")]
+  PrefabDoesNotExist { name:String },
+  #[error("The ordering constraints of systems \"{systems}\" are cyclic")]
+  SystemOrderCycle { systems:String },
   #[error("Attempted to downcast component to the wrong type")]
   DowncastToWrongType,
   #[error("No resource found at given path")]
@@ -43,3 +88,21 @@ pub enum EcsErrors {
   #[error("Unable to read the exe at the given path")]
   ExeResourceRegistrationFailed
 }
+
+/// A storage invariant found broken by
+/// [`World::validate`](crate::world::World::validate).
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum IntegrityError {
+  #[error("The storage of \"{ty}\" has {len} slots but the world has {entities} entities")]
+  ColumnLength { ty:String, len:usize, entities:usize },
+  #[error("Entity \"{entity}\"'s bitmask holds \"{ty}\" but its slot is empty")]
+  MissingComponent { entity:usize, ty:String },
+  #[error("Entity \"{entity}\"'s slot holds \"{ty}\" but its bitmask does not")]
+  UntrackedComponent { entity:usize, ty:String },
+  #[error("Entity \"{entity}\" is both reserved and free")]
+  ReservedAndFree { entity:usize },
+  #[error("The bitmask of \"{ty}\" does not have exactly one bit set")]
+  InvalidBitmask { ty:String },
+  #[error("\"{ty}\" and \"{other}\" share a bitmask")]
+  SharedBitmask { ty:String, other:String }
+}
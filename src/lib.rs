@@ -14,20 +14,34 @@
 //!    .unwrap()
 //!    .with_component(-5)
 //!    .unwrap()
-//!    .with_component(100.0_f32)
+//!    .with_components((100.0_f32, 'a'))
+//!    .unwrap()
+//!    .with_component("name")
 //!    .unwrap();
+//!
+//!  assert_eq!(*world.get_component::<char>(0).unwrap(), 'a');
 //!  ```
 
 //Add to crate attributes?
-#![feature(ptr_alignment_type)]
-#![feature(unchecked_math)]
-#![feature(slice_index_methods)]
 #![allow(dead_code)]
 
-mod errors;
+// Lets `nina-derive` name the crate as `::nina` from inside it too.
+extern crate self as nina;
+
+pub mod errors;
 pub mod storage;
 pub mod world;
 
+// Fallible APIs return `errors::NinaError`, which converts into an
+// `eyre::Report` with `?`.
+#[cfg(feature = "eyre")]
+pub use eyre;
+
+#[doc(hidden)]
+pub mod __private {
+  pub use crate::errors::Result;
+}
+
 // Refactor
 // -Fix the crate imports
 // -I think it's worth moving Arena into storage
@@ -0,0 +1,176 @@
+use smallvec::SmallVec;
+
+const BLOCK_BITS:usize = u64::BITS as usize;
+
+///A growable set of bits used to track which components an entity holds.
+///
+/// The first 128 bits are stored inline so worlds with fewer component types
+/// than that never allocate. Setting a bit past the end grows the mask.
+#[derive(Debug, Clone, Default)]
+pub struct Bitmask {
+  blocks:SmallVec<[u64; 2]>
+}
+
+impl Bitmask {
+  ///Constructs a new, empty [`Bitmask`].
+  pub fn new() -> Self {
+    Bitmask { blocks:SmallVec::new() }
+  }
+
+  ///Constructs a [`Bitmask`] with only `bit` set.
+  pub fn from_bit(bit:usize) -> Self {
+    let mut mask = Bitmask::new();
+    mask.set(bit);
+    mask
+  }
+
+  ///Sets `bit`, growing the mask if needed.
+  pub fn set(&mut self, bit:usize) {
+    let (block, offset) = Self::locate(bit);
+    if block >= self.blocks.len() {
+      self.blocks.resize(block + 1, 0);
+    }
+    self.blocks[block] |= 1 << offset;
+  }
+
+  ///Unsets `bit`.
+  pub fn unset(&mut self, bit:usize) {
+    let (block, offset) = Self::locate(bit);
+    if let Some(block) = self.blocks.get_mut(block) {
+      *block &= !(1 << offset);
+    }
+  }
+
+  ///Returns `true` if `bit` is set.
+  pub fn is_set(&self, bit:usize) -> bool {
+    let (block, offset) = Self::locate(bit);
+    match self.blocks.get(block) {
+      Some(block) => block & (1 << offset) != 0,
+      None => false
+    }
+  }
+
+  ///Returns `true` if no bits are set.
+  pub fn is_empty(&self) -> bool {
+    self.blocks.iter().all(|block| *block == 0)
+  }
+
+  ///Unsets every bit.
+  pub fn clear(&mut self) {
+    self.blocks.iter_mut().for_each(|block| *block = 0);
+  }
+
+  ///Sets every bit which is set in `other`.
+  pub fn insert(&mut self, other:&Bitmask) {
+    if other.blocks.len() > self.blocks.len() {
+      self.blocks.resize(other.blocks.len(), 0);
+    }
+    for (block, other) in self.blocks.iter_mut().zip(other.blocks.iter()) {
+      *block |= other;
+    }
+  }
+
+  ///Unsets every bit which is set in `other`.
+  pub fn remove(&mut self, other:&Bitmask) {
+    for (block, other) in self.blocks.iter_mut().zip(other.blocks.iter()) {
+      *block &= !other;
+    }
+  }
+
+  ///Returns `true` if every bit set in `other` is also set in `self`.
+  pub fn contains(&self, other:&Bitmask) -> bool {
+    other
+      .blocks
+      .iter()
+      .enumerate()
+      .all(|(index, other)| self.blocks.get(index).copied().unwrap_or(0) & other == *other)
+  }
+
+  ///Returns `true` if `self` and `other` share at least one set bit.
+  pub fn intersects(&self, other:&Bitmask) -> bool {
+    self.blocks.iter().zip(other.blocks.iter()).any(|(block, other)| block & other != 0)
+  }
+
+  ///Iterates over the set bits in ascending order.
+  pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+    self.blocks.iter().enumerate().flat_map(|(index, block)| {
+      (0..BLOCK_BITS)
+        .filter(move |offset| block & (1 << offset) != 0)
+        .map(move |offset| index * BLOCK_BITS + offset)
+    })
+  }
+
+  ///Returns the block index and bit offset of `bit`.
+  fn locate(bit:usize) -> (usize, usize) {
+    (bit / BLOCK_BITS, bit % BLOCK_BITS)
+  }
+}
+
+impl PartialEq for Bitmask {
+  fn eq(&self, other:&Self) -> bool {
+    // Masks of different lengths are equal if the extra blocks are empty
+    let len = self.blocks.len().max(other.blocks.len());
+    (0..len).all(|index| self.blocks.get(index).copied().unwrap_or(0) == other.blocks.get(index).copied().unwrap_or(0))
+  }
+}
+
+impl Eq for Bitmask {}
+
+impl FromIterator<usize> for Bitmask {
+  ///Constructs a [`Bitmask`] with every bit yielded by the iterator set.
+  fn from_iter<I:IntoIterator<Item = usize>>(iter:I) -> Self {
+    let mut mask = Bitmask::new();
+    iter.into_iter().for_each(|bit| mask.set(bit));
+    mask
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::Bitmask;
+
+  #[test]
+  fn set_and_unset_bits() {
+    let mut mask = Bitmask::new();
+    assert!(mask.is_empty());
+
+    mask.set(3);
+    mask.set(200);
+    assert!(mask.is_set(3));
+    assert!(mask.is_set(200));
+    assert!(!mask.is_set(4));
+    assert!(!mask.is_set(1000));
+
+    mask.unset(200);
+    assert!(!mask.is_set(200));
+    assert_eq!(mask, Bitmask::from_bit(3));
+
+    mask.clear();
+    assert!(mask.is_empty());
+  }
+
+  #[test]
+  fn compare_masks_across_the_128_bit_boundary() {
+    let low = Bitmask::from_iter([0, 127]);
+    let high = Bitmask::from_iter([0, 127, 128]);
+
+    assert!(high.contains(&low));
+    assert!(!low.contains(&high));
+    assert!(low.intersects(&high));
+    assert!(!low.intersects(&Bitmask::from_bit(128)));
+
+    let mut combined = low.clone();
+    combined.insert(&Bitmask::from_bit(128));
+    assert_eq!(combined, high);
+
+    combined.remove(&Bitmask::from_bit(128));
+    assert_eq!(combined, low);
+  }
+
+  #[test]
+  fn iterate_set_bits() {
+    let mask = Bitmask::from_iter([130, 0, 64, 3]);
+    assert_eq!(mask.iter().collect::<Vec<_>>(), vec![0, 3, 64, 130]);
+    assert_eq!(Bitmask::new().iter().count(), 0);
+  }
+}
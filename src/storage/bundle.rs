@@ -1,24 +1,161 @@
 use super::type_info::TypeInfo;
-use super::EcsData;
-use eyre::Result;
+use super::{EcsData, ErasedBox};
+use crate::errors::Result;
 use std::mem;
 
+#[cfg(feature = "derive")]
+pub use nina_derive::Bundle;
+
 // Refactor:
 // There 100% must be a better way to do the num macro
 
 ///An arbitrary tuple of [`EcsData`].
+///
+/// Structs with named fields can implement it with `#[derive(Bundle)]` when
+/// the `derive` feature is enabled.
+///
+/// Every element of a plain tuple is a component, an inner bundle is stored
+/// as a single component of its own type. Wrap a tuple of bundles in
+/// [`Nested`] to flatten them, or mark the nested fields of a derived bundle
+/// `#[bundle]`.
 pub trait Bundle {
   ///Stores the number of items in the [`Bundle`].
   const LENGTH:usize;
 
   ///Takes a callback that moves components out of the bundle one-by-one.
+  ///
+  /// # Safety
+  /// - The callback takes ownership of each component. It must move the data
+  ///   out of the pointer before returning as the bundle will not drop it.
   unsafe fn put(self, f:impl FnMut(*mut u8, TypeInfo) -> Result<()>) -> Result<()>;
 
+  ///Builds the bundle from a callback that writes components into it
+  /// one-by-one, mirroring [`Bundle::put`].
+  ///
+  /// # Safety
+  /// - Each call which returns `Ok` must have written a valid value of type
+  ///   `ty` to the pointer.
+  /// - Components written before a call returns an error are leaked.
+  unsafe fn take(f:impl FnMut(*mut u8, TypeInfo) -> Result<()>) -> Result<Self>
+  where Self: Sized;
+
   ///Returns a [`Vec`] containing the [`TypeInfo`] of all the components in the
   /// bundle.
   fn types() -> Vec<TypeInfo>;
 }
 
+///A set of components which can be moved into storage one-by-one.
+///
+/// Implemented for every [`Bundle`] and for [`DynamicBundle`], whose
+/// component types are only known at runtime.
+pub trait ErasedBundle {
+  ///Returns the [`TypeInfo`] of all the components in the bundle.
+  fn component_types(&self) -> Vec<TypeInfo>;
+
+  ///Takes a callback that moves components out of the bundle one-by-one.
+  ///
+  /// # Safety
+  /// - The callback takes ownership of each component. It must move the data
+  ///   out of the pointer before returning as the bundle will not drop it.
+  unsafe fn put_erased(self, f:impl FnMut(*mut u8, TypeInfo) -> Result<()>) -> Result<()>;
+}
+
+impl<B:Bundle> ErasedBundle for B {
+  fn component_types(&self) -> Vec<TypeInfo> {
+    B::types()
+  }
+
+  unsafe fn put_erased(self, f:impl FnMut(*mut u8, TypeInfo) -> Result<()>) -> Result<()> {
+    self.put(f)
+  }
+}
+
+///A growable bundle of components whose types are chosen at runtime.
+///
+/// ```
+/// # use nina::{storage::DynamicBundle, world::World};
+/// let mut world = World::new();
+/// let mut bundle = DynamicBundle::new();
+/// bundle.push(1_u32).push('a');
+/// let entity = world.spawn(bundle).unwrap();
+/// assert_eq!(*world.get_component::<char>(entity).unwrap(), 'a');
+/// ```
+#[derive(Default)]
+pub struct DynamicBundle {
+  components:Vec<ErasedBox>
+}
+
+impl DynamicBundle {
+  pub fn new() -> Self {
+    DynamicBundle::default()
+  }
+
+  ///Adds a component to the bundle.
+  pub fn push<T:EcsData + Send>(&mut self, value:T) -> &mut Self {
+    let mut value = mem::ManuallyDrop::new(value);
+    let ptr = (&mut *value as *mut T).cast::<u8>();
+    self.components.push(ErasedBox::from_raw_parts(TypeInfo::of::<T>(), ptr));
+    self
+  }
+
+  ///Adds a type-erased component to the bundle.
+  ///
+  /// `ty.size()` bytes are copied from `ptr` into a buffer owned by the
+  /// bundle so `ptr` does not need to outlive the call.
+  ///
+  /// # Ownership
+  ///
+  /// The bundle takes ownership of the value. The caller must not drop the
+  /// source afterwards, i.e. `mem::forget` it. If the bundle is dropped
+  /// without being inserted, the value is dropped with it.
+  ///
+  /// The value's type must be [`Send`] since the bundle may be sent to
+  /// another thread.
+  pub fn push_erased(&mut self, ty:TypeInfo, ptr:*mut u8) -> &mut Self {
+    self.components.push(ErasedBox::from_raw_parts(ty, ptr));
+    self
+  }
+
+  ///Adds a boxed type-erased component to the bundle.
+  ///
+  /// The value's type must be [`Send`] since the bundle may be sent to
+  /// another thread.
+  pub fn push_boxed(&mut self, component:ErasedBox) -> &mut Self {
+    self.components.push(component);
+    self
+  }
+
+  ///Returns the number of components in the bundle.
+  pub fn len(&self) -> usize {
+    self.components.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.components.is_empty()
+  }
+}
+
+// Components can only be pushed if they are `Send` or the caller of
+// `push_erased` promises so
+unsafe impl Send for DynamicBundle {}
+
+impl ErasedBundle for DynamicBundle {
+  fn component_types(&self) -> Vec<TypeInfo> {
+    self.components.iter().map(|component| component.ty()).collect()
+  }
+
+  unsafe fn put_erased(self, mut f:impl FnMut(*mut u8, TypeInfo) -> Result<()>) -> Result<()> {
+    // Components left after an error are dropped with the iterator
+    for component in self.components {
+      let result = f(component.ptr(), component.ty());
+      // The callback owns the value now
+      component.forget();
+      result?;
+    }
+    Ok(())
+  }
+}
+
 macro_rules! impl_tuple {
   ($($name:ident),*) => {
     impl<$($name:EcsData),*> Bundle for ($($name,)*) {
@@ -55,6 +192,16 @@ macro_rules! impl_tuple {
       }
 
       #[allow(unused_variables, unused_mut)]
+      unsafe fn take(mut f: impl FnMut(*mut u8, TypeInfo) -> Result<()>) -> Result<Self>{
+        $(
+          #[allow(non_snake_case)]
+          let mut $name = mem::MaybeUninit::<$name>::uninit();
+          f($name.as_mut_ptr().cast::<u8>(), TypeInfo::of::<$name>())?;
+        )*
+        Ok(($($name.assume_init(),)*))
+      }
+
+      #[allow(unused_variables, unused_mut, clippy::vec_init_then_push)]
       fn types()->Vec<TypeInfo>{
         let mut types = Vec::new();
         $(
@@ -66,6 +213,51 @@ macro_rules! impl_tuple {
   };
 }
 
+///A tuple of [`Bundle`]s flattened into one bundle, in order.
+///
+/// Loose components are passed as one element tuples.
+///
+/// ```
+/// # use nina::{storage::Nested, world::World};
+/// let mut world = World::new();
+/// let entity = world.spawn(Nested(((1_u32, 'a'), (true,)))).unwrap();
+/// assert_eq!(*world.get_component::<char>(entity).unwrap(), 'a');
+/// assert!(*world.get_component::<bool>(entity).unwrap());
+/// ```
+pub struct Nested<B>(pub B);
+
+macro_rules! impl_nested {
+  ($($name:ident),*) => {
+    impl<$($name:Bundle),*> Bundle for Nested<($($name,)*)> {
+      const LENGTH:usize = 0 $(+ $name::LENGTH)*;
+
+      #[allow(unused_variables, unused_mut)]
+      unsafe fn put(self, mut f: impl FnMut(*mut u8, TypeInfo) -> Result<()>) -> Result<()>{
+        #[allow(non_snake_case)]
+        let ($($name,)*) = self.0;
+        $(
+          $name.put(&mut f)?;
+        )*
+        Ok(())
+      }
+
+      #[allow(unused_variables, unused_mut)]
+      unsafe fn take(mut f: impl FnMut(*mut u8, TypeInfo) -> Result<()>) -> Result<Self>{
+        Ok(Nested(($($name::take(&mut f)?,)*)))
+      }
+
+      #[allow(unused_mut)]
+      fn types()->Vec<TypeInfo>{
+        let mut types = Vec::new();
+        $(
+          types.extend($name::types());
+        )*
+        types
+      }
+    }
+  };
+}
+
 macro_rules! count_items {
   () => { 0 };
   ($first:ident $(, $rest:ident)*) => { 1 + count_items!($($rest),*) };
@@ -92,10 +284,15 @@ macro_rules! reverse_apply {
 }
 
 smaller_tuples_too!(impl_tuple, O, N, M, L, K, J, I, H, G, F, E, D, C, B, A);
+smaller_tuples_too!(impl_nested, O, N, M, L, K, J, I, H, G, F, E, D, C, B, A);
 
 #[cfg(test)]
 mod tests {
-  use crate::storage::{Bundle, TypeInfo};
+  use crate::{
+    storage::{Bundle, DynamicBundle, ErasedBundle, Nested, TypeInfo},
+    world::{command_buffer::CommandBuffer, World}
+  };
+  use std::{mem, sync::Arc};
 
   #[test]
   fn get_type_info_from_bundle() {
@@ -116,4 +313,153 @@ mod tests {
     let num_1 = <(u32, f32, String, u32, f32, String, u32, f32, String, u32, f32, String, f32)>::LENGTH;
     assert_eq!(num_1, 13);
   }
+
+  struct Health(u32);
+  struct Position(f32, f32);
+  struct Velocity(f32, f32);
+
+  #[derive(nina_derive::Bundle)]
+  struct MotionBundle {
+    pos:Position,
+    vel:Velocity
+  }
+
+  #[derive(nina_derive::Bundle)]
+  struct PlayerBundle {
+    health:Health,
+    #[bundle]
+    motion:MotionBundle,
+    name:String
+  }
+
+  #[test]
+  fn derived_bundle_flattens_fields_in_declaration_order() {
+    assert_eq!(PlayerBundle::LENGTH, 4);
+    assert_eq!(
+      PlayerBundle::types(),
+      vec![
+        TypeInfo::of::<Health>(),
+        TypeInfo::of::<Position>(),
+        TypeInfo::of::<Velocity>(),
+        TypeInfo::of::<String>()
+      ]
+    );
+  }
+
+  fn dynamic_bundle() -> DynamicBundle {
+    let mut bundle = DynamicBundle::new();
+    let mut name = mem::ManuallyDrop::new("Nina".to_string());
+    bundle
+      .push(Health(100))
+      .push(Position(1.0, 2.0))
+      .push_erased(TypeInfo::of::<String>(), (&mut *name as *mut String).cast());
+    bundle
+  }
+
+  #[test]
+  fn spawn_dynamic_bundle() {
+    let bundle = dynamic_bundle();
+    assert_eq!(bundle.len(), 3);
+    assert_eq!(
+      bundle.component_types(),
+      vec![TypeInfo::of::<Health>(), TypeInfo::of::<Position>(), TypeInfo::of::<String>()]
+    );
+
+    let mut world = World::new();
+    let spawned = world.spawn(bundle).unwrap();
+    let added = world.reserve_entity();
+    world.add_components(added, dynamic_bundle()).unwrap();
+
+    let mut buffer = CommandBuffer::new();
+    let buffered = buffer.spawn_entity(&world, dynamic_bundle());
+    buffer.run(&mut world);
+
+    for entity in [spawned, added, buffered] {
+      assert_eq!(world.get_component::<Health>(entity).unwrap().0, 100);
+      assert_eq!(world.get_component::<Position>(entity).unwrap().0, 1.0);
+      assert_eq!(world.get_component::<String>(entity).unwrap(), "Nina");
+    }
+  }
+
+  #[test]
+  fn dropping_a_dynamic_bundle_drops_its_components() {
+    let counted = Arc::new(());
+    let mut bundle = DynamicBundle::new();
+    bundle.push(counted.clone());
+    assert_eq!(Arc::strong_count(&counted), 2);
+
+    drop(bundle);
+    assert_eq!(Arc::strong_count(&counted), 1);
+  }
+
+  struct Extra(u8);
+
+  #[test]
+  fn nested_tuples_flatten_their_bundles() {
+    type Flat = (Health, Position, Velocity, String, Extra);
+    type Composed = Nested<(PlayerBundle, (Extra,))>;
+    assert_eq!(Composed::types(), Flat::types());
+    assert_eq!(Composed::LENGTH, Flat::LENGTH);
+
+    let mut world = World::new();
+    let player = PlayerBundle {
+      health:Health(100),
+      motion:MotionBundle {
+        pos:Position(1.0, 2.0),
+        vel:Velocity(0.5, 0.0)
+      },
+      name:"Nina".to_string()
+    };
+    let entity = world.spawn(Nested((player, (Extra(7),)))).unwrap();
+    assert_eq!(world.get_component::<Health>(entity).unwrap().0, 100);
+    assert_eq!(world.get_component::<Position>(entity).unwrap().1, 2.0);
+    assert_eq!(world.get_component::<Velocity>(entity).unwrap().0, 0.5);
+    assert_eq!(world.get_component::<String>(entity).unwrap(), "Nina");
+    assert_eq!(world.get_component::<Extra>(entity).unwrap().0, 7);
+
+    let Nested((player, (extra,))) = world.take_bundle::<Composed>(entity).unwrap();
+    assert_eq!((player.name.as_str(), extra.0), ("Nina", 7));
+    assert!(world.get_component::<Health>(entity).is_err());
+  }
+
+  #[test]
+  fn take_derived_bundle() {
+    let mut world = World::new();
+    let entity = world
+      .spawn(PlayerBundle {
+        health:Health(100),
+        motion:MotionBundle {
+          pos:Position(1.0, 2.0),
+          vel:Velocity(0.5, 0.0)
+        },
+        name:"Nina".to_string()
+      })
+      .unwrap();
+
+    let player = world.take_bundle::<PlayerBundle>(entity).unwrap();
+    assert_eq!(player.health.0, 100);
+    assert_eq!(player.motion.vel.0, 0.5);
+    assert_eq!(player.name, "Nina");
+    assert!(world.get_component::<Position>(entity).is_err());
+  }
+
+  #[test]
+  fn spawn_derived_bundle() {
+    let mut world = World::new();
+    let entity = world
+      .spawn(PlayerBundle {
+        health:Health(100),
+        motion:MotionBundle {
+          pos:Position(1.0, 2.0),
+          vel:Velocity(0.5, 0.0)
+        },
+        name:"Nina".to_string()
+      })
+      .unwrap();
+
+    assert_eq!(world.get_component::<Health>(entity).unwrap().0, 100);
+    assert_eq!(world.get_component::<Position>(entity).unwrap().1, 2.0);
+    assert_eq!(world.get_component::<Velocity>(entity).unwrap().0, 0.5);
+    assert_eq!(world.get_component::<String>(entity).unwrap(), "Nina");
+  }
 }
@@ -0,0 +1,604 @@
+use super::{
+  erased_collections::ErasedVec,
+  ticks::{ComponentTicks, Tick},
+  type_info::TypeInfo
+};
+use crate::{
+  errors::{EcsErrors, ErasedVecErrors, Result},
+  world::entities::Entity
+};
+use std::{fmt, mem};
+
+/// How a [`ComponentStorage`] lays out its components.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StorageKind {
+  /// Each entity owns the row matching its id. The rows of entities without
+  /// the component are padded.
+  #[default]
+  Table,
+  /// Rows are handed out as entities gain the component. Removing a
+  /// component leaves a hole until [`ComponentStorage::compact`] closes it.
+  Dense,
+  /// A sparse set. Rows are handed out like [`StorageKind::Dense`] but
+  /// removing a component moves the last row into its place right away,
+  /// which suits components added and removed every few frames.
+  Sparse
+}
+
+/// The storage of a single component type, indexed by [`Entity`].
+///
+/// Wraps an [`ErasedVec`] and maps each entity to the row holding its
+/// component according to the storage's [`StorageKind`].
+pub struct ComponentStorage {
+  data:ErasedVec,
+  kind:StorageKind,
+  /// The row of each entity in a dense storage, `None` if the entity has no
+  /// row.
+  rows:Vec<Option<usize>>,
+  /// The entity owning each row of a dense storage.
+  entities:Vec<Entity>
+}
+
+impl ComponentStorage {
+  /// Constructs a new, empty [`ComponentStorage`] holding the type described
+  /// by `ty`.
+  pub fn new_erased(ty:TypeInfo, kind:StorageKind) -> Self {
+    ComponentStorage {
+      data:ErasedVec::new_erased(ty),
+      kind,
+      rows:Vec::new(),
+      entities:Vec::new()
+    }
+  }
+
+  pub fn ty(&self) -> TypeInfo {
+    self.data.ty()
+  }
+
+  pub fn kind(&self) -> StorageKind {
+    self.kind
+  }
+
+  /// Returns the rows holding the components.
+  ///
+  /// Rows are indexed by entity in table storages. In dense storages
+  /// [`Self::row_entities`] names the entity owning each row.
+  pub fn data(&self) -> &ErasedVec {
+    &self.data
+  }
+
+  /// Returns the entity owning each row of a dense or sparse storage, or
+  /// `None` for table storages.
+  pub fn row_entities(&self) -> Option<&[Entity]> {
+    match self.kind {
+      StorageKind::Table => None,
+      StorageKind::Dense | StorageKind::Sparse => Some(&self.entities)
+    }
+  }
+
+  /// Returns the number of entity slots in the storage.
+  pub fn len(&self) -> usize {
+    match self.kind {
+      StorageKind::Table => self.data.len(),
+      StorageKind::Dense | StorageKind::Sparse => self.rows.len()
+    }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  /// Returns the number of rows the storage can hold without reallocating.
+  pub fn capacity(&self) -> usize {
+    self.data.capacity()
+  }
+
+  /// Returns the number of entities whose slot holds a value.
+  pub fn filled_count(&self) -> usize {
+    self.data.filled_count()
+  }
+
+  /// Returns the number of bytes allocated for the rows, see
+  /// [`ErasedVec::allocated_bytes`], and for the entity to row maps of dense
+  /// and sparse storages.
+  pub fn allocated_bytes(&self) -> usize {
+    self.data.allocated_bytes() + self.rows.capacity() * mem::size_of::<Option<usize>>() + self.entities.capacity() * mem::size_of::<Entity>()
+  }
+
+  /// Returns the row holding the entity's component, if it has one.
+  pub fn row(&self, entity:Entity) -> Option<usize> {
+    match self.kind {
+      StorageKind::Table => (entity < self.data.len()).then_some(entity),
+      StorageKind::Dense | StorageKind::Sparse => self.rows.get(entity).copied().flatten()
+    }
+  }
+
+  /// Returns the row holding the entity's component.
+  ///
+  /// # Panics
+  /// - Panics if the entity has no row.
+  fn expect_row(&self, entity:Entity) -> usize {
+    self
+      .row(entity)
+      .unwrap_or_else(|| panic!("{}", EcsErrors::ComponentDataDoesNotExist { entity, ty:self.ty().name() }))
+  }
+
+  /// See [`ErasedVec::tracks_filled`].
+  pub fn tracks_filled(&self) -> bool {
+    self.data.tracks_filled()
+  }
+
+  /// Returns `true` if the entity's slot holds a value.
+  pub fn is_filled(&self, entity:Entity) -> bool {
+    self.row(entity).is_some_and(|row| self.data.is_filled(row))
+  }
+
+  /// Fetches the entity's component.
+  ///
+  /// Returns `None` if the slot is out of bounds or not filled.
+  pub fn try_get<T:'static>(&self, entity:Entity) -> Option<&T> {
+    self.data.try_get(self.row(entity)?)
+  }
+
+  /// Mutably fetches the entity's component, marking it as changed.
+  ///
+  /// Returns `None` if the slot is out of bounds or not filled.
+  pub fn try_get_mut<T:'static>(&self, entity:Entity) -> Option<&mut T> {
+    self.data.try_get_mut(self.row(entity)?)
+  }
+
+  /// Fetches the entity's component.
+  ///
+  /// # Panics
+  /// - Panics if the entity has no row.
+  pub fn get<T:'static>(&self, entity:Entity) -> &T {
+    self.data.get(self.expect_row(entity))
+  }
+
+  /// Mutably fetches the entity's component, marking it as changed.
+  ///
+  /// # Panics
+  /// - Panics if the entity has no row.
+  pub fn get_mut<T:'static>(&self, entity:Entity) -> &mut T {
+    self.data.get_mut(self.expect_row(entity))
+  }
+
+  /// Returns a pointer to the entity's component.
+  ///
+  /// # Safety
+  /// - The entity must have a row, see [`ErasedVec::indexed_ptr`].
+  pub unsafe fn indexed_ptr<T:'static>(&self, entity:Entity) -> *mut T {
+    match self.kind {
+      StorageKind::Table => self.data.indexed_ptr(entity),
+      StorageKind::Dense | StorageKind::Sparse => self.data.indexed_ptr(self.rows[entity].unwrap_unchecked())
+    }
+  }
+
+  /// Returns an iterator over every entity slot. Slots without a value are
+  /// yielded as `None`.
+  pub fn iter<T:'static>(&self) -> impl Iterator<Item = Option<&T>> + '_ {
+    (0..self.len()).map(|entity| self.try_get(entity))
+  }
+
+  /// Returns an iterator over every entity slot which yields mutable
+  /// references. Slots without a value are yielded as `None`.
+  ///
+  /// Every filled slot is marked as changed.
+  pub fn iter_mut<T:'static>(&mut self) -> impl Iterator<Item = Option<&mut T>> + '_ {
+    // Each entity owns a distinct row so the references never alias
+    let this = &*self;
+    (0..this.len()).map(move |entity| this.try_get_mut(entity))
+  }
+
+  /// Returns the tick stamped on slots as they are written.
+  pub fn tick(&self) -> Tick {
+    self.data.tick()
+  }
+
+  /// Sets the tick stamped on slots as they are written.
+  pub fn set_tick(&mut self, tick:Tick) {
+    self.data.set_tick(tick);
+  }
+
+  /// Returns the [`ComponentTicks`] of the entity's slot.
+  ///
+  /// # Panics
+  /// - Panics if the entity has no row.
+  pub fn ticks(&self, entity:Entity) -> &ComponentTicks {
+    self.data.ticks(self.expect_row(entity))
+  }
+
+  /// Records a change to the entity's component at the current tick.
+  ///
+  /// # Panics
+  /// - Panics if the entity has no row.
+  pub fn mark_changed(&self, entity:Entity) {
+    self.data.mark_changed(self.expect_row(entity));
+  }
+
+  /// Reserves room for at least `additional` more entities.
+  pub fn reserve_exact(&mut self, additional:usize) {
+    match self.kind {
+      StorageKind::Table => self.data.reserve_exact(additional),
+      StorageKind::Dense | StorageKind::Sparse => self.rows.reserve_exact(additional)
+    }
+  }
+
+  /// Appends an empty slot for a new entity.
+  pub fn pad(&mut self) {
+    match self.kind {
+      StorageKind::Table => self.data.pad(),
+      StorageKind::Dense | StorageKind::Sparse => self.rows.push(None)
+    }
+  }
+
+  /// Moves the value behind `ptr` into the entity's slot.
+  ///
+  /// Dense storages append a row for entities without one.
+  ///
+  /// # Warning
+  /// - Like [`ErasedVec::set_erased`] the value currently in the slot is not
+  ///   dropped. Prefer [`Self::reset_erased`].
+  ///
+  /// # Panics
+  /// - Panics if `entity >= len`.
+  /// - Panics if `ty` != `self.ty()`
+  pub fn set_erased(&mut self, entity:Entity, ty:TypeInfo, ptr:*mut u8) {
+    let row = self.row_or_pad(entity);
+    self.data.set_erased(row, ty, ptr);
+  }
+
+  /// Moves the value behind `ptr` into the entity's slot, dropping the value
+  /// it held.
+  ///
+  /// # Panics
+  /// - Panics if `entity >= len`.
+  /// - Panics if `ty` != `self.ty()`
+  pub fn reset_erased(&mut self, entity:Entity, ty:TypeInfo, ptr:*mut u8) {
+    // Replacing a component keeps its row
+    let row = self.row_or_pad(entity);
+    self.data.reset_erased(row, ty, ptr);
+  }
+
+  /// Moves `value` into the entity's slot, dropping the value it held.
+  ///
+  /// # Panics
+  /// - Panics if `entity >= len`.
+  pub fn reset<T:'static>(&mut self, entity:Entity, value:T) {
+    let row = self.row_or_pad(entity);
+    self.data.clear(row);
+    self.data.set(row, value);
+  }
+
+  /// Moves `value` into the entity's slot.
+  ///
+  /// Like [`Self::set_erased`] the value currently in the slot is not
+  /// dropped.
+  ///
+  /// # Panics
+  /// - Panics if `entity >= len`.
+  pub fn set<T:'static>(&mut self, entity:Entity, value:T) {
+    let row = self.row_or_pad(entity);
+    self.data.set(row, value);
+  }
+
+  /// Returns the entity's row, appending a padded row to a dense storage for
+  /// entities without one.
+  ///
+  /// # Panics
+  /// - Panics if `entity >= len`.
+  fn row_or_pad(&mut self, entity:Entity) -> usize {
+    assert!(entity < self.len(), "Entity {entity} is past the end of the storage");
+    match self.row(entity) {
+      Some(row) => row,
+      None => {
+        let row = self.entities.len();
+        self.data.pad();
+        self.rows[entity] = Some(row);
+        self.entities.push(entity);
+        row
+      }
+    }
+  }
+
+  /// Ensures the entity's slot can be written without allocating.
+  ///
+  /// Table storages already hold a slot per entity. Dense storages reserve
+  /// a row for entities without one. Returns an error instead of panicking or
+  /// aborting if the storage cannot grow, see [`ErasedVec::try_reserve`].
+  ///
+  /// # Panics
+  /// - Panics if `entity >= len`.
+  pub fn try_reserve_slot(&mut self, entity:Entity) -> Result<(), ErasedVecErrors> {
+    assert!(entity < self.len(), "Entity {entity} is past the end of the storage");
+    if self.row(entity).is_none() {
+      self.data.try_reserve(1)?;
+      self.entities.try_reserve(1).map_err(|_| ErasedVecErrors::ErasedVecAllocError)?;
+    }
+    Ok(())
+  }
+
+  /// Drops the entity's component and marks its slot as unfilled.
+  ///
+  /// Does nothing if the slot is not filled. Dense storages detach the row
+  /// from the entity and keep it as a hole until they are compacted, sparse
+  /// storages remove it.
+  ///
+  /// # Panics
+  /// - Panics if `entity >= len`.
+  pub fn clear(&mut self, entity:Entity) {
+    assert!(entity < self.len(), "Entity {entity} is past the end of the storage");
+    match (self.kind, self.row(entity)) {
+      (StorageKind::Table, Some(row)) => self.data.clear(row),
+      (StorageKind::Dense, Some(row)) => {
+        self.data.clear(row);
+        self.rows[entity] = None;
+      }
+      (StorageKind::Sparse, Some(row)) => self.remove_row(row),
+      (_, None) => {}
+    }
+  }
+
+  /// Marks the entity's slot as unfilled without dropping its value and
+  /// returns a pointer to the value.
+  ///
+  /// The row is kept so the pointer stays valid. Dense storages detach it
+  /// like [`Self::clear`] so compaction reclaims it, sparse storages remove
+  /// it when the entity's slot is next cleared. See
+  /// [`ErasedVec::take_erased`].
+  pub fn take_erased(&mut self, entity:Entity) -> *mut u8 {
+    let row = self.expect_row(entity);
+    if self.kind == StorageKind::Dense {
+      self.rows[entity] = None;
+    }
+    self.data.take_erased(row)
+  }
+
+  /// Clones the entity's component into the uninitialized memory at `out`.
+  ///
+  /// See [`ErasedVec::clone_slot`].
+  pub fn clone_slot(&self, entity:Entity, out:*mut u8) -> Result<()> {
+    match self.row(entity) {
+      Some(row) => self.data.clone_slot(row, out),
+      None => Err(EcsErrors::ComponentDataDoesNotExist { entity, ty:self.ty().name() }.into())
+    }
+  }
+
+  /// Clones the component of `src` into the slot of `dst`, dropping the
+  /// value `dst` held.
+  ///
+  /// See [`ErasedVec::clone_within`].
+  pub fn clone_within(&mut self, src:Entity, dst:Entity) -> Result<()> {
+    let src = self.row(src).ok_or_else(|| EcsErrors::ComponentDataDoesNotExist {
+      entity:src,
+      ty:self.ty().name()
+    })?;
+    let dst = self.row_or_pad(dst);
+    self.data.clone_within(src, dst)
+  }
+
+  /// Removes the holes left in a dense or sparse storage by removed
+  /// components, moving the last rows into them.
+  ///
+  /// Does nothing for table storages, whose rows are fixed to their entity.
+  pub fn compact(&mut self) {
+    if self.kind == StorageKind::Table {
+      return;
+    }
+
+    // Walking backwards means the row moved into a hole was already kept
+    for row in (0..self.data.len()).rev() {
+      // Marker columns read every row as filled, so a hole is a row its
+      // entity no longer points at
+      if self.rows[self.entities[row]] == Some(row) {
+        continue;
+      }
+
+      self.remove_row(row);
+    }
+  }
+
+  /// Drops the value in `row` if it is filled and moves the last row into
+  /// its place.
+  fn remove_row(&mut self, row:usize) {
+    let entity = self.entities[row];
+    // A hole's entity may have been given a new row since
+    if self.rows[entity] == Some(row) {
+      self.rows[entity] = None;
+    }
+    self.data.swap_remove(row);
+    self.entities.swap_remove(row);
+    if let Some(&moved) = self.entities.get(row) {
+      self.rows[moved] = Some(row);
+    }
+  }
+
+  /// Deep copies the [`ComponentStorage`], see [`ErasedVec::try_clone`].
+  pub fn try_clone(&self) -> Option<ComponentStorage> {
+    Some(ComponentStorage {
+      data:self.data.try_clone()?,
+      kind:self.kind,
+      rows:self.rows.clone(),
+      entities:self.entities.clone()
+    })
+  }
+
+  /// Replaces the [`TypeInfo`] describing the stored type.
+  ///
+  /// See [`ErasedVec::set_ty`].
+  pub(crate) fn set_ty(&mut self, ty:TypeInfo) {
+    self.data.set_ty(ty);
+  }
+}
+
+impl fmt::Debug for ComponentStorage {
+  /// Lists the filled slots by entity.
+  ///
+  /// Every slot of a marker column reads as filled, so only its type and
+  /// length are shown. The world's bitmasks say which entities hold it.
+  fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result {
+    if !self.tracks_filled() {
+      return f
+        .debug_struct("ComponentStorage")
+        .field("ty", &self.ty().type_name())
+        .field("len", &self.len())
+        .finish();
+    }
+
+    f.debug_map()
+      .entries(
+        (0..self.len())
+          .filter(|entity| self.is_filled(*entity))
+          .map(|entity| (entity, unsafe { self.ty().debug_value(self.indexed_ptr::<u8>(entity)) }))
+      )
+      .finish()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{ComponentStorage, StorageKind};
+  use crate::storage::TypeInfo;
+  use std::mem::ManuallyDrop;
+
+  fn set(storage:&mut ComponentStorage, entity:usize, value:String) {
+    let mut value = ManuallyDrop::new(value);
+    storage.reset_erased(entity, TypeInfo::of::<String>(), (&mut *value as *mut String).cast());
+  }
+
+  fn dense(entities:usize) -> ComponentStorage {
+    let mut storage = ComponentStorage::new_erased(TypeInfo::of::<String>(), StorageKind::Dense);
+    for _ in 0..entities {
+      storage.pad();
+    }
+    storage
+  }
+
+  #[test]
+  fn dense_storages_hand_out_rows_in_insertion_order() {
+    let mut storage = dense(4);
+    set(&mut storage, 3, "c".repeat(16));
+    set(&mut storage, 1, "a".repeat(16));
+
+    assert_eq!(storage.len(), 4);
+    assert_eq!(storage.data().len(), 2);
+    assert_eq!(storage.row_entities(), Some(&[3, 1][..]));
+    assert_eq!(storage.try_get::<String>(1).unwrap(), &"a".repeat(16));
+    assert!(storage.try_get::<String>(0).is_none());
+
+    // Replacing a component reuses its row
+    set(&mut storage, 3, "b".repeat(16));
+    assert_eq!(storage.data().len(), 2);
+    assert_eq!(storage.iter::<String>().flatten().count(), 2);
+  }
+
+  #[test]
+  fn compaction_moves_rows_into_holes() {
+    let mut storage = dense(6);
+    for entity in 0..6 {
+      set(&mut storage, entity, entity.to_string().repeat(16));
+    }
+    storage.clear(0);
+    storage.clear(2);
+    storage.clear(5);
+    assert_eq!(storage.data().len(), 6);
+
+    storage.compact();
+    assert_eq!(storage.data().len(), 3);
+    assert_eq!(storage.data().as_slice::<String>().unwrap().len(), 3);
+    for (row, entity) in storage.row_entities().unwrap().iter().enumerate() {
+      assert_eq!(storage.row(*entity), Some(row));
+      assert_eq!(storage.get::<String>(*entity), &entity.to_string().repeat(16));
+    }
+    assert_eq!(storage.row(2), None);
+
+    // Removed entities get a new row
+    set(&mut storage, 2, "x".repeat(16));
+    assert_eq!(storage.row(2), Some(3));
+  }
+
+  #[test]
+  fn sparse_storages_remove_rows_right_away() {
+    let mut storage = ComponentStorage::new_erased(TypeInfo::of::<String>(), StorageKind::Sparse);
+    for _ in 0..5 {
+      storage.pad();
+    }
+    for entity in [4, 0, 2] {
+      set(&mut storage, entity, entity.to_string().repeat(16));
+    }
+
+    storage.clear(4);
+    assert_eq!(storage.data().len(), 2);
+    assert_eq!(storage.row_entities(), Some(&[2, 0][..]));
+    assert_eq!(storage.row(2), Some(0));
+    assert_eq!(storage.row(4), None);
+    assert_eq!(
+      storage.iter::<String>().map(|name| name.cloned()).collect::<Vec<_>>(),
+      vec![Some("0".repeat(16)), None, Some("2".repeat(16)), None, None]
+    );
+
+    // Taking a value leaves a hole until the slot is cleared
+    let taken = unsafe { storage.take_erased(2).cast::<String>().read() };
+    assert_eq!(taken, "2".repeat(16));
+    assert!(!storage.is_filled(2));
+    storage.clear(2);
+    assert_eq!(storage.data().len(), 1);
+    assert_eq!(storage.get::<String>(0), &"0".repeat(16));
+  }
+
+  #[test]
+  fn dense_marker_rows_are_freed() {
+    struct Marker;
+
+    let mut storage = ComponentStorage::new_erased(TypeInfo::of::<Marker>(), StorageKind::Dense);
+    for entity in 0..4 {
+      storage.pad();
+      storage.reset(entity, Marker);
+    }
+    assert!(!storage.tracks_filled());
+
+    // Replacing a marker keeps its row
+    storage.reset(2, Marker);
+    assert_eq!(storage.row_entities(), Some(&[0, 1, 2, 3][..]));
+
+    storage.clear(0);
+    storage.clear(2);
+    assert!(!storage.is_filled(0));
+    assert_eq!(storage.row(2), None);
+
+    // A hole whose entity was given a new row is still removed
+    storage.reset(0, Marker);
+    storage.compact();
+    assert_eq!(storage.data().len(), 3);
+    assert_eq!(storage.row_entities(), Some(&[3, 1, 0][..]));
+    for (row, entity) in storage.row_entities().unwrap().iter().enumerate() {
+      assert_eq!(storage.row(*entity), Some(row));
+    }
+  }
+
+  #[test]
+  fn marker_columns_do_not_list_their_slots() {
+    struct Marker;
+
+    let mut storage = ComponentStorage::new_erased(TypeInfo::of::<Marker>(), StorageKind::Table);
+    for _ in 0..100 {
+      storage.pad();
+    }
+    let debug = format!("{storage:?}");
+    assert!(debug.contains("len: 100"));
+    assert!(!debug.contains("99"));
+  }
+
+  #[test]
+  fn table_storages_are_not_compacted() {
+    let mut storage = ComponentStorage::new_erased(TypeInfo::of::<String>(), StorageKind::Table);
+    storage.pad();
+    storage.pad();
+    set(&mut storage, 1, "a".repeat(16));
+    storage.compact();
+
+    assert_eq!(storage.row(1), Some(1));
+    assert_eq!(storage.row_entities(), None);
+    assert!(!storage.is_filled(0));
+  }
+}
@@ -1,9 +1,23 @@
 use std::any::Any;
 
+/// Types that can be components.
+///
+/// This is just a convenient shorthand for `'static + Any + Send + Sync`, and
+/// never needs to be implemented manually.
+#[cfg(feature = "sync")]
+pub trait EcsData: 'static + Any + Send + Sync {}
+
+#[cfg(feature = "sync")]
+impl<T:'static + Send + Sync> EcsData for T {}
+
 /// Types that can be components.
 ///
 /// This is just a convenient shorthand for `'static + Any`, and never
-/// needs to be implemented manually.
+/// needs to be implemented manually. Enable the `sync` feature to require
+/// components to be `Send + Sync` and make the [`World`](crate::world::World)
+/// `Send + Sync`.
+#[cfg(not(feature = "sync"))]
 pub trait EcsData: 'static + Any {}
 
+#[cfg(not(feature = "sync"))]
 impl<T:'static> EcsData for T {}
@@ -1,14 +1,21 @@
-use super::{type_info::TypeInfo, Bundle};
-use crate::errors::ErasedVecErrors::{DoesNotContainType, ErasedVecAllocError, ErasedVecCapacityOverflow, IncorrectTypeInsertion, IndexOutOfBounds};
+use super::{
+  ticks::{ComponentTicks, Tick},
+  type_info::TypeInfo,
+  ErasedBundle
+};
+use crate::errors::ErasedVecErrors::{
+  self, DoesNotContainType, ErasedVecAllocError, ErasedVecCapacityOverflow, IncorrectTypeInsertion, IndexOutOfBounds, InsertOutOfBounds,
+  NotCloneable, SparseColumn, UnfilledSlot
+};
+use crate::errors::Result;
 use std::{
   alloc::{self, Layout},
-  mem,
-  ptr::{self, NonNull}
+  fmt, mem,
+  ptr::{self, NonNull},
+  slice
 };
 
 // Refactor:
-// -No `pop` method. Unsure it is needed.
-// -No `remove` method. Unsure it is needed.
 // -Split this into multiple modules.
 // -Redo the Box, it doesn't need to used the RawErasedVec
 
@@ -20,36 +27,48 @@ struct RawErasedVec {
 
 impl RawErasedVec {
   fn new<T:'static>() -> Self {
-    let ty = TypeInfo::of::<T>();
-    let ptr = NonNull::dangling();
-    let cap = if ty.size() == 0 { usize::MAX } else { 0 };
-
-    RawErasedVec { ptr, cap, ty }
+    Self::new_erased(TypeInfo::of::<T>())
   }
 
   fn new_erased(ty:TypeInfo) -> Self {
+    let cap = if ty.size() == 0 { usize::MAX } else { 0 };
+
     RawErasedVec {
       ty,
-      ptr:NonNull::dangling(),
-      cap:0
+      ptr:Self::dangling(ty),
+      cap
     }
   }
 
+  /// Returns a dangling pointer aligned for `ty`, used while nothing is
+  /// allocated and for zero-sized types.
+  fn dangling(ty:TypeInfo) -> NonNull<u8> {
+    NonNull::new(ptr::without_provenance_mut(ty.layout().align())).unwrap()
+  }
+
   fn grow_exact(&mut self, cap:usize) {
+    match self.try_grow_exact(cap) {
+      Ok(()) => {}
+      Err(ErasedVecAllocError) => alloc::handle_alloc_error(self.ty.array(cap.max(1)).unwrap()),
+      Err(error) => panic!("{error}")
+    }
+  }
+
+  /// Grows the allocation to hold exactly `cap` elements.
+  ///
+  /// Returns [`ErasedVecCapacityOverflow`] if the allocation would exceed
+  /// `isize::MAX` bytes and [`ErasedVecAllocError`] if the allocator fails.
+  /// The allocation is left untouched on error.
+  fn try_grow_exact(&mut self, cap:usize) -> Result<(), ErasedVecErrors> {
     // since we set the capacity to usize::MAX when `ty` has size 0,
     // getting to here necessarily means the Vec is overfull.
-    assert!(self.ty.size() != 0, "{ErasedVecCapacityOverflow}");
-
-    let (new_cap, new_layout) = if self.cap == 0 {
-      (1, self.ty.array(1).unwrap())
-    } else {
-      let new_cap = cap;
-      let new_layout = self.ty.array(new_cap).unwrap();
-      (new_cap, new_layout)
-    };
+    if self.ty.size() == 0 {
+      return Err(ErasedVecCapacityOverflow);
+    }
 
-    // Ensure that the new allocation doesn't exceed `isize::MAX` bytes.
-    assert!(new_layout.size() <= isize::MAX as usize, "{ErasedVecAllocError}",);
+    let new_cap = if self.cap == 0 { cap.max(1) } else { cap };
+    // Fails if the allocation would exceed `isize::MAX` bytes
+    let new_layout = self.ty.array(new_cap).map_err(|_| ErasedVecCapacityOverflow)?;
 
     let new_ptr = if self.cap == 0 {
       unsafe { alloc::alloc(new_layout) }
@@ -59,18 +78,58 @@ impl RawErasedVec {
       unsafe { alloc::realloc(old_ptr, old_layout, new_layout.size()) }
     };
 
-    // If allocation fails, `new_ptr` will be null, in which case we abort.
-    self.ptr = match NonNull::new(new_ptr) {
-      Some(p) => p,
-      None => alloc::handle_alloc_error(new_layout)
-    };
-
+    // A failed allocation returns null and leaves the old one in place
+    self.ptr = NonNull::new(new_ptr).ok_or(ErasedVecAllocError)?;
     self.cap = new_cap;
+    Ok(())
+  }
+
+  /// Grows the allocation to hold at least `required` elements, doubling the
+  /// capacity if that is larger.
+  ///
+  /// Falls back to exactly `required` elements if the doubled capacity cannot
+  /// be allocated.
+  fn try_grow_amortized(&mut self, required:usize) -> Result<(), ErasedVecErrors> {
+    if 2 * self.cap > required && self.try_grow_exact(2 * self.cap).is_ok() {
+      return Ok(());
+    }
+    self.try_grow_exact(required)
+  }
+
+  /// Same as [`Self::try_grow_amortized`] but panics or aborts if exactly
+  /// `required` elements cannot be allocated.
+  fn grow_amortized(&mut self, required:usize) {
+    if 2 * self.cap <= required || self.try_grow_exact(2 * self.cap).is_err() {
+      self.grow_exact(required);
+    }
   }
 
   fn grow(&mut self) {
     self.grow_exact(2 * self.cap);
   }
+
+  /// Shrinks the allocation to hold exactly `cap` elements.
+  fn shrink_to(&mut self, cap:usize) {
+    // Zero-sized types never allocate
+    if self.ty.size() == 0 || cap >= self.cap {
+      return;
+    }
+
+    let old_layout = self.ty.array(self.cap).unwrap();
+    if cap == 0 {
+      unsafe { alloc::dealloc(self.ptr.as_ptr(), old_layout) }
+      self.ptr = Self::dangling(self.ty);
+    } else {
+      let new_layout = self.ty.array(cap).unwrap();
+      let new_ptr = unsafe { alloc::realloc(self.ptr.as_ptr(), old_layout, new_layout.size()) };
+      self.ptr = match NonNull::new(new_ptr) {
+        Some(p) => p,
+        None => alloc::handle_alloc_error(new_layout)
+      };
+    }
+
+    self.cap = cap;
+  }
 }
 
 impl Drop for RawErasedVec {
@@ -84,10 +143,18 @@ impl Drop for RawErasedVec {
 }
 
 ///A type erased vector used for storing data in the ECS.
+///
+/// Columns of markers, zero-sized types without drop glue, store neither
+/// values nor `filled` flags. Every slot of a marker column reads as filled,
+/// so which entities hold a marker is decided by the world's bitmasks alone.
 pub struct ErasedVec {
   buf:RawErasedVec,
   ///Tracks with indices in an `ErasedVec` are filled. Useful for drop logic.
-  filled:Vec<bool>,
+  filled:Filled,
+  ///The ticks at which each slot was last added and changed.
+  ticks:Vec<ComponentTicks>,
+  ///The tick stamped on slots as they are written.
+  tick:Tick,
   len:usize
 }
 
@@ -96,13 +163,31 @@ impl ErasedVec {
   ///
   ///The vector will not allocate until elements are pushed onto it.
   pub fn new<T:'static>() -> Self {
+    Self::new_erased(TypeInfo::of::<T>())
+  }
+
+  ///Constructs a new, empty [`ErasedVec`] holding the type described by
+  /// `ty`.
+  ///
+  ///The vector will not allocate until elements are pushed onto it.
+  pub fn new_erased(ty:TypeInfo) -> Self {
     ErasedVec {
-      buf:RawErasedVec::new::<T>(),
-      filled:Vec::new(),
+      buf:RawErasedVec::new_erased(ty),
+      filled:Filled::new(ty),
+      ticks:Vec::new(),
+      tick:0,
       len:0
     }
   }
 
+  ///Constructs a new, empty [`ErasedVec<T>`] with room for at least
+  /// `capacity` elements.
+  pub fn with_capacity<T:'static>(capacity:usize) -> Self {
+    let mut vec = Self::new::<T>();
+    vec.reserve_exact(capacity);
+    vec
+  }
+
   fn ptr(&self) -> *mut u8 {
     self.buf.ptr.as_ptr()
   }
@@ -111,6 +196,9 @@ impl ErasedVec {
   ///
   /// # Warning
   /// - The pointer is calculated using the internal [`TypeInfo`].
+  ///
+  /// # Safety
+  /// - `index` must be within the allocated capacity of the [`ErasedVec`].
   pub unsafe fn indexed_ptr<T:'static>(&self, index:usize) -> *mut T {
     let index = index * self.ty().size();
     self.ptr().add(index) as *mut T
@@ -124,39 +212,267 @@ impl ErasedVec {
     self.buf.cap
   }
 
+  ///Returns the number of elements the vector can hold without
+  /// reallocating.
+  pub fn capacity(&self) -> usize {
+    self.cap()
+  }
+
+  ///Reserves capacity for at least `additional` more elements. May reserve
+  /// more space to avoid frequent reallocations, falling back to exactly the
+  /// requested capacity if the extra space cannot be allocated.
+  pub fn reserve(&mut self, additional:usize) {
+    let required = self.len.checked_add(additional).unwrap_or_else(|| panic!("{ErasedVecCapacityOverflow}"));
+    if required > self.cap() {
+      self.buf.grow_amortized(required);
+    }
+    self.filled.reserve(additional);
+    self.ticks.reserve(additional);
+  }
+
+  ///Tries to reserve capacity for at least `additional` more elements,
+  /// like [`Self::reserve`].
+  ///
+  /// Returns [`ErasedVecCapacityOverflow`] if the capacity would exceed
+  /// `isize::MAX` bytes and [`ErasedVecAllocError`] if the allocator fails,
+  /// instead of panicking or aborting. If doubling the capacity fails only
+  /// the requested capacity is retried. The vector is left usable on error.
+  pub fn try_reserve(&mut self, additional:usize) -> Result<(), ErasedVecErrors> {
+    let required = self.len.checked_add(additional).ok_or(ErasedVecCapacityOverflow)?;
+    if required > self.cap() {
+      self.buf.try_grow_amortized(required)?;
+    }
+    self.filled.try_reserve(additional)?;
+    self.ticks.try_reserve(additional).map_err(|_| ErasedVecAllocError)?;
+    Ok(())
+  }
+
+  ///Reserves capacity for exactly `additional` more elements.
+  pub fn reserve_exact(&mut self, additional:usize) {
+    let required = self.len.checked_add(additional).unwrap_or_else(|| panic!("{ErasedVecCapacityOverflow}"));
+    if required > self.cap() {
+      self.buf.grow_exact(required);
+    }
+    self.filled.reserve_exact(additional);
+    self.ticks.reserve_exact(additional);
+  }
+
+  ///Shrinks the capacity of the vector as much as possible.
+  pub fn shrink_to_fit(&mut self) {
+    self.buf.shrink_to(self.len);
+  }
+
   ///Returns the number of elements in the vector, also referred to as its
   /// ‘length’.
   pub fn len(&self) -> usize {
     self.len
   }
 
+  ///Returns `true` if the vector contains no elements.
+  pub fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+
+  ///Returns the tick stamped on slots as they are written.
+  pub fn tick(&self) -> Tick {
+    self.tick
+  }
+
+  ///Sets the tick stamped on slots as they are written.
+  pub fn set_tick(&mut self, tick:Tick) {
+    self.tick = tick;
+  }
+
+  ///Returns the [`ComponentTicks`] of the slot at `index`.
+  ///
+  /// # Panics
+  /// - Panics if `index >= len`.
+  pub fn ticks(&self, index:usize) -> &ComponentTicks {
+    &self.ticks[index]
+  }
+
+  ///Records a change to the slot at `index` at the current tick.
+  ///
+  /// # Panics
+  /// - Panics if `index >= len`.
+  pub fn mark_changed(&self, index:usize) {
+    self.ticks[index].set_changed(self.tick);
+  }
+
   ///Fetch data from the [`ErasedVec`] by index.
   ///
   /// # Panics
   /// - Panics if the [`TypeInfo`] of the value does not match the type
   ///   contained in the `ErasedVec`.
-  /// - Panics if `index` > `self.len`.
+  /// - Panics if `index >= len`.
   pub fn get<T:'static>(&self, index:usize) -> &T {
     // Confirm the vector contains `T`
     self.assert_type_info(TypeInfo::of::<T>());
 
     // Confirm the index is in bounds
-    assert!(index <= self.len, "{}", IndexOutOfBounds { len:self.len, index });
+    assert!(index < self.len, "{}", IndexOutOfBounds { len:self.len, index });
 
     // Get a pointer the data and cast it to `&T`
     unsafe { &*(self.indexed_ptr(index)) }
   }
 
+  ///Returns `true` if the [`ErasedVec`] tracks which of its slots are
+  /// filled.
+  ///
+  /// Returns `false` for markers, zero-sized types without drop glue, whose
+  /// slots all read as filled. References to a marker point at the same
+  /// dangling address, which is a valid instance of any zero-sized type.
+  pub fn tracks_filled(&self) -> bool {
+    matches!(self.filled, Filled::Tracked(_))
+  }
+
+  ///Returns the number of slots holding a value.
+  pub fn filled_count(&self) -> usize {
+    match &self.filled {
+      Filled::Tracked(flags) => flags.iter().filter(|filled| **filled).count(),
+      Filled::Untracked => self.len
+    }
+  }
+
+  ///Returns the number of bytes allocated for the elements, their filled
+  /// flags and their ticks.
+  ///
+  /// Heap memory owned by the elements themselves is not counted.
+  pub fn allocated_bytes(&self) -> usize {
+    let filled = match &self.filled {
+      Filled::Tracked(flags) => flags.capacity() * mem::size_of::<bool>(),
+      Filled::Untracked => 0
+    };
+    self.cap() * self.ty().size() + filled + self.ticks.capacity() * mem::size_of::<ComponentTicks>()
+  }
+
+  ///Returns `true` if the slot at `index` holds a value. Padded slots and
+  /// indices past the end are not filled.
+  ///
+  /// Every slot of a marker column is filled, see [`Self::tracks_filled`].
+  pub fn is_filled(&self, index:usize) -> bool {
+    index < self.len && self.filled.get(index)
+  }
+
   ///Fetch data from the [`ErasedVec`] by index.
   ///
-  /// # Warning
+  /// Returns `None` if the slot is out of bounds or not filled.
+  ///
+  /// # Panics
+  /// - Panics if the [`TypeInfo`] of the value does not match the type
+  ///   contained in the `ErasedVec`.
+  pub fn try_get<T:'static>(&self, index:usize) -> Option<&T> {
+    self.assert_type_info(TypeInfo::of::<T>());
+
+    if self.is_filled(index) {
+      Some(unsafe { &*(self.indexed_ptr(index)) })
+    } else {
+      None
+    }
+  }
+
+  ///Fetch data mutably from the [`ErasedVec`] by index.
+  ///
+  /// Returns `None` if the slot is out of bounds or not filled.
+  ///
+  /// # Panics
+  /// - Panics if the [`TypeInfo`] of the value does not match the type
+  ///   contained in the `ErasedVec`.
+  #[allow(clippy::mut_from_ref)]
+  pub fn try_get_mut<T:'static>(&self, index:usize) -> Option<&mut T> {
+    self.assert_type_info(TypeInfo::of::<T>());
+
+    if self.is_filled(index) {
+      self.mark_changed(index);
+      Some(unsafe { &mut *(self.indexed_ptr(index)) })
+    } else {
+      None
+    }
+  }
+
+  ///Returns an iterator over every slot in the [`ErasedVec`]. Unfilled slots
+  /// are yielded as `None`.
+  ///
+  /// # Panics
+  /// - Panics if the [`TypeInfo`] of `T` does not match the type contained in
+  ///   the `ErasedVec`.
+  pub fn iter<T:'static>(&self) -> impl Iterator<Item = Option<&T>> + '_ {
+    // Checked once so the elements can be read without checks
+    self.assert_type_info(TypeInfo::of::<T>());
+
+    (0..self.len).map(|index| self.filled.get(index).then(|| unsafe { &*self.indexed_ptr::<T>(index) }))
+  }
+
+  ///Returns an iterator over every slot in the [`ErasedVec`] which yields
+  /// mutable references. Unfilled slots are yielded as `None`.
+  ///
+  /// Every filled slot is marked as changed.
+  ///
+  /// # Panics
+  /// - Panics if the [`TypeInfo`] of `T` does not match the type contained in
+  ///   the `ErasedVec`.
+  pub fn iter_mut<T:'static>(&mut self) -> impl Iterator<Item = Option<&mut T>> + '_ {
+    // Checked once so the elements can be read without checks
+    self.assert_type_info(TypeInfo::of::<T>());
+
+    let this = &*self;
+    (0..this.len).map(move |index| {
+      this.filled.get(index).then(|| {
+        this.mark_changed(index);
+        // Each index is yielded once so the references never alias
+        unsafe { &mut *this.indexed_ptr::<T>(index) }
+      })
+    })
+  }
+
+  ///Returns the [`ErasedVec`]'s elements as a slice.
+  ///
+  /// Returns an error if the vector does not contain `T` or if any slot is
+  /// unfilled.
+  pub fn as_slice<T:'static>(&self) -> Result<&[T]> {
+    self.check_dense::<T>()?;
+    Ok(unsafe { std::slice::from_raw_parts(self.slice_ptr::<T>(), self.len) })
+  }
+
+  ///Returns the [`ErasedVec`]'s elements as a mutable slice.
+  ///
+  /// Every slot is marked as changed. Returns an error if the vector does not
+  /// contain `T` or if any slot is unfilled.
+  pub fn as_mut_slice<T:'static>(&mut self) -> Result<&mut [T]> {
+    self.check_dense::<T>()?;
+    (0..self.len).for_each(|index| self.mark_changed(index));
+    Ok(unsafe { std::slice::from_raw_parts_mut(self.slice_ptr::<T>(), self.len) })
+  }
+
+  ///Errors if the [`ErasedVec`] does not contain `T` or holds an unfilled
+  /// slot.
+  fn check_dense<T:'static>(&self) -> Result<()> {
+    let ty = TypeInfo::of::<T>();
+    if ty != self.ty() {
+      return Err(DoesNotContainType(ty.name()).into());
+    }
+    if let Some(index) = (0..self.len).find(|index| !self.filled.get(*index)) {
+      return Err(SparseColumn { ty:ty.name(), index }.into());
+    }
+    Ok(())
+  }
+
+  ///Returns a pointer to the start of the buffer, which is aligned for `T`
+  /// even when nothing is allocated.
+  fn slice_ptr<T:'static>(&self) -> *mut T {
+    self.ptr().cast::<T>()
+  }
+
+  ///Fetch data from the [`ErasedVec`] by index.
+  ///
+  /// # Safety
   /// - Does not check whether the `ErasedVec` contains the requested type `T`.
   ///
   /// # Panics
-  /// - Panics if `index` > `self.len`.
+  /// - Panics if `index >= len`.
   pub unsafe fn get_unchecked<T:'static>(&self, index:usize) -> &T {
     // Confirm the index is in bounds
-    assert!(index <= self.len, "{}", IndexOutOfBounds { len:self.len, index });
+    assert!(index < self.len, "{}", IndexOutOfBounds { len:self.len, index });
 
     // Get a pointer the data and cast it to `&T`
     unsafe { &*(self.indexed_ptr(index)) }
@@ -166,30 +482,33 @@ impl ErasedVec {
   ///
   /// # Panics
   /// - Panics if the [`TypeInfo`] of the value does not match the type
-  ///   contained
-  /// in the `ErasedVec`.
-  /// - Panics if `index` > `self.len`.
+  ///   contained in the `ErasedVec`.
+  /// - Panics if `index >= len`.
+  #[allow(clippy::mut_from_ref)]
   pub fn get_mut<T:'static>(&self, index:usize) -> &mut T {
     // Confirm the vector contains `T`
     self.assert_type_info(TypeInfo::of::<T>());
 
     // Confirm the index is in bounds
-    assert!(index <= self.len, "{}", IndexOutOfBounds { len:self.len, index });
+    assert!(index < self.len, "{}", IndexOutOfBounds { len:self.len, index });
 
     // Get a pointer the data and cast it to `&mut T`
+    self.mark_changed(index);
     unsafe { &mut *(self.indexed_ptr(index)) }
   }
 
   ///Fetch data mutably sfrom the [`ErasedVec`] by index.
   ///
-  /// # Warning
+  /// # Safety
   /// - Does not check whether the `ErasedVec` contains the requested type `T`.
   ///
   /// # Panics
-  /// - Panics if `index` > `self.len`.
+  /// - Panics if `index >= len`.
+  #[allow(clippy::mut_from_ref)]
   pub unsafe fn get_mut_unchecked<T:'static + Send + Sync>(&self, index:usize) -> &mut T {
     // Confirm the index is in bounds
-    assert!(index <= self.len, "{}", IndexOutOfBounds { len:self.len, index });
+    assert!(index < self.len, "{}", IndexOutOfBounds { len:self.len, index });
+    self.mark_changed(index);
 
     // Get a pointer the data and cast it to `&T`
     unsafe { &mut *(self.indexed_ptr(index)) }
@@ -198,19 +517,23 @@ impl ErasedVec {
   ///Pushes a value semantically equivelent to `None<T>` into the
   /// [`ErasedVec`].
   ///
+  /// In a column of markers the padded slot reads as filled, see
+  /// [`Self::tracks_filled`].
+  ///
   /// # Warning
   /// - Data is padded with 0s, attempting to access it before it is overwritten
-  /// with a value of type `T` will cause undefined behavior.
+  ///   with a value of type `T` will cause undefined behavior.
   pub fn pad(&mut self) {
-    let mut padding:Vec<u8> = Vec::new();
-    padding.resize(self.ty().size(), 0);
-    let padding = padding.as_mut_ptr();
+    // Grow the Vec if it is at max capacity
+    if self.len == self.cap() {
+      self.buf.grow()
+    }
 
-    let len = self.len();
+    unsafe { ptr::write_bytes(self.indexed_ptr::<u8>(self.len), 0, self.ty().size()) };
 
-    self.push_erased(padding, self.ty());
-    // Mark the array as not filled
-    self.filled[len] = false;
+    self.filled.push(false);
+    self.ticks.push(ComponentTicks::new(self.tick));
+    self.len += 1;
   }
 
   ///Append a value to the back of the [`ErasedVec`].
@@ -235,19 +558,86 @@ impl ErasedVec {
     mem::forget(value);
 
     self.filled.push(true);
+    self.ticks.push(ComponentTicks::new(self.tick));
     self.len += 1;
   }
 
+  ///Tries to append an element to the back of the vector, see
+  /// [`Self::try_reserve`] for the errors returned if it has to grow.
+  ///
+  /// The value is dropped on error.
+  ///
+  /// # Panics
+  /// - Panics if the [`TypeInfo`] of `T` does not match the type contained in
+  ///   the `ErasedVec`.
+  pub fn try_push<T:'static>(&mut self, value:T) -> Result<(), ErasedVecErrors> {
+    self.assert_type_info_insert(TypeInfo::of::<T>());
+    self.try_reserve(1)?;
+    self.push(value);
+    Ok(())
+  }
+
+  ///Removes the last element from the [`ErasedVec`] and returns it.
+  ///
+  /// Returns `None` if the vector is empty. A padded (unfilled) last slot is
+  /// removed and `None` is returned, compare [`Self::len`] before and after to
+  /// tell the cases apart.
+  ///
+  /// # Panics
+  /// - Panics if the [`TypeInfo`] of `T` does not match the type contained in
+  ///   the `ErasedVec`.
+  pub fn pop<T:'static>(&mut self) -> Option<T> {
+    self.assert_type_info(TypeInfo::of::<T>());
+
+    let index = self.len.checked_sub(1)?;
+    let value = if self.filled.get(index) {
+      Some(unsafe { ptr::read(self.indexed_ptr::<T>(index)) })
+    } else {
+      None
+    };
+    self.pop_slot();
+    value
+  }
+
+  ///Removes the last element from the [`ErasedVec`] and copies it into `out`.
+  ///
+  /// Returns the [`TypeInfo`] of the copied value. Like [`Self::pop`], returns
+  /// `None` if the vector is empty or if the removed slot was unfilled, in
+  /// which case nothing is written to `out`.
+  ///
+  /// # Warning
+  /// - The caller takes ownership of the value written to `out`. `out` must be
+  ///   valid for writes of `self.ty().size()` bytes.
+  #[allow(clippy::not_unsafe_ptr_arg_deref)]
+  pub fn pop_erased(&mut self, out:*mut u8) -> Option<TypeInfo> {
+    let index = self.len.checked_sub(1)?;
+    let ty = if self.filled.get(index) {
+      unsafe { ptr::copy_nonoverlapping(self.indexed_ptr::<u8>(index), out, self.ty().size()) };
+      Some(self.ty())
+    } else {
+      None
+    };
+    self.pop_slot();
+    ty
+  }
+
+  /// Removes the last slot without dropping it.
+  fn pop_slot(&mut self) {
+    self.filled.pop();
+    self.ticks.pop();
+    self.len -= 1;
+  }
+
   ///Append a type-erased value to the back of the [`ErasedVec`].
   ///
   /// # Warning
   /// - Must call [`mem::forget`] on the value being inserted or a double free
-  /// will occur.
+  ///   will occur.
   ///
   /// # Panics
   /// - Panics if the [`TypeInfo`] of the value does not match the type
-  ///   contained
-  /// in the `ErasedVec`.
+  ///   contained in the `ErasedVec`.
+  #[allow(clippy::not_unsafe_ptr_arg_deref)]
   pub fn push_erased(&mut self, val_ptr:*mut u8, ty:TypeInfo) {
     // Grow the Vec if it is at max capacity
     if self.len == self.cap() {
@@ -264,6 +654,7 @@ impl ErasedVec {
     }
 
     self.filled.push(true);
+    self.ticks.push(ComponentTicks::new(self.tick));
     self.len += 1;
   }
 
@@ -276,7 +667,7 @@ impl ErasedVec {
     self.assert_type_info_insert(TypeInfo::of::<T>());
 
     // Check whether the index is within bounds
-    assert!(index <= self.len, "{}", IndexOutOfBounds { len:self.len, index });
+    assert!(index <= self.len, "{}", InsertOutOfBounds { len:self.len, index });
     if self.len == self.cap() {
       self.buf.grow()
     }
@@ -293,6 +684,7 @@ impl ErasedVec {
     }
 
     self.filled.insert(index, true);
+    self.ticks.insert(index, ComponentTicks::new(self.tick));
     self.len += 1;
   }
 
@@ -302,19 +694,20 @@ impl ErasedVec {
   /// # Warning
   ///
   /// - Must call [`mem::forget`] on the value being inserted or a double free
-  /// will occur.
+  ///   will occur.
   ///
   /// # Panics
   ///
   /// - Panics if `index > len`.
   /// - Panics if `ty` != `self.ty()`
+  #[allow(clippy::not_unsafe_ptr_arg_deref)]
   pub fn insert_erased(&mut self, val_ptr:*mut u8, ty:TypeInfo, index:usize) {
     if self.len == self.cap() {
       self.buf.grow()
     }
 
     // Check whether the index is within bounds
-    assert!(index <= self.len, "{}", IndexOutOfBounds { len:self.len, index });
+    assert!(index <= self.len, "{}", InsertOutOfBounds { len:self.len, index });
 
     self.assert_type_info_insert(ty);
 
@@ -329,6 +722,7 @@ impl ErasedVec {
     }
 
     self.filled.insert(index, true);
+    self.ticks.insert(index, ComponentTicks::new(self.tick));
     self.len += 1;
   }
 
@@ -336,107 +730,569 @@ impl ErasedVec {
   ///
   /// # Panics
   ///
-  /// - Panics if `index > len`.
+  /// - Panics if `index >= len`.
   /// - Panics if `ty` != `self.ty()`
   pub fn set<T:'static>(&mut self, index:usize, data:T) {
     self.assert_type_info_insert(TypeInfo::of::<T>());
 
     // Check whether the index is within bounds
-    assert!(index <= self.len, "{}", IndexOutOfBounds { len:self.len, index });
-    if self.len == self.cap() {
-      self.buf.grow()
-    }
+    assert!(index < self.len, "{}", IndexOutOfBounds { len:self.len, index });
 
     unsafe {
       // Copy the value as raw bits into the `ErasedVec`
       let val_ptr = (&data as *const T).cast::<u8>();
       ptr::copy_nonoverlapping(val_ptr, self.indexed_ptr(index), self.ty().size());
     }
+
+    // The `ErasedVec` now owns the value
+    mem::forget(data);
+    self.filled.set(index, true);
+    self.ticks[index] = ComponentTicks::new(self.tick);
   }
 
   /// Sets the `index` within the vector.
   ///
   /// # Warning
   /// - Must call [`mem::forget`] on the value or wrap it in a
-  /// [`mem::ManuallyDrop`] or a double free will occur.
+  ///   [`mem::ManuallyDrop`] or a double free will occur.
   /// - If reassigning an index, must use [`Self::clear`] to reset the data
   ///   currently stored at the index. Prefer to use [`Self::reset_erased`] to
   ///   reset an index
   ///
   /// # Panics
-  /// - Panics if `index > len`.
+  /// - Panics if `index >= len`.
   /// - Panics if `ty` != `self.ty()`
+  #[allow(clippy::not_unsafe_ptr_arg_deref)]
   pub fn set_erased(&mut self, index:usize, ty:TypeInfo, ptr:*mut u8) {
-    if self.len == self.cap() {
-      self.buf.grow()
+    // Check whether the index is within bounds
+    assert!(index < self.len, "{}", IndexOutOfBounds { len:self.len, index });
+
+    self.assert_type_info_insert(ty);
+
+    unsafe {
+      // Copy the value as raw bits into the `ErasedVec`
+      ptr::copy_nonoverlapping(ptr, self.indexed_ptr(index), self.ty().size());
+    }
+
+    self.filled.set(index, true);
+    self.ticks[index] = ComponentTicks::new(self.tick);
+  }
+
+  /// Sets the `index` within the vector.
+  ///
+  /// Destroys the data currently at `index` to prevent leaking. Unfilled
+  /// slots, such as padding, are never dropped.
+  ///
+  /// # Panics
+  /// - Panics if `index >= len`.
+  /// - Panics if `ty` != `self.ty()`
+  pub fn reset_erased(&mut self, index:usize, ty:TypeInfo, ptr:*mut u8) {
+    self.clear(index);
+    self.set_erased(index, ty, ptr);
+  }
+
+  /// Drops the value stored at a given index and marks the slot as unfilled.
+  ///
+  /// Does nothing if the slot is not filled.
+  ///
+  /// # Panics
+  /// - Panics if the index is out of bounds.
+  pub fn clear(&mut self, index:usize) {
+    // Check whether the index is within bounds
+    assert!(index < self.len, "{}", IndexOutOfBounds { len:self.len, index });
+
+    if self.filled.get(index) {
+      self.filled.set(index, false);
+      unsafe { self.ty().drop(self.indexed_ptr(index)) }
+    }
+  }
+
+  /// Shortens the vector to `new_len`, dropping the filled slots past it.
+  ///
+  /// Does nothing if `new_len >= len`.
+  pub fn truncate(&mut self, new_len:usize) {
+    if new_len >= self.len {
+      return;
+    }
+
+    for index in new_len..self.len {
+      if self.filled.get(index) {
+        unsafe { self.ty().drop(self.indexed_ptr(index)) }
+      }
+    }
+
+    self.filled.truncate(new_len);
+    self.ticks.truncate(new_len);
+    self.len = new_len;
+  }
+
+  /// Drops every filled slot and empties the vector. The capacity is kept.
+  pub fn clear_all(&mut self) {
+    self.truncate(0);
+  }
+
+  /// Marks the slot at `index` as unfilled without dropping its value and
+  /// returns a pointer to the value.
+  ///
+  /// # Warning
+  /// - The caller takes ownership of the value and must move it out of the
+  ///   pointer before the slot is written to again.
+  ///
+  /// # Panics
+  /// - Panics if `index >= len`.
+  /// - Panics if the slot is not filled.
+  pub fn take_erased(&mut self, index:usize) -> *mut u8 {
+    assert!(index < self.len, "{}", IndexOutOfBounds { len:self.len, index });
+    assert!(self.filled.get(index), "Cannot take from unfilled slot {index}.");
+
+    self.filled.set(index, false);
+    unsafe { self.indexed_ptr(index) }
+  }
+
+  /// Removes the element at `index`, dropping it if the slot is filled, and
+  /// moves the last element into its place.
+  ///
+  /// # Panics
+  /// - Panics if `index >= len`.
+  pub fn swap_remove(&mut self, index:usize) {
+    assert!(index < self.len, "{}", IndexOutOfBounds { len:self.len, index });
+
+    if self.filled.get(index) {
+      unsafe { self.ty().drop(self.indexed_ptr(index)) }
+    }
+    self.swap_remove_slot(index);
+  }
+
+  /// Removes the element at `index` and returns it, moving the last element
+  /// into its place.
+  ///
+  /// # Panics
+  /// - Panics if the [`TypeInfo`] of `T` does not match the type contained in
+  ///   the `ErasedVec`.
+  /// - Panics if `index >= len`.
+  /// - Panics if the slot is not filled.
+  pub fn swap_remove_typed<T:'static>(&mut self, index:usize) -> T {
+    let value = self.read_typed::<T>(index);
+    self.swap_remove_slot(index);
+    value
+  }
+
+  /// Removes the element at `index`, dropping it if the slot is filled, and
+  /// shifts all elements after it to the left.
+  ///
+  /// # Panics
+  /// - Panics if `index >= len`.
+  pub fn remove(&mut self, index:usize) {
+    assert!(index < self.len, "{}", IndexOutOfBounds { len:self.len, index });
+
+    if self.filled.get(index) {
+      unsafe { self.ty().drop(self.indexed_ptr(index)) }
+    }
+    self.remove_slot(index);
+  }
+
+  /// Removes the element at `index` and returns it, shifting all elements
+  /// after it to the left.
+  ///
+  /// # Panics
+  /// - Panics if the [`TypeInfo`] of `T` does not match the type contained in
+  ///   the `ErasedVec`.
+  /// - Panics if `index >= len`.
+  /// - Panics if the slot is not filled.
+  pub fn remove_typed<T:'static>(&mut self, index:usize) -> T {
+    let value = self.read_typed::<T>(index);
+    self.remove_slot(index);
+    value
+  }
+
+  /// Swaps the elements at `a` and `b` along with their filled flags and
+  /// ticks. Nothing is dropped.
+  ///
+  /// # Panics
+  /// - Panics if `a >= len` or `b >= len`.
+  pub fn swap(&mut self, a:usize, b:usize) {
+    for index in [a, b] {
+      assert!(index < self.len, "{}", IndexOutOfBounds { len:self.len, index });
+    }
+
+    if a != b {
+      unsafe { ptr::swap_nonoverlapping(self.indexed_ptr::<u8>(a), self.indexed_ptr::<u8>(b), self.ty().size()) }
+      self.filled.swap(a, b);
+      self.ticks.swap(a, b);
+    }
+  }
+
+  /// Rotates the elements `n` places to the left, along with their filled
+  /// flags and ticks, so the element at `n` becomes the first. Nothing is
+  /// dropped.
+  ///
+  /// # Panics
+  /// - Panics if `n > len`.
+  pub fn rotate_left(&mut self, n:usize) {
+    assert!(n <= self.len, "{}", IndexOutOfBounds { len:self.len, index:n });
+
+    let size = self.ty().size();
+    self.bytes_mut().rotate_left(n * size);
+    self.filled.rotate_left(n);
+    self.ticks.rotate_left(n);
+  }
+
+  /// Rotates the elements `n` places to the right, along with their filled
+  /// flags and ticks, so the last `n` elements come first. Nothing is
+  /// dropped.
+  ///
+  /// # Panics
+  /// - Panics if `n > len`.
+  pub fn rotate_right(&mut self, n:usize) {
+    assert!(n <= self.len, "{}", IndexOutOfBounds { len:self.len, index:n });
+
+    let size = self.ty().size();
+    self.bytes_mut().rotate_right(n * size);
+    self.filled.rotate_right(n);
+    self.ticks.rotate_right(n);
+  }
+
+  /// Views the elements as raw bytes, padded slots included.
+  fn bytes_mut(&mut self) -> &mut [u8] {
+    unsafe { slice::from_raw_parts_mut(self.ptr(), self.len * self.ty().size()) }
+  }
+
+  /// Retains only the elements for which `f` returns `true`, dropping the
+  /// rest and shifting the survivors left in order.
+  ///
+  /// Padded slots hold no value to test so they are removed as well. If `f`
+  /// panics the untested elements are kept.
+  ///
+  /// # Panics
+  /// - Panics if the [`TypeInfo`] of `T` does not match the type contained in
+  ///   the `ErasedVec`.
+  pub fn retain<T:'static>(&mut self, mut f:impl FnMut(&T) -> bool) {
+    self.assert_type_info(TypeInfo::of::<T>());
+    self.retain_erased(|ptr| f(unsafe { &*ptr.cast::<T>() }))
+  }
+
+  /// Retains only the elements for which `f` returns `true` when passed a
+  /// pointer to them, dropping the rest and shifting the survivors left in
+  /// order.
+  ///
+  /// Padded slots are removed without calling `f`, see [`Self::retain`].
+  pub fn retain_erased(&mut self, mut f:impl FnMut(*const u8) -> bool) {
+    let len = self.len;
+    let mut guard = Retain {
+      vec:self,
+      read:0,
+      write:0,
+      len
+    };
+
+    while guard.read < len {
+      let read = guard.read;
+      let filled = guard.vec.filled.get(read);
+      let keep = filled && f(unsafe { guard.vec.indexed_ptr::<u8>(read) });
+
+      // The slot counts as processed before its value is dropped so a
+      // panicking drop is not dropped again
+      guard.read += 1;
+      if keep {
+        guard.vec.move_slot(read, guard.write);
+        guard.write += 1;
+      } else if filled {
+        unsafe { guard.vec.ty().drop(guard.vec.indexed_ptr(read)) }
+      }
+    }
+  }
+
+  /// Moves the slot at `src` over the slot at `dst` without dropping either.
+  /// The flags and ticks left at `src` are stale.
+  fn move_slot(&mut self, src:usize, dst:usize) {
+    if src != dst {
+      unsafe { ptr::copy_nonoverlapping(self.indexed_ptr::<u8>(src), self.indexed_ptr::<u8>(dst), self.ty().size()) }
+      self.filled.set(dst, self.filled.get(src));
+      self.ticks.swap(dst, src);
+    }
+  }
+
+  /// Reads the value at `index` out of the [`ErasedVec`]. The slot must be
+  /// removed afterwards or the value will be dropped twice.
+  fn read_typed<T:'static>(&self, index:usize) -> T {
+    self.assert_type_info(TypeInfo::of::<T>());
+    assert!(index < self.len, "{}", IndexOutOfBounds { len:self.len, index });
+    assert!(self.filled.get(index), "Cannot take from unfilled slot {index}.");
+
+    unsafe { ptr::read(self.indexed_ptr::<T>(index)) }
+  }
+
+  /// Moves the last slot over the slot at `index` without dropping either.
+  fn swap_remove_slot(&mut self, index:usize) {
+    let last = self.len - 1;
+    if index != last {
+      unsafe { ptr::copy_nonoverlapping(self.indexed_ptr::<u8>(last), self.indexed_ptr::<u8>(index), self.ty().size()) }
+    }
+
+    self.filled.swap_remove(index);
+    self.ticks.swap_remove(index);
+    self.len -= 1;
+  }
+
+  /// Shifts the slots after `index` left over it without dropping any.
+  fn remove_slot(&mut self, index:usize) {
+    unsafe {
+      let count = (self.len - index - 1) * self.ty().size();
+      ptr::copy(self.indexed_ptr::<u8>(index + 1), self.indexed_ptr::<u8>(index), count);
+    }
+
+    self.filled.remove(index);
+    self.ticks.remove(index);
+    self.len -= 1;
+  }
+
+  /// Clones the value at `index` into the uninitialized memory at `out`
+  /// using the [`TypeInfo`]'s clone shim.
+  ///
+  /// Returns an error if the type is not cloneable or the slot is not
+  /// filled.
+  ///
+  /// # Warning
+  /// - `out` must be valid for writes of `self.ty().size()` bytes. The caller
+  ///   owns the clone.
+  #[allow(clippy::not_unsafe_ptr_arg_deref)]
+  pub fn clone_slot(&self, index:usize, out:*mut u8) -> Result<()> {
+    let clone = self.ty().clone_shim().ok_or_else(|| NotCloneable(self.ty().name()))?;
+    if !self.is_filled(index) {
+      return Err(UnfilledSlot { index }.into());
+    }
+
+    unsafe { clone(self.indexed_ptr::<u8>(index), out) };
+    Ok(())
+  }
+
+  /// Clones the value at `src` into the slot at `dst`, dropping the value
+  /// `dst` held.
+  ///
+  /// Returns an error if the type is not cloneable or `src` is not filled.
+  ///
+  /// # Panics
+  /// - Panics if `dst >= len`.
+  pub fn clone_within(&mut self, src:usize, dst:usize) -> Result<()> {
+    assert!(dst < self.len, "{}", IndexOutOfBounds { len:self.len, index:dst });
+    if src == dst {
+      return Ok(());
+    }
+
+    self.clear(dst);
+    self.clone_slot(src, unsafe { self.indexed_ptr::<u8>(dst) })?;
+    self.filled.set(dst, true);
+    self.ticks[dst] = ComponentTicks::new(self.tick);
+    Ok(())
+  }
+
+  /// Deep copies the [`ErasedVec`] using its [`TypeInfo`]'s clone shim.
+  ///
+  /// Unfilled slots are padded in the copy. Returns `None` if the stored type
+  /// is not cloneable.
+  pub fn try_clone(&self) -> Option<ErasedVec> {
+    let clone = self.ty().clone_shim()?;
+    let mut copy = ErasedVec::new_erased(self.ty());
+    copy.tick = self.tick;
+
+    for index in 0..self.len {
+      copy.pad();
+      if self.filled.get(index) {
+        unsafe { clone(self.indexed_ptr::<u8>(index), copy.indexed_ptr::<u8>(index)) };
+        copy.filled.set(index, true);
+      }
+    }
+    copy.ticks.clone_from(&self.ticks);
+
+    Some(copy)
+  }
+
+  /// Replaces the [`TypeInfo`] describing the stored type. Used to attach
+  /// optional shims after the vector was created.
+  ///
+  /// # Panics
+  /// - Panics if `ty` describes a different type.
+  pub(crate) fn set_ty(&mut self, ty:TypeInfo) {
+    self.assert_type_info_insert(ty);
+    self.buf.ty = ty;
+  }
+
+  ///Panics if the queried [`TypeInfo`] is not the same as the data the
+  /// [`ErasedVec`] holds.
+  fn assert_type_info_insert(&self, ty:TypeInfo) {
+    assert_eq!(
+      ty,
+      self.ty(),
+      "{}",
+      IncorrectTypeInsertion {
+        insert_type:ty.name(),
+        vec_type:self.ty().name()
+      }
+    );
+  }
+
+  ///Panics if the queried [`TypeInfo`] is not the same as the data the
+  /// [`ErasedVec`] holds.
+  fn assert_type_info(&self, ty:TypeInfo) {
+    assert_eq!(ty, self.ty(), "{}", DoesNotContainType(ty.name()));
+  }
+}
+
+/// Tracks the progress of [`ErasedVec::retain_erased`].
+///
+/// Slots before `write` were kept and slots from `read` to `len` have not
+/// been processed yet. On drop, including when the predicate panics, the
+/// unprocessed slots are shifted down after the kept ones and the vector is
+/// shortened to fit.
+struct Retain<'a> {
+  vec:&'a mut ErasedVec,
+  read:usize,
+  write:usize,
+  len:usize
+}
+
+impl Drop for Retain<'_> {
+  fn drop(&mut self) {
+    let remaining = self.len - self.read;
+    for offset in 0..remaining {
+      self.vec.move_slot(self.read + offset, self.write + offset);
+    }
+
+    let len = self.write + remaining;
+    self.vec.filled.truncate(len);
+    self.vec.ticks.truncate(len);
+    self.vec.len = len;
+  }
+}
+
+impl fmt::Debug for ErasedVec {
+  /// Lists the filled slots by index. Values whose type has no debug shim
+  /// render as `TypeName(opaque)`.
+  fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result {
+    f.debug_map()
+      .entries(
+        (0..self.len)
+          .filter(|index| self.filled.get(*index))
+          .map(|index| (index, unsafe { self.ty().debug_value(self.indexed_ptr::<u8>(index)) }))
+      )
+      .finish()
+  }
+}
+
+impl Drop for ErasedVec {
+  fn drop(&mut self) {
+    for index in 0..self.len {
+      if self.filled.get(index) {
+        unsafe { self.ty().drop(self.indexed_ptr(index)) }
+      }
+    }
+  }
+}
+
+/// Which slots of an [`ErasedVec`] hold a value.
+enum Filled {
+  /// A flag per slot.
+  Tracked(Vec<bool>),
+  /// Every slot is filled, used for markers.
+  Untracked
+}
+
+impl Filled {
+  fn new(ty:TypeInfo) -> Self {
+    if ty.size() == 0 && !ty.needs_drop() {
+      Filled::Untracked
+    } else {
+      Filled::Tracked(Vec::new())
+    }
+  }
+
+  /// Returns `true` if the slot at `index` is filled.
+  ///
+  /// # Panics
+  /// - Panics if a tracked `index` is out of bounds.
+  fn get(&self, index:usize) -> bool {
+    match self {
+      Filled::Tracked(flags) => flags[index],
+      Filled::Untracked => true
+    }
+  }
+
+  fn set(&mut self, index:usize, filled:bool) {
+    if let Filled::Tracked(flags) = self {
+      flags[index] = filled;
     }
+  }
 
-    // Check whether the index is within bounds
-    assert!(index <= self.len, "{}", IndexOutOfBounds { len:self.len, index });
+  fn push(&mut self, filled:bool) {
+    if let Filled::Tracked(flags) = self {
+      flags.push(filled);
+    }
+  }
 
-    self.assert_type_info_insert(ty);
+  fn pop(&mut self) {
+    if let Filled::Tracked(flags) = self {
+      flags.pop();
+    }
+  }
 
-    // Destroy the data currently there. This is unnecessary for non-collection
-    // types but collections cause a leak unless destroyed.
+  fn insert(&mut self, index:usize, filled:bool) {
+    if let Filled::Tracked(flags) = self {
+      flags.insert(index, filled);
+    }
+  }
 
-    unsafe {
-      // Copy the value as raw bits into the `ErasedVec`
-      ptr::copy_nonoverlapping(ptr, self.indexed_ptr(index), self.ty().size());
+  fn remove(&mut self, index:usize) {
+    if let Filled::Tracked(flags) = self {
+      flags.remove(index);
+    }
+  }
+
+  fn swap_remove(&mut self, index:usize) {
+    if let Filled::Tracked(flags) = self {
+      flags.swap_remove(index);
     }
+  }
 
-    self.filled[index] = true;
+  fn swap(&mut self, a:usize, b:usize) {
+    if let Filled::Tracked(flags) = self {
+      flags.swap(a, b);
+    }
   }
 
-  /// Sets the `index` within the vector.
-  ///
-  /// Destroys the data currently at `index` to prevent leaking.
-  ///
-  /// # Panics
-  /// - Panics if `index > len`.
-  /// - Panics if `ty` != `self.ty()`
-  pub fn reset_erased(&mut self, index:usize, ty:TypeInfo, ptr:*mut u8) {
-    unsafe { self.clear(index) };
-    self.set_erased(index, ty, ptr);
+  fn rotate_left(&mut self, n:usize) {
+    if let Filled::Tracked(flags) = self {
+      flags.rotate_left(n);
+    }
   }
 
-  /// Drops the value stored at a given index.
-  ///
-  /// # Panics
-  /// - Panics if the index is out of bounds.
-  pub unsafe fn clear(&self, index:usize) {
-    // Check whether the index is within bounds
-    assert!(index <= self.len, "{}", IndexOutOfBounds { len:self.len, index });
+  fn rotate_right(&mut self, n:usize) {
+    if let Filled::Tracked(flags) = self {
+      flags.rotate_right(n);
+    }
+  }
 
-    self.ty().drop(self.indexed_ptr(index));
+  fn truncate(&mut self, len:usize) {
+    if let Filled::Tracked(flags) = self {
+      flags.truncate(len);
+    }
   }
 
-  ///Panics if the queried [`TypeInfo`] is not the same as the data the
-  /// [`ErasedVec`] holds.
-  fn assert_type_info_insert(&self, ty:TypeInfo) {
-    assert_eq!(
-      ty,
-      self.ty(),
-      "{}",
-      IncorrectTypeInsertion {
-        insert_type:ty.name(),
-        vec_type:self.ty().name()
-      }
-    );
+  fn reserve(&mut self, additional:usize) {
+    if let Filled::Tracked(flags) = self {
+      flags.reserve(additional);
+    }
   }
 
-  ///Panics if the queried [`TypeInfo`] is not the same as the data the
-  /// [`ErasedVec`] holds.
-  fn assert_type_info(&self, ty:TypeInfo) {
-    assert_eq!(ty, self.ty(), "{}", DoesNotContainType(ty.name()));
+  fn reserve_exact(&mut self, additional:usize) {
+    if let Filled::Tracked(flags) = self {
+      flags.reserve_exact(additional);
+    }
   }
-}
 
-impl Drop for ErasedVec {
-  fn drop(&mut self) {
-    for index in 0..self.len {
-      if self.filled[index] {
-        unsafe { self.ty().drop(self.indexed_ptr(index)) }
-      }
+  fn try_reserve(&mut self, additional:usize) -> Result<(), ErasedVecErrors> {
+    match self {
+      Filled::Tracked(flags) => flags.try_reserve(additional).map_err(|_| ErasedVecAllocError),
+      Filled::Untracked => Ok(())
     }
   }
 }
@@ -448,7 +1304,10 @@ impl ErasedBox {
   pub fn new<T:'static>(value:T) -> Self {
     // Create the buf
     let mut buf = RawErasedVec::new::<T>();
-    buf.grow_exact(1);
+    // Zero-sized values need no allocation
+    if mem::size_of::<T>() != 0 {
+      buf.grow_exact(1);
+    }
 
     // Allocate space in the buf and insert the data into it
     unsafe {
@@ -462,10 +1321,17 @@ impl ErasedBox {
     ErasedBox(buf)
   }
 
+  /// Create an [`ErasedBox`] by copying `ty.size()` bytes from `ptr`.
+  ///
+  /// The box takes ownership of the value, the caller must not drop the
+  /// source.
   pub fn from_raw_parts(ty:TypeInfo, ptr:*mut u8) -> Self {
     // Create the buf
     let mut buf = RawErasedVec::new_erased(ty);
-    buf.grow_exact(1);
+    // Zero-sized values need no allocation
+    if ty.size() != 0 {
+      buf.grow_exact(1);
+    }
 
     // Allocate space in the buf and insert the data into it
     unsafe {
@@ -508,6 +1374,7 @@ impl ErasedBox {
   /// in the `ErasedVec`.
   ///
   /// Panics if `index` > `self.len`
+  #[allow(clippy::mut_from_ref)]
   pub fn get_mut<T:'static>(&self) -> &mut T {
     // Confirm the vector contains `T`
     self.assert_type_info(TypeInfo::of::<T>());
@@ -516,6 +1383,84 @@ impl ErasedBox {
     unsafe { &mut *(self.ptr() as *mut T) }
   }
 
+  ///Moves the value out of the [`ErasedBox`] and frees its buffer without
+  /// running the drop shim.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the [`TypeInfo`] of `T` does not match the type contained in
+  /// the `ErasedBox`.
+  pub fn into_inner<T:'static>(self) -> T {
+    self.assert_type_info(TypeInfo::of::<T>());
+
+    let value = unsafe { ptr::read(self.ptr() as *const T) };
+    self.forget();
+    value
+  }
+
+  ///Replaces the [`ErasedBox`]'s value with `value` and returns the old
+  /// value.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the [`TypeInfo`] of `T` does not match the type contained in
+  /// the `ErasedBox`.
+  pub fn replace<T:'static>(&mut self, value:T) -> T {
+    mem::replace(self.get_mut::<T>(), value)
+  }
+
+  ///Swaps the [`ErasedBox`]'s value with the type-erased value at `ptr`.
+  ///
+  /// # Warning
+  /// - The box takes ownership of the value at `ptr` and the caller takes
+  ///   ownership of the old value which is written to `ptr`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `ty` does not match the type contained in the `ErasedBox`.
+  #[allow(clippy::not_unsafe_ptr_arg_deref)]
+  pub fn replace_erased(&mut self, ty:TypeInfo, ptr:*mut u8) {
+    self.assert_type_info(ty);
+    unsafe { ptr::swap_nonoverlapping(ptr, self.ptr(), ty.size()) }
+  }
+
+  ///Consumes the [`ErasedBox`] and returns the [`TypeInfo`] and pointer of
+  /// its value.
+  ///
+  /// # Warning
+  /// - The caller takes ownership of the value and its buffer. The buffer was
+  ///   allocated with `ty.layout()` and must be freed with it unless the type
+  ///   is zero-sized.
+  pub fn into_raw_parts(self) -> (TypeInfo, *mut u8) {
+    let this = mem::ManuallyDrop::new(self);
+    (this.ty(), this.ptr())
+  }
+
+  ///Frees the [`ErasedBox`]'s buffer without running the drop shim.
+  ///
+  /// Used once the value has been moved out of the box.
+  pub fn forget(self) {
+    let this = mem::ManuallyDrop::new(self);
+    // Dropping the buffer deallocates it without dropping the value
+    drop(unsafe { ptr::read(&this.0) });
+  }
+
+  /// Deep copies the [`ErasedBox`] using its [`TypeInfo`]'s clone shim.
+  ///
+  /// Returns `None` if the stored type is not cloneable.
+  pub fn try_clone(&self) -> Option<ErasedBox> {
+    let clone = self.ty().clone_shim()?;
+    let mut buf = RawErasedVec::new_erased(self.ty());
+    // Zero-sized values need no allocation
+    if self.ty().size() != 0 {
+      buf.grow_exact(1);
+    }
+
+    unsafe { clone(self.ptr(), buf.ptr.as_ptr()) };
+
+    Some(ErasedBox(buf))
+  }
+
   ///Panics if the queried [`TypeInfo`] is not the same as the data the
   /// [`ErasedVec`] holds.
   fn assert_type_info(&self, ty:TypeInfo) {
@@ -536,50 +1481,51 @@ pub struct NoDropTuple {
   buf:NonNull<u8>,
   layout:Layout,
   tys:Vec<TypeInfo>,
-  field_size:usize,
+  ///The offset of each field from the start of the buffer.
+  offsets:Vec<usize>,
   len:usize
 }
 
 impl NoDropTuple {
-  pub fn new<B:Bundle>(tuple:B) -> Self {
+  pub fn new<B:ErasedBundle>(tuple:B) -> Self {
+    let tys = tuple.component_types();
+
+    // Lay the fields out like a `repr(C)` struct so each is aligned
+    let mut layout = Layout::from_size_align(0, 1).unwrap();
+    let mut offsets = Vec::with_capacity(tys.len());
+    for ty in &tys {
+      let (extended, offset) = layout.extend(ty.layout()).unwrap();
+      layout = extended;
+      offsets.push(offset);
+    }
+
     // Create the "tuple"
     let mut no_drop = NoDropTuple {
       buf:NonNull::dangling(),
-      layout:unsafe { Layout::from_size_align_unchecked(0, 2) },
-      tys:B::types(),
-      field_size:0,
-      len:B::LENGTH
+      layout:layout.pad_to_align(),
+      len:tys.len(),
+      tys,
+      offsets
     };
 
-    // Size of the `NoDropTuple`'s largest element
-    no_drop.field_size = no_drop.tys.iter().max_by(|ty_1, ty_2| ty_1.size().cmp(&ty_2.size())).unwrap().size();
-    // Align of the `NoDropTuple`'s largest element
-    let align = no_drop
-      .tys
-      .iter()
-      .max_by(|ty_1, ty_2| ty_1.layout().align().cmp(&ty_2.layout().align()))
-      .unwrap()
-      .layout()
-      .align();
-
-    // Update the layout
-    // `NoDropTuple`'s size is the size of the largest element * the number of
-    // elements
-    no_drop.layout = Layout::from_size_align(no_drop.field_size * no_drop.len, align).unwrap();
-
-    // Allocate space
-    no_drop.buf = match NonNull::new(unsafe { alloc::alloc(no_drop.layout) }) {
-      Some(p) => p,
-      None => alloc::handle_alloc_error(no_drop.layout)
+    // Allocate space. Empty and zero-sized tuples only need a dangling pointer
+    // aligned for their fields
+    no_drop.buf = if no_drop.layout.size() == 0 {
+      NonNull::new(ptr::without_provenance_mut(no_drop.layout.align())).unwrap()
+    } else {
+      match NonNull::new(unsafe { alloc::alloc(no_drop.layout) }) {
+        Some(p) => p,
+        None => alloc::handle_alloc_error(no_drop.layout)
+      }
     };
 
     // Copy tuple data into the allocated space
     let mut cursor = 0;
     unsafe {
       tuple
-        .put(|ptr, ty| {
+        .put_erased(|ptr, ty| {
           // Copy the data to its "field" in the `NoDropTuple`
-          ptr::copy_nonoverlapping(ptr, no_drop.ptr().add(cursor * no_drop.field_size), ty.size());
+          ptr::copy_nonoverlapping(ptr, no_drop.ptr().add(no_drop.offsets[cursor]), ty.size());
 
           //Iterate the cursor
           cursor += 1;
@@ -599,11 +1545,15 @@ impl NoDropTuple {
     self.len
   }
 
+  pub fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+
   ///Returns the [`TypeInfo`] and pointer of the item stored in the tuple at
   /// the requested index.
   pub fn get(&self, index:usize) -> (TypeInfo, *mut u8) {
     let ty = self.tys[index];
-    let ptr = unsafe { self.ptr().add(self.field_size * index) };
+    let ptr = unsafe { self.ptr().add(self.offsets[index]) };
     (ty, ptr)
   }
 }
@@ -611,14 +1561,25 @@ impl NoDropTuple {
 impl Drop for NoDropTuple {
   fn drop(&mut self) {
     // Deallocate the buffer but do not call drop on the buff's contents
-    unsafe { alloc::dealloc(self.ptr(), self.layout) }
+    if self.layout.size() != 0 {
+      unsafe { alloc::dealloc(self.ptr(), self.layout) }
+    }
   }
 }
 
 #[cfg(test)]
+#[allow(clippy::excessive_precision)]
 mod test {
   use super::*;
-  use crate::storage::type_info::TypeInfo;
+  use crate::{
+    errors::{ErasedVecErrors, NinaError},
+    storage::type_info::TypeInfo
+  };
+  use std::{
+    cell::Cell,
+    rc::Rc,
+    sync::atomic::{AtomicU32, Ordering}
+  };
 
   #[test]
   fn push_into_erasedvec_and_read() {
@@ -680,13 +1641,17 @@ mod test {
 
     //Confirm pushing normally works
     assert_eq!(player_vec.len, 6);
+    // Markers do not track their slots, padding reads as a marker
+    assert!(!player_vec.tracks_filled());
+    assert!(player_vec.is_filled(2));
+    assert!(!player_vec.is_filled(6));
 
     assert_eq!(*player_vec.get::<Player>(0), Player);
     assert_eq!(*player_vec.get::<Player>(1), Player);
-    assert_eq!(unsafe { *player_vec.get_unchecked::<[u8; 0]>(2) }, []);
+    assert_eq!(*player_vec.get::<Player>(2), Player);
     assert_eq!(*player_vec.get::<Player>(3), Player);
-    assert_eq!(unsafe { *player_vec.get_unchecked::<[u8; 0]>(4) }, []);
-    assert_eq!(unsafe { *player_vec.get_unchecked::<[u8; 0]>(5) }, []);
+    assert_eq!(*player_vec.get::<Player>(4), Player);
+    assert_eq!(*player_vec.get::<Player>(5), Player);
 
     let mut player_vec = ErasedVec::new::<Player>();
     let ty:TypeInfo = TypeInfo::of::<Player>();
@@ -699,15 +1664,62 @@ mod test {
 
     //Confirm pushing erased works
     assert_eq!(player_vec.len, 6);
-    assert_eq!(player_vec.len, 6);
-    assert_eq!(player_vec.len, 6);
 
     assert_eq!(*player_vec.get::<Player>(0), Player);
     assert_eq!(*player_vec.get::<Player>(1), Player);
-    assert_eq!(unsafe { *player_vec.get_unchecked::<[u8; 0]>(2) }, []);
+    assert_eq!(*player_vec.get::<Player>(2), Player);
     assert_eq!(*player_vec.get::<Player>(3), Player);
-    assert_eq!(unsafe { *player_vec.get_unchecked::<[u8; 0]>(4) }, []);
-    assert_eq!(unsafe { *player_vec.get_unchecked::<[u8; 0]>(5) }, []);
+    assert_eq!(*player_vec.get::<Player>(4), Player);
+    assert_eq!(*player_vec.get::<Player>(5), Player);
+  }
+
+  #[test]
+  fn marker_columns_do_not_allocate() {
+    let mut player_vec = ErasedVec::new::<Player>();
+    let dangling = player_vec.ptr();
+    for _ in 0..1000 {
+      player_vec.push(Player);
+      player_vec.pad();
+    }
+    player_vec.set(1, Player);
+    player_vec.clear(2);
+    player_vec.swap_remove(3);
+    player_vec.remove(0);
+
+    assert_eq!(player_vec.len(), 1998);
+    assert_eq!(player_vec.capacity(), usize::MAX);
+    assert_eq!(player_vec.ptr(), dangling);
+    assert_eq!(dangling as usize, mem::align_of::<Player>());
+    assert!(matches!(player_vec.filled, Filled::Untracked));
+    // Only the ticks are stored, one pair per slot
+    assert_eq!(
+      player_vec.allocated_bytes(),
+      player_vec.ticks.capacity() * mem::size_of::<ComponentTicks>()
+    );
+    assert_eq!(player_vec.iter::<Player>().flatten().count(), 1998);
+    assert_eq!(player_vec.as_slice::<Player>().unwrap().len(), 1998);
+  }
+
+  #[test]
+  fn zero_sized_types_with_drop_glue_track_their_slots() {
+    static DROPS:AtomicU32 = AtomicU32::new(0);
+    struct Guard;
+    impl Drop for Guard {
+      fn drop(&mut self) {
+        DROPS.fetch_add(1, Ordering::Relaxed);
+      }
+    }
+
+    let mut guards = ErasedVec::new::<Guard>();
+    assert!(guards.tracks_filled());
+    guards.push(Guard);
+    guards.pad();
+    guards.push(Guard);
+    assert!(!guards.is_filled(1));
+    assert_eq!(guards.capacity(), usize::MAX);
+
+    drop(guards);
+    assert_eq!(DROPS.load(Ordering::Relaxed), 2);
   }
 
   #[test]
@@ -804,6 +1816,398 @@ mod test {
     assert_eq!(health.max, 400);
   }
 
+  #[test]
+  fn removing_from_erasedvec_moves_and_drops_values() {
+    let mut vec = ErasedVec::new::<String>();
+    for name in ["a", "b", "c", "d", "e"] {
+      vec.push(name.repeat(32));
+    }
+    vec.pad();
+
+    // Dropping an unfilled slot does nothing
+    vec.swap_remove(5);
+    vec.swap_remove(0);
+    assert_eq!(vec.len(), 4);
+    assert_eq!(*vec.get::<String>(0), "e".repeat(32));
+
+    vec.remove(1);
+    assert_eq!(vec.len(), 3);
+    assert_eq!(*vec.get::<String>(1), "c".repeat(32));
+    assert_eq!(*vec.get::<String>(2), "d".repeat(32));
+
+    // The typed variants hand the value back
+    assert_eq!(vec.swap_remove_typed::<String>(0), "e".repeat(32));
+    assert_eq!(*vec.get::<String>(0), "d".repeat(32));
+    assert_eq!(vec.remove_typed::<String>(0), "d".repeat(32));
+    assert_eq!(vec.swap_remove_typed::<String>(0), "c".repeat(32));
+    assert!(vec.is_empty());
+
+    let mut players = ErasedVec::new::<Player>();
+    players.push(Player);
+    players.pad();
+    players.remove(0);
+    assert_eq!(players.len(), 1);
+  }
+
+  #[test]
+  fn popping_from_erasedvec_returns_ownership() {
+    let mut vec = ErasedVec::new::<String>();
+    vec.push("a".repeat(32));
+    vec.pad();
+    vec.push("b".repeat(32));
+
+    assert_eq!(vec.pop::<String>(), Some("b".repeat(32)));
+    // The padded slot is removed without returning a value
+    assert_eq!(vec.pop::<String>(), None);
+    assert_eq!(vec.len(), 1);
+
+    let mut out = mem::MaybeUninit::<String>::uninit();
+    assert_eq!(vec.pop_erased(out.as_mut_ptr().cast()), Some(TypeInfo::of::<String>()));
+    assert_eq!(unsafe { out.assume_init() }, "a".repeat(32));
+    assert_eq!(vec.pop::<String>(), None);
+    assert!(vec.is_empty());
+  }
+
+  #[test]
+  fn reserved_capacity_is_not_regrown() {
+    let mut vec = ErasedVec::with_capacity::<Health>(10);
+    assert_eq!(vec.capacity(), 10);
+
+    vec.reserve(1000);
+    let capacity = vec.capacity();
+    assert!(capacity >= 1000);
+    for health in 0..1000 {
+      vec.push(Health::new(health));
+    }
+    assert_eq!(vec.capacity(), capacity);
+
+    vec.pop::<Health>();
+    vec.shrink_to_fit();
+    assert_eq!(vec.capacity(), 999);
+    assert_eq!(vec.get::<Health>(998).max, 998);
+
+    vec.reserve_exact(3);
+    assert_eq!(vec.capacity(), 1002);
+
+    let mut players = ErasedVec::with_capacity::<Player>(10);
+    players.shrink_to_fit();
+    assert_eq!(players.capacity(), usize::MAX);
+  }
+
+  #[test]
+  fn padded_slots_are_not_filled() {
+    let mut vec = ErasedVec::new::<Health>();
+    vec.push(Health::new(1));
+    vec.pad();
+
+    assert!(vec.is_filled(0));
+    assert!(!vec.is_filled(1));
+    assert!(!vec.is_filled(2));
+    assert_eq!(vec.try_get::<Health>(0).unwrap().max, 1);
+    assert!(vec.try_get::<Health>(1).is_none());
+    assert!(vec.try_get_mut::<Health>(2).is_none());
+
+    vec.try_get_mut::<Health>(0).unwrap().max = 2;
+    assert_eq!(vec.get::<Health>(0).max, 2);
+  }
+
+  #[test]
+  fn iterate_a_column_with_padded_slots() {
+    let mut vec = ErasedVec::new::<String>();
+    vec.push("a".to_string());
+    vec.pad();
+    vec.push("b".to_string());
+    vec.pad();
+
+    for name in vec.iter_mut::<String>().flatten() {
+      name.push('!');
+    }
+    let names = vec.iter::<String>().map(|name| name.cloned()).collect::<Vec<_>>();
+    assert_eq!(names, vec![Some("a!".to_string()), None, Some("b!".to_string()), None]);
+  }
+
+  #[test]
+  fn dense_columns_can_be_viewed_as_slices() {
+    let mut vec = ErasedVec::new::<u32>();
+    assert!(vec.as_slice::<u32>().unwrap().is_empty());
+    for value in [4_u32, 1, 3, 2] {
+      vec.push(value);
+    }
+    vec.as_mut_slice::<u32>().unwrap().sort();
+    assert_eq!(vec.as_slice::<u32>().unwrap(), &[1, 2, 3, 4]);
+    assert!(vec.as_slice::<i32>().is_err());
+
+    // A hole makes the column sparse until it is compacted
+    vec.clear(1);
+    let error = vec.as_slice::<u32>().unwrap_err();
+    assert!(matches!(error, NinaError::ErasedVec(ErasedVecErrors::SparseColumn { index:1, .. })));
+    vec.swap_remove(1);
+    assert_eq!(vec.as_slice::<u32>().unwrap(), &[1, 4, 3]);
+
+    let mut players = ErasedVec::new::<Player>();
+    players.push(Player);
+    assert_eq!(players.as_slice::<Player>().unwrap(), &[Player]);
+  }
+
+  #[test]
+  fn truncating_drops_only_filled_slots() {
+    let drops = Rc::new(Cell::new(0));
+    let mut vec = ErasedVec::new::<Buffer>();
+    for _ in 0..3 {
+      vec.push(Buffer::new(&drops));
+      vec.pad();
+    }
+
+    // Drops the third buffer and skips the padded slots
+    vec.truncate(4);
+    assert_eq!((vec.len(), drops.get()), (4, 1));
+    vec.truncate(10);
+    assert_eq!(vec.len(), 4);
+
+    vec.clear_all();
+    assert!(vec.is_empty());
+    assert_eq!(drops.get(), 3);
+
+    // The vector is reusable once cleared
+    vec.push(Buffer::new(&drops));
+    drop(vec);
+    assert_eq!(drops.get(), 4);
+  }
+
+  #[test]
+  fn retain_drops_rejected_elements() {
+    let mut vec = ErasedVec::new::<String>();
+    for word in ["keep", "drop", "keep too", "drop too", "last"] {
+      vec.push(word.to_string());
+    }
+    vec.retain::<String>(|word| !word.starts_with("drop"));
+
+    let words = vec.iter::<String>().map(|word| word.unwrap().as_str()).collect::<Vec<_>>();
+    assert_eq!(words, ["keep", "keep too", "last"]);
+
+    // Survivors keep their ticks
+    vec.set_tick(5);
+    vec.push("new".to_string());
+    vec.retain::<String>(|word| word != "keep");
+    assert_eq!(vec.len(), 3);
+    assert_eq!(vec.ticks(2).added(), 5);
+    assert_eq!(vec.get::<String>(2), "new");
+  }
+
+  #[test]
+  fn retain_removes_padded_slots() {
+    let drops = Rc::new(Cell::new(0));
+    let mut vec = ErasedVec::new::<Buffer>();
+    for _ in 0..3 {
+      vec.pad();
+      vec.push(Buffer::new(&drops));
+    }
+
+    let mut seen = 0;
+    vec.retain_erased(|_| {
+      seen += 1;
+      seen != 2
+    });
+    assert_eq!((seen, vec.len(), drops.get()), (3, 2, 1));
+    assert!(vec.is_filled(0) && vec.is_filled(1));
+
+    drop(vec);
+    assert_eq!(drops.get(), 3);
+  }
+
+  #[test]
+  fn retain_keeps_untested_elements_when_the_predicate_panics() {
+    let drops = Rc::new(Cell::new(0));
+    let mut vec = ErasedVec::new::<Buffer>();
+    for _ in 0..4 {
+      vec.push(Buffer::new(&drops));
+    }
+    vec.pad();
+
+    let mut tested = 0;
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+      vec.retain::<Buffer>(|_| {
+        tested += 1;
+        match tested {
+          1 => false,
+          3 => panic!("predicate failed"),
+          _ => true
+        }
+      })
+    }));
+    assert!(result.is_err());
+
+    // The first buffer was dropped and the rest, padding included, shifted
+    // down
+    assert_eq!((vec.len(), drops.get()), (4, 1));
+    assert!((0..3).all(|index| vec.is_filled(index)));
+    assert!(!vec.is_filled(3));
+
+    drop(vec);
+    assert_eq!(drops.get(), 4);
+  }
+
+  #[test]
+  fn swap_and_rotate_move_values_without_dropping() {
+    let drops = Rc::new(Cell::new(0));
+    let mut vec = ErasedVec::new::<Buffer>();
+    vec.push(Buffer::new(&drops));
+    vec.pad();
+    vec.push(Buffer::new(&drops));
+
+    vec.swap(0, 1);
+    vec.swap(2, 2);
+    assert!(!vec.is_filled(0) && vec.is_filled(1) && vec.is_filled(2));
+    vec.rotate_left(1);
+    assert!(vec.is_filled(0) && vec.is_filled(1) && !vec.is_filled(2));
+    vec.rotate_right(3);
+    vec.rotate_left(0);
+    assert!(!vec.is_filled(2));
+    assert_eq!(drops.get(), 0);
+
+    drop(vec);
+    assert_eq!(drops.get(), 2);
+  }
+
+  #[test]
+  fn reorder_a_string_column() {
+    let mut vec = ErasedVec::new::<String>();
+    for (tick, word) in ["a", "b", "c", "d"].into_iter().enumerate() {
+      vec.set_tick(tick as Tick);
+      vec.push(word.to_string());
+    }
+    let words = |vec:&ErasedVec| vec.iter::<String>().map(|word| word.unwrap().clone()).collect::<Vec<_>>();
+
+    vec.swap(0, 3);
+    assert_eq!(words(&vec), ["d", "b", "c", "a"]);
+    assert_eq!(vec.ticks(0).added(), 3);
+
+    vec.rotate_left(1);
+    assert_eq!(words(&vec), ["b", "c", "a", "d"]);
+    vec.rotate_right(2);
+    assert_eq!(words(&vec), ["a", "d", "b", "c"]);
+    assert_eq!(vec.ticks(1).added(), 3);
+  }
+
+  #[test]
+  #[should_panic(expected = "Cannot access index 2 past the end")]
+  fn swap_past_len_panics() {
+    let mut vec = ErasedVec::new::<String>();
+    vec.push("a".to_string());
+    vec.push("b".to_string());
+    vec.swap(0, 2);
+  }
+
+  #[test]
+  #[should_panic(expected = "Cannot access index 3 past the end")]
+  fn rotate_past_len_panics() {
+    let mut vec = ErasedVec::new::<String>();
+    vec.push("a".to_string());
+    vec.rotate_left(3);
+  }
+
+  /// A runtime-defined type `size` bytes large.
+  fn huge(size:usize) -> TypeInfo {
+    unsafe fn drop_huge(_:*mut u8) {}
+    TypeInfo::from_raw_parts(1, Layout::from_size_align(size, 1).unwrap(), drop_huge, "Huge")
+  }
+
+  #[test]
+  fn fallible_reserves_report_overflow() {
+    let mut vec = ErasedVec::new_erased(huge(1 << 60));
+    assert!(matches!(vec.try_reserve(16), Err(ErasedVecErrors::ErasedVecCapacityOverflow)));
+    assert!(matches!(vec.try_reserve(usize::MAX), Err(ErasedVecErrors::ErasedVecCapacityOverflow)));
+    assert_eq!((vec.len(), vec.capacity()), (0, 0));
+
+    let mut vec = ErasedVec::new::<String>();
+    vec.try_push("a".to_string()).unwrap();
+    assert!(matches!(vec.try_reserve(usize::MAX), Err(ErasedVecErrors::ErasedVecCapacityOverflow)));
+    assert!(matches!(
+      vec.try_reserve(isize::MAX as usize),
+      Err(ErasedVecErrors::ErasedVecCapacityOverflow)
+    ));
+
+    // The vector is untouched and usable after a failed reserve
+    vec.try_reserve(8).unwrap();
+    assert!(vec.capacity() >= 9);
+    vec.try_push("b".to_string()).unwrap();
+    assert_eq!(vec.iter::<String>().flatten().collect::<Vec<_>>(), ["a", "b"]);
+  }
+
+  // Miri reports running out of memory instead of the allocator failing
+  #[cfg_attr(miri, ignore)]
+  #[test]
+  fn fallible_reserves_report_allocator_failure() {
+    // Fits in an `isize` but not in any address space
+    let mut vec = ErasedVec::new_erased(huge(1 << 61));
+    assert!(matches!(vec.try_reserve(1), Err(ErasedVecErrors::ErasedVecAllocError)));
+    assert_eq!(vec.capacity(), 0);
+  }
+
+  #[test]
+  fn clone_slots_with_the_clone_shim() {
+    let mut vec = ErasedVec::new_erased(TypeInfo::of_cloneable::<Vec<u8>>());
+    vec.push(vec![1_u8; 16]);
+    vec.pad();
+
+    let mut out = mem::MaybeUninit::<Vec<u8>>::uninit();
+    vec.clone_slot(0, out.as_mut_ptr().cast()).unwrap();
+    let mut clone = unsafe { out.assume_init() };
+    clone.push(2);
+    assert_eq!(vec.get::<Vec<u8>>(0).len(), 16);
+
+    vec.clone_within(0, 1).unwrap();
+    vec.get_mut::<Vec<u8>>(1).clear();
+    assert_eq!(vec.get::<Vec<u8>>(0).len(), 16);
+    assert!(vec.is_filled(1));
+
+    vec.clear(1);
+    let mut out = mem::MaybeUninit::<Vec<u8>>::uninit();
+    assert!(vec.clone_slot(1, out.as_mut_ptr().cast()).is_err());
+    let mut plain = ErasedVec::new::<Vec<u8>>();
+    plain.push(vec![1_u8]);
+    assert!(plain.clone_slot(0, out.as_mut_ptr().cast()).is_err());
+  }
+
+  #[test]
+  fn debug_format_filled_slots() {
+    let mut vec = ErasedVec::new_erased(TypeInfo::of_debug::<u32>());
+    vec.push(1_u32);
+    vec.pad();
+    vec.push(3_u32);
+    assert_eq!(format!("{vec:?}"), "{0: 1, 2: 3}");
+
+    let mut opaque = ErasedVec::new::<u8>();
+    opaque.push(1_u8);
+    assert_eq!(format!("{opaque:?}"), "{0: u8(opaque)}");
+  }
+
+  #[test]
+  #[should_panic(expected = "Cannot access index 1 past the end")]
+  fn get_at_len_panics() {
+    let mut vec = ErasedVec::new::<Health>();
+    vec.push(Health::new(1));
+    vec.get::<Health>(1);
+  }
+
+  #[test]
+  #[should_panic(expected = "Cannot access index 1 past the end")]
+  fn set_at_len_panics() {
+    let mut vec = ErasedVec::new::<Health>();
+    vec.push(Health::new(1));
+    vec.set(1, Health::new(2));
+  }
+
+  #[test]
+  #[should_panic(expected = "Cannot insert into 3 past the end")]
+  fn insert_past_len_panics() {
+    let mut vec = ErasedVec::new::<Health>();
+    vec.push(Health::new(1));
+    // Inserting at `len` appends
+    vec.insert(1, Health::new(2));
+    vec.insert(3, Health::new(3));
+  }
+
   #[test]
   fn fetching_item_from_nodroptuple_after_drop_works() {
     let data = ("a".to_string(), 13.0_f32, 15_u128, 16_usize);
@@ -840,9 +2244,151 @@ mod test {
     assert_eq!(string, "a");
   }
 
+  #[test]
+  fn nodroptuple_fields_are_aligned() {
+    let data = (1_u8, 2_u16, 3_u64, "a".repeat(32), 4_u8, 5_u128);
+    let tuple = NoDropTuple::new(data);
+
+    for index in 0..tuple.len() {
+      let (ty, ptr) = tuple.get(index);
+      assert_eq!(ptr as usize % ty.layout().align(), 0);
+    }
+    unsafe {
+      assert_eq!(*(tuple.get(0).1 as *const u8), 1);
+      assert_eq!(*(tuple.get(1).1 as *const u16), 2);
+      assert_eq!(*(tuple.get(2).1 as *const u64), 3);
+      assert_eq!(ptr::read(tuple.get(3).1 as *const String), "a".repeat(32));
+      assert_eq!(*(tuple.get(4).1 as *const u8), 4);
+      assert_eq!(*(tuple.get(5).1 as *const u128), 5);
+    }
+  }
+
+  #[test]
+  fn empty_and_zero_sized_nodroptuples_do_not_allocate() {
+    let empty = NoDropTuple::new(());
+    assert!(empty.is_empty());
+
+    let markers = NoDropTuple::new((Player, [0_u64; 0]));
+    assert_eq!(markers.len(), 2);
+    assert_eq!(markers.get(0).0, TypeInfo::of::<Player>());
+    let (ty, ptr) = markers.get(1);
+    assert_eq!(ty, TypeInfo::of::<[u64; 0]>());
+    assert_eq!(ptr as usize % ty.layout().align(), 0);
+  }
+
+  #[test]
+  fn move_values_out_of_erasedbox() {
+    let name = ErasedBox::new("a".repeat(32));
+    assert_eq!(name.into_inner::<String>(), "a".repeat(32));
+
+    let bytes = ErasedBox::new(vec![1_u8; 64]);
+    let (ty, ptr) = bytes.into_raw_parts();
+    assert_eq!(ty, TypeInfo::of::<Vec<u8>>());
+    let bytes = unsafe {
+      let bytes = ptr::read(ptr as *const Vec<u8>);
+      alloc::dealloc(ptr, ty.layout());
+      bytes
+    };
+    assert_eq!(bytes, vec![1; 64]);
+
+    // A copied value is owned by the box
+    let mut name = "b".repeat(32);
+    let boxed = ErasedBox::from_raw_parts(TypeInfo::of::<String>(), (&mut name as *mut String).cast());
+    mem::forget(name);
+    assert_eq!(boxed.into_inner::<String>(), "b".repeat(32));
+  }
+
+  #[test]
+  fn replace_erasedbox_values() {
+    let drops = Rc::new(Cell::new(0));
+    let mut boxed = ErasedBox::new(Buffer::new(&drops));
+
+    let old = boxed.replace(Buffer::new(&drops));
+    assert_eq!(drops.get(), 0);
+    drop(old);
+    assert_eq!(drops.get(), 1);
+
+    let mut value = mem::ManuallyDrop::new(Buffer::new(&drops));
+    boxed.replace_erased(TypeInfo::of::<Buffer>(), (&mut *value as *mut Buffer).cast());
+    assert_eq!(drops.get(), 1);
+    // `value` now holds the box's old value
+    unsafe { mem::ManuallyDrop::drop(&mut value) };
+    assert_eq!(drops.get(), 2);
+
+    drop(boxed);
+    assert_eq!(drops.get(), 3);
+  }
+
+  #[test]
+  #[should_panic(expected = "does not contain data")]
+  fn erasedbox_into_inner_checks_the_type() {
+    ErasedBox::new(1_u32).into_inner::<i32>();
+  }
+
+  #[test]
+  fn pointers_are_aligned_for_high_alignment_types() {
+    let mut vec = ErasedVec::new::<Aligned>();
+    assert_eq!(vec.ptr() as usize % 64, 0);
+    assert!(vec.as_slice::<Aligned>().unwrap().is_empty());
+
+    vec.push(Aligned(1));
+    vec.push(Aligned(2));
+    assert_eq!(vec.ptr() as usize % 64, 0);
+    assert_eq!(vec.get::<Aligned>(1), &Aligned(2));
+    assert_eq!(vec.remove_typed::<Aligned>(0), Aligned(1));
+
+    // Releasing the allocation falls back to an aligned dangling pointer
+    vec.clear_all();
+    vec.shrink_to_fit();
+    assert_eq!(vec.capacity(), 0);
+    assert_eq!(vec.ptr() as usize % 64, 0);
+
+    let mut markers = ErasedVec::new::<AlignedMarker>();
+    markers.push(AlignedMarker);
+    markers.push(AlignedMarker);
+    assert_eq!(markers.ptr() as usize % 64, 0);
+    assert_eq!(markers.get::<AlignedMarker>(1), &AlignedMarker);
+    assert_eq!(markers.as_slice::<AlignedMarker>().unwrap().len(), 2);
+    assert_eq!(markers.pop::<AlignedMarker>(), Some(AlignedMarker));
+
+    let boxed = ErasedBox::new(AlignedMarker);
+    assert_eq!(boxed.ptr() as usize % 64, 0);
+    assert_eq!(boxed.get::<AlignedMarker>(), &AlignedMarker);
+    let boxed = ErasedBox::new(Aligned(3));
+    assert_eq!(boxed.into_inner::<Aligned>(), Aligned(3));
+  }
+
+  #[repr(align(64))]
+  #[derive(Debug, PartialEq)]
+  struct Aligned(u8);
+
+  #[repr(align(64))]
+  #[derive(Debug, PartialEq)]
+  struct AlignedMarker;
+
   #[derive(Debug, PartialEq, PartialOrd)]
   struct Player;
 
+  struct Buffer {
+    _bytes:Vec<u8>,
+    drops:Rc<Cell<u32>>
+  }
+
+  impl Buffer {
+    fn new(drops:&Rc<Cell<u32>>) -> Self {
+      Buffer {
+        _bytes:vec![1; 64],
+        drops:drops.clone()
+      }
+    }
+  }
+
+  impl Drop for Buffer {
+    fn drop(&mut self) {
+      self.drops.set(self.drops.get() + 1)
+    }
+  }
+
   #[derive(Debug, Clone, Copy)]
   struct Health {
     pub max:i32,
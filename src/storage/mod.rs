@@ -1,7 +1,10 @@
+mod bitmask;
 mod bundle;
+mod component_storage;
 mod ecs_data;
 mod erased_collections;
+mod ticks;
 mod type_info;
 mod type_map;
 
-pub use self::{bundle::*, ecs_data::*, erased_collections::*, type_info::*, type_map::*};
+pub use self::{bitmask::*, bundle::*, component_storage::*, ecs_data::*, erased_collections::*, ticks::*, type_info::*, type_map::*};
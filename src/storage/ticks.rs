@@ -0,0 +1,49 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+
+///A counter the [`World`](crate::world::World) advances to mark the passage
+/// of time for change detection.
+pub type Tick = u32;
+
+///The ticks at which a component slot was last added and changed.
+///
+/// The ticks are atomic so mutable access handed out through a shared borrow
+/// can still record the change.
+#[derive(Debug, Default)]
+pub struct ComponentTicks {
+  added:AtomicU32,
+  changed:AtomicU32
+}
+
+impl ComponentTicks {
+  ///Constructs [`ComponentTicks`] for a value added at `tick`.
+  pub fn new(tick:Tick) -> Self {
+    ComponentTicks {
+      added:AtomicU32::new(tick),
+      changed:AtomicU32::new(tick)
+    }
+  }
+
+  ///Returns the tick at which the value was added.
+  pub fn added(&self) -> Tick {
+    self.added.load(Ordering::Relaxed)
+  }
+
+  ///Returns the tick at which the value was last changed.
+  pub fn changed(&self) -> Tick {
+    self.changed.load(Ordering::Relaxed)
+  }
+
+  ///Records a change to the value at `tick`.
+  pub fn set_changed(&self, tick:Tick) {
+    self.changed.store(tick, Ordering::Relaxed);
+  }
+}
+
+impl Clone for ComponentTicks {
+  fn clone(&self) -> Self {
+    ComponentTicks {
+      added:AtomicU32::new(self.added()),
+      changed:AtomicU32::new(self.changed())
+    }
+  }
+}
@@ -1,10 +1,11 @@
-use crate::errors::TypeInfoErrors;
-use eyre::Result;
+use crate::errors::{Result, TypeInfoErrors};
 use std::{
   alloc::Layout,
   any::TypeId,
   cmp::Ordering,
-  hash::{Hash, Hasher}
+  fmt,
+  hash::{Hash, Hasher},
+  mem
 };
 
 // Refactor:
@@ -16,20 +17,42 @@ use std::{
 
 #[derive(Debug, Copy, Clone)]
 /// Metadata required to store a component.
-/// - A [`TypeId`], to be able to dynamically name/check the
-/// component type.
-/// - A [`Layout`], so that we know how to allocate memory for
-/// this component type.
+/// - A key, the [`TypeId`] of Rust types or a salt for runtime-defined types,
+///   to be able to dynamically name/check the component type.
+/// - A [`Layout`], so that we know how to allocate memory for this component
+///   type.
 /// - A drop function which internally calls
 ///   [`ptr::drop_in_place`](core::ptr::drop_in_place) with the correct type
 ///   parameter.
+/// - An optional clone function, present for types registered as cloneable.
+/// - An optional debug formatting function, present for types registered as
+///   debuggable.
+/// - Whether the type is [`Copy`], so its bytes can be compared and copied
+///   directly.
+/// - Whether dropping the type runs any code.
 pub struct TypeInfo {
-  id:TypeId,
+  key:TypeKey,
   layout:Layout,
   drop:unsafe fn(*mut u8),
+  clone:Option<unsafe fn(*const u8, *mut u8)>,
+  debug:Option<DebugShim>,
+  copy:bool,
+  needs_drop:bool,
   type_name:&'static str
 }
 
+/// Identifies the type a [`TypeInfo`] describes.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum TypeKey {
+  /// A Rust type.
+  Rust(TypeId),
+  /// A type defined at runtime, identified by its salt.
+  Raw(u64)
+}
+
+/// Formats the value behind the pointer with its [`Debug`](fmt::Debug) impl.
+pub type DebugShim = unsafe fn(*const u8, &mut fmt::Formatter) -> fmt::Result;
+
 impl TypeInfo {
   pub fn of<T:'static>() -> Self {
     unsafe fn drop_ptr<T>(x:*mut u8) {
@@ -41,17 +64,95 @@ impl TypeInfo {
     let layout = Layout::new::<T>();
 
     TypeInfo {
-      id:TypeId::of::<T>(),
+      key:TypeKey::Rust(TypeId::of::<T>()),
       layout,
       drop:drop_ptr::<T>,
-      #[cfg(debug_assertions)]
+      clone:None,
+      debug:None,
+      copy:false,
+      needs_drop:mem::needs_drop::<T>(),
       type_name:core::any::type_name::<T>()
     }
   }
 
+  /// Create the [`TypeInfo`] of a type defined at runtime, such as a blob
+  /// of bytes with a custom destructor.
+  ///
+  /// Runtime-defined types are identified by `id_salt`. Two [`TypeInfo`]s
+  /// with the same salt describe the same type, and never equal the
+  /// [`TypeInfo`] of a Rust type. The destructor is always assumed to run
+  /// code.
+  pub fn from_raw_parts(id_salt:u64, layout:Layout, drop:unsafe fn(*mut u8), name:&'static str) -> Self {
+    TypeInfo {
+      key:TypeKey::Raw(id_salt),
+      layout,
+      drop,
+      clone:None,
+      debug:None,
+      copy:false,
+      needs_drop:true,
+      type_name:name
+    }
+  }
+
+  /// Create the [`TypeInfo`] of a type which can be cloned through its clone
+  /// shim.
+  pub fn of_cloneable<T:'static + Clone>() -> Self {
+    unsafe fn clone_ptr<T:Clone>(src:*const u8, dst:*mut u8) {
+      dst.cast::<T>().write((*src.cast::<T>()).clone())
+    }
+
+    TypeInfo {
+      clone:Some(clone_ptr::<T>),
+      ..Self::of::<T>()
+    }
+  }
+
+  /// Create the [`TypeInfo`] of a [`Copy`] type.
+  ///
+  /// The type is also cloneable through its clone shim.
+  pub fn of_copy<T:'static + Copy>() -> Self {
+    TypeInfo {
+      copy:true,
+      ..Self::of_cloneable::<T>()
+    }
+  }
+
+  /// Create the [`TypeInfo`] of a type which can be formatted through its
+  /// debug shim.
+  pub fn of_debug<T:'static + fmt::Debug>() -> Self {
+    unsafe fn debug_ptr<T:fmt::Debug>(ptr:*const u8, f:&mut fmt::Formatter) -> fmt::Result {
+      fmt::Debug::fmt(&*ptr.cast::<T>(), f)
+    }
+
+    TypeInfo {
+      debug:Some(debug_ptr::<T>),
+      ..Self::of::<T>()
+    }
+  }
+
+  /// Returns a copy of this [`TypeInfo`] which also carries the optional
+  /// shims `other` has and this one lacks, or `None` if `other` adds nothing.
+  pub(crate) fn merge_shims(self, other:TypeInfo) -> Option<Self> {
+    debug_assert_eq!(self, other);
+    let adds_shims = (self.clone.is_none() && other.clone.is_some()) || (self.debug.is_none() && other.debug.is_some()) || (!self.copy && other.copy);
+
+    adds_shims.then_some(TypeInfo {
+      clone:self.clone.or(other.clone),
+      debug:self.debug.or(other.debug),
+      copy:self.copy || other.copy,
+      ..self
+    })
+  }
+
   /// Access the [`TypeId`] for this component type.
-  pub fn id(&self) -> TypeId {
-    self.id
+  ///
+  /// Returns `None` for types created with [`TypeInfo::from_raw_parts`].
+  pub fn id(&self) -> Option<TypeId> {
+    match self.key {
+      TypeKey::Rust(id) => Some(id),
+      TypeKey::Raw(_) => None
+    }
   }
 
   /// Access the [`Layout`] of this component type.
@@ -64,6 +165,11 @@ impl TypeInfo {
     self.type_name.to_string()
   }
 
+  /// Access the name of this component type without allocating.
+  pub fn type_name(&self) -> &'static str {
+    self.type_name
+  }
+
   /// Access the size of this component type.
   pub fn size(&self) -> usize {
     self.layout.size()
@@ -92,9 +198,15 @@ impl TypeInfo {
       if element_size != 0 && n > (isize::MAX as usize - (align - 1)) / element_size {
         return Err(TypeInfoErrors::LayoutError);
       }
-      let array_size = unsafe { element_size.unchecked_mul(n) };
+      let array_size = match element_size.checked_mul(n) {
+        Some(array_size) => array_size,
+        None => return Err(TypeInfoErrors::LayoutError)
+      };
 
-      unsafe { Ok(Layout::from_size_align_unchecked(array_size, align)) }
+      match Layout::from_size_align(array_size, align) {
+        Ok(layout) => Ok(layout),
+        Err(_) => Err(TypeInfoErrors::LayoutError)
+      }
     }
   }
 
@@ -113,6 +225,66 @@ impl TypeInfo {
   pub fn drop_shim(&self) -> unsafe fn(*mut u8) {
     self.drop
   }
+
+  /// Returns `true` if dropping a value of this type runs any code.
+  pub fn needs_drop(&self) -> bool {
+    self.needs_drop
+  }
+
+  /// Get the function pointer which clones the value behind its first
+  /// argument into the uninitialized memory behind its second argument.
+  ///
+  /// Returns `None` if the type was not created with
+  /// [`TypeInfo::of_cloneable`].
+  pub fn clone_shim(&self) -> Option<unsafe fn(*const u8, *mut u8)> {
+    self.clone
+  }
+
+  /// Returns `true` if this [`TypeInfo`] carries a clone shim.
+  pub fn is_cloneable(&self) -> bool {
+    self.clone.is_some()
+  }
+
+  /// Returns `true` if this [`TypeInfo`] was created with
+  /// [`TypeInfo::of_copy`].
+  pub fn is_copy(&self) -> bool {
+    self.copy
+  }
+
+  /// Get the function pointer which formats the value behind its argument.
+  ///
+  /// Returns `None` if the type was not created with
+  /// [`TypeInfo::of_debug`].
+  pub fn debug_shim(&self) -> Option<DebugShim> {
+    self.debug
+  }
+
+  /// Returns a [`Debug`](fmt::Debug) wrapper for the value behind `ptr`.
+  ///
+  /// Types without a debug shim render as `TypeName(opaque)`.
+  ///
+  /// # Safety
+  /// - `ptr` must point to a valid value of the type this [`TypeInfo`]
+  ///   describes for as long as the wrapper is used.
+  pub unsafe fn debug_value(&self, ptr:*const u8) -> ErasedDebug {
+    ErasedDebug { ty:*self, ptr }
+  }
+}
+
+/// Formats a type-erased value with its [`TypeInfo`]'s debug shim. Created
+/// by [`TypeInfo::debug_value`].
+pub struct ErasedDebug {
+  ty:TypeInfo,
+  ptr:*const u8
+}
+
+impl fmt::Debug for ErasedDebug {
+  fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result {
+    match self.ty.debug {
+      Some(debug) => unsafe { debug(self.ptr, f) },
+      None => write!(f, "{}(opaque)", self.ty.type_name)
+    }
+  }
 }
 
 impl PartialOrd for TypeInfo {
@@ -122,27 +294,69 @@ impl PartialOrd for TypeInfo {
 }
 
 impl Ord for TypeInfo {
-  /// Order by alignment, descending. Ties broken with TypeId.
+  /// Order by alignment, descending. Ties broken with the type's key.
   fn cmp(&self, other:&Self) -> Ordering {
     self
       .layout
       .align()
       .cmp(&other.layout.align())
       .reverse()
-      .then_with(|| self.id.cmp(&other.id))
+      .then_with(|| self.key.cmp(&other.key))
   }
 }
 
 impl PartialEq for TypeInfo {
   fn eq(&self, other:&Self) -> bool {
-    self.id == other.id
+    self.key == other.key
   }
 }
 
 impl Hash for TypeInfo {
   fn hash<H:Hasher>(&self, state:&mut H) {
-    self.id.hash(state);
+    // `TypeIdHasher` only accepts a single write so the variant is not hashed
+    match self.key {
+      TypeKey::Rust(id) => id.hash(state),
+      TypeKey::Raw(salt) => state.write_u64(salt)
+    }
   }
 }
 
 impl Eq for TypeInfo {}
+
+#[cfg(test)]
+mod tests {
+  use super::TypeInfo;
+
+  #[test]
+  fn format_values_through_the_debug_shim() {
+    let value = vec![1_u8, 2];
+    let ptr = (&value as *const Vec<u8>).cast::<u8>();
+    let debug = unsafe { TypeInfo::of_debug::<Vec<u8>>().debug_value(ptr) };
+    assert_eq!(format!("{debug:?}"), "[1, 2]");
+    let opaque = unsafe { TypeInfo::of::<Vec<u8>>().debug_value(ptr) };
+    assert_eq!(format!("{opaque:?}"), "alloc::vec::Vec<u8>(opaque)");
+
+    let merged = TypeInfo::of_cloneable::<Vec<u8>>().merge_shims(TypeInfo::of_debug::<Vec<u8>>()).unwrap();
+    assert!(merged.is_cloneable() && merged.debug_shim().is_some());
+  }
+
+  #[test]
+  fn raw_types_are_keyed_by_their_salt() {
+    unsafe fn drop_nothing(_:*mut u8) {}
+    let layout = std::alloc::Layout::new::<u64>();
+
+    let raw_1 = TypeInfo::from_raw_parts(1, layout, drop_nothing, "Blob1");
+    let raw_2 = TypeInfo::from_raw_parts(2, layout, drop_nothing, "Blob2");
+    assert_ne!(raw_1, raw_2);
+    assert_eq!(raw_1, TypeInfo::from_raw_parts(1, layout, drop_nothing, "Blob1"));
+    assert_ne!(raw_1, TypeInfo::of::<u64>());
+    assert_eq!(raw_1.id(), None);
+    assert_eq!(raw_1.name(), "Blob1");
+  }
+
+  #[test]
+  fn name_is_available_in_every_profile() {
+    assert_eq!(TypeInfo::of::<u32>().name(), "u32");
+    assert_eq!(TypeInfo::of_cloneable::<String>().name(), "alloc::string::String");
+  }
+}
@@ -0,0 +1,123 @@
+use crate::{
+  errors::{EcsErrors, Result},
+  storage::TypeInfo
+};
+
+/// Records the types an operation reads and writes so conflicting borrows can
+/// be rejected before any reference is handed out.
+///
+/// A type may be read any number of times but a write excludes every other
+/// read or write of the same type.
+#[derive(Debug, Clone, Default)]
+pub struct Access {
+  reads:Vec<TypeInfo>,
+  writes:Vec<TypeInfo>
+}
+
+impl Access {
+  /// Constructs a new, empty [`Access`].
+  pub fn new() -> Self {
+    Access::default()
+  }
+
+  /// Records a read of `ty`.
+  ///
+  /// Returns an error if `ty` is already written.
+  pub fn add_read(&mut self, ty:TypeInfo) -> Result<()> {
+    if self.writes.contains(&ty) {
+      return Err(EcsErrors::ConflictingAccess { ty:ty.name() }.into());
+    }
+    self.reads.push(ty);
+    Ok(())
+  }
+
+  /// Records a write of `ty`.
+  ///
+  /// Returns an error if `ty` is already read or written.
+  pub fn add_write(&mut self, ty:TypeInfo) -> Result<()> {
+    if self.writes.contains(&ty) || self.reads.contains(&ty) {
+      return Err(EcsErrors::ConflictingAccess { ty:ty.name() }.into());
+    }
+    self.writes.push(ty);
+    Ok(())
+  }
+
+  /// Returns the types which are read.
+  pub fn reads(&self) -> &[TypeInfo] {
+    &self.reads
+  }
+
+  /// Returns the types which are written.
+  pub fn writes(&self) -> &[TypeInfo] {
+    &self.writes
+  }
+
+  /// Returns `true` if neither [`Access`] writes a type the other reads or
+  /// writes.
+  pub fn is_compatible(&self, other:&Access) -> bool {
+    let conflicts = |a:&Access, b:&Access| a.writes.iter().any(|ty| b.reads.contains(ty) || b.writes.contains(ty));
+    !conflicts(self, other) && !conflicts(other, self)
+  }
+}
+
+/// The components and resources a system reads and writes.
+///
+/// Components and resources are tracked separately since a type may be used
+/// as both without the borrows conflicting.
+#[derive(Debug, Clone, Default)]
+pub struct SystemAccess {
+  pub components:Access,
+  pub resources:Access,
+  /// Whether the system queues commands.
+  pub commands:bool
+}
+
+impl SystemAccess {
+  /// Constructs a new, empty [`SystemAccess`].
+  pub fn new() -> Self {
+    SystemAccess::default()
+  }
+
+  /// Returns `true` if the systems can run at the same time without a
+  /// write/write or read/write conflict on any component or resource.
+  pub fn is_compatible(&self, other:&SystemAccess) -> bool {
+    self.components.is_compatible(&other.components) && self.resources.is_compatible(&other.resources)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::Access;
+  use crate::storage::TypeInfo;
+
+  #[test]
+  fn writes_conflict_with_every_other_access() {
+    let mut access = Access::new();
+    assert!(access.add_read(TypeInfo::of::<u32>()).is_ok());
+    assert!(access.add_read(TypeInfo::of::<u32>()).is_ok());
+    assert!(access.add_write(TypeInfo::of::<u32>()).is_err());
+
+    assert!(access.add_write(TypeInfo::of::<f32>()).is_ok());
+    assert!(access.add_write(TypeInfo::of::<f32>()).is_err());
+    assert!(access.add_read(TypeInfo::of::<f32>()).is_err());
+
+    assert_eq!(access.reads(), &[TypeInfo::of::<u32>(), TypeInfo::of::<u32>()]);
+    assert_eq!(access.writes(), &[TypeInfo::of::<f32>()]);
+  }
+
+  #[test]
+  fn only_writes_make_accesses_incompatible() {
+    let mut reader = Access::new();
+    reader.add_read(TypeInfo::of::<u32>()).unwrap();
+    let mut writer = Access::new();
+    writer.add_write(TypeInfo::of::<u32>()).unwrap();
+    let mut other = Access::new();
+    other.add_write(TypeInfo::of::<f32>()).unwrap();
+
+    assert!(reader.is_compatible(&reader));
+    assert!(!reader.is_compatible(&writer));
+    assert!(!writer.is_compatible(&reader));
+    assert!(!writer.is_compatible(&writer));
+    assert!(writer.is_compatible(&other));
+  }
+}
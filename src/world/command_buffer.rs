@@ -1,10 +1,15 @@
 use super::{entities::Entity, World};
-use crate::storage::{Bundle, EcsData, NoDropTuple, TypeInfo};
+use crate::storage::{Bundle, EcsData, ErasedBox, ErasedBundle, NoDropTuple, TypeInfo};
+use std::sync::MutexGuard;
 
 /// Records operations for future application to a World
 ///
 /// Useful when operations cannot be applied directly due to ordering concerns
 /// or borrow checking.
+///
+/// A [`CommandBuffer`] is [`Send`] so commands can be recorded on worker
+/// threads. Give each thread its own buffer and merge them with
+/// [`CommandBuffer::append`] before running.
 pub struct CommandBuffer(Vec<Command>);
 
 impl CommandBuffer {
@@ -13,32 +18,79 @@ impl CommandBuffer {
   }
 
   /// Create a new entity with the provided components.
-  pub fn spawn_entity<B:Bundle>(&mut self, components:B) {
-    let insert_info = InsertInfo {
-      entity:None,
-      components:NoDropTuple::new(components)
-    };
-    self.0.push(Command::InsertOrSpawn(insert_info))
+  ///
+  /// The entity's id is reserved in `world` immediately so further commands
+  /// can target it. The components are inserted when the buffer runs.
+  pub fn spawn_entity<B:ErasedBundle + Send>(&mut self, world:&World, components:B) -> Entity {
+    let entity = world.entities.reserve_entity();
+    self.insert_components(entity, components);
+    entity
+  }
+
+  /// Reserve a new entity in `world` and return an [`EntityCommands`] for
+  /// chaining the commands which build it.
+  ///
+  /// ```
+  /// # use nina::world::{command_buffer::CommandBuffer, World};
+  /// let mut world = World::new();
+  /// let mut buffer = CommandBuffer::new();
+  /// let entity = buffer.spawn(&world).insert(1_u32).insert_bundle((true, 'a')).id();
+  /// buffer.run(&mut world);
+  /// assert_eq!(*world.get_component::<char>(entity).unwrap(), 'a');
+  /// ```
+  pub fn spawn(&mut self, world:&World) -> EntityCommands<'_> {
+    let entity = world.entities.reserve_entity();
+    self.entity(entity)
+  }
+
+  /// Return an [`EntityCommands`] for chaining commands which target
+  /// `entity`.
+  pub fn entity(&mut self, entity:Entity) -> EntityCommands<'_> {
+    EntityCommands { buffer:self, entity }
   }
 
   /// Delete the specified entity.
+  ///
+  /// Does nothing if the entity is already deleted when the buffer runs.
   pub fn delete_entity(&mut self, entity:Entity) {
     self.0.push(Command::DeleteEntity(entity));
   }
 
   /// Add a commponent to the specified entity.
-  pub fn insert_component<T:EcsData>(&mut self, entity:Entity, component:T) {
+  pub fn insert_component<T:EcsData + Send>(&mut self, entity:Entity, component:T) {
     self.insert_components(entity, (component,));
   }
 
   /// Add commponents to the specified entity. To insert one
   /// component see [`Self::insert_component`].
-  pub fn insert_components<B:Bundle>(&mut self, entity:Entity, components:B) {
+  pub fn insert_components<B:ErasedBundle + Send>(&mut self, entity:Entity, components:B) {
     let insert_info = InsertInfo {
-      entity:Some(entity),
-      components:NoDropTuple::new(components)
+      entity,
+      components:NoDropTuple::new(components),
+      taken:0
+    };
+    self.0.push(Command::Insert(insert_info))
+  }
+
+  /// Add a type-erased component to the specified entity.
+  ///
+  /// `ty.size()` bytes are copied from `ptr` into a buffer owned by the
+  /// [`CommandBuffer`] so `ptr` does not need to outlive the call.
+  ///
+  /// # Ownership
+  ///
+  /// The buffer takes ownership of the value. The caller must not drop the
+  /// source afterwards, i.e. `mem::forget` it. If the buffer is dropped
+  /// without running, the value is dropped with it.
+  ///
+  /// The value's type must be [`Send`] since the buffer may be sent to
+  /// another thread before it runs.
+  pub fn insert_component_erased(&mut self, entity:Entity, ty:TypeInfo, ptr:*mut u8) {
+    let insert_info = InsertErasedInfo {
+      entity,
+      component:ErasedBox::from_raw_parts(ty, ptr)
     };
-    self.0.push(Command::InsertOrSpawn(insert_info))
+    self.0.push(Command::InsertErased(insert_info))
   }
 
   /// Removes the component specified by the generic parameter.
@@ -48,68 +100,249 @@ impl CommandBuffer {
 
   /// Removes the components specified by the generic parameter.
   ///
-  /// Enter components as a [`Bundle`] i.e. `(A,B,C)`.
+  /// Enter components as a [`Bundle`] i.e. `(A,B,C)`. Does nothing if the
+  /// entity is deleted when the buffer runs.
   pub fn remove_components<T:Bundle>(&mut self, entity:Entity) {
     let remove_info = RemoveInfo { entity, tys:T::types() };
     self.0.push(Command::RemoveComponent(remove_info))
   }
 
-  /// Execute the buffered commands.
+  /// Queue a closure which is run with the [`World`] in order with the other
+  /// buffered commands.
+  pub fn add(&mut self, f:impl FnOnce(&mut World) + Send + 'static) {
+    self.0.push(Command::Closure(Box::new(f)));
+  }
+
+  /// Moves every command in `other` to the end of the [`CommandBuffer`],
+  /// keeping the order of both buffers.
+  pub fn append(&mut self, mut other:CommandBuffer) {
+    self.0.append(&mut other.0);
+  }
+
+  /// Execute the buffered commands, leaving the [`CommandBuffer`] empty.
+  ///
+  /// Each command is applied exactly once so running the buffer again does
+  /// nothing until new commands are queued.
   pub fn run(&mut self, world:&mut World) {
-    for cmd in &self.0 {
+    // Create the entities reserved by `spawn_entity`
+    world.entities.flush_reserved();
+
+    for cmd in self.0.drain(..) {
       match cmd {
-        Command::InsertOrSpawn(insert_info) => {
-          let entity = match insert_info.entity {
-            Some(entity) => entity,
-            None => world.reserve_entity()
-          };
-          for index in 0..insert_info.components.len() {
-            let (ty, ptr) = insert_info.components.get(index);
-            world.add_component_erased(entity, ty, ptr).unwrap();
+        Command::Insert(mut insert_info) => {
+          while insert_info.taken < insert_info.components.len() {
+            let (ty, ptr) = insert_info.components.get(insert_info.taken);
+            world.add_component_erased(insert_info.entity, ty, ptr).unwrap();
+            // The world now owns the value
+            insert_info.taken += 1;
           }
         }
+        Command::InsertErased(insert_info) => {
+          let component = insert_info.component;
+          world.add_component_erased(insert_info.entity, component.ty(), component.ptr()).unwrap();
+          // The world now owns the value
+          component.forget();
+        }
+        // Removing from or deleting an entity an earlier command deleted does
+        // nothing
         Command::RemoveComponent(remove_info) => {
-          for ty in &remove_info.tys {
-            world.delete_component_erased(remove_info.entity, *ty).unwrap();
+          if world.entities.is_alive(remove_info.entity) {
+            for ty in remove_info.tys {
+              world.delete_component_erased(remove_info.entity, ty).unwrap();
+            }
           }
         }
-        Command::DeleteEntity(entity) => world.delete_entity(*entity).unwrap()
+        Command::DeleteEntity(entity) => {
+          if world.entities.is_alive(entity) {
+            world.delete_entity(entity).unwrap()
+          }
+        }
+        Command::Closure(f) => f(world)
       }
     }
   }
 
+  /// Returns the number of queued commands.
+  pub fn len(&self) -> usize {
+    self.0.len()
+  }
+
+  /// Returns `true` if no commands are queued.
+  pub fn is_empty(&self) -> bool {
+    self.0.is_empty()
+  }
+
   /// Remove all commands from the [`CommandBuffer`].
   pub fn clear(&mut self) {
     *self = CommandBuffer::new();
   }
 }
 
+impl Default for CommandBuffer {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+/// Records commands targeting a single entity into its parent
+/// [`CommandBuffer`].
+pub struct EntityCommands<'a> {
+  buffer:&'a mut CommandBuffer,
+  entity:Entity
+}
+
+impl<'a> EntityCommands<'a> {
+  /// Returns the entity the commands target.
+  pub fn id(&self) -> Entity {
+    self.entity
+  }
+
+  /// Add a component to the entity.
+  pub fn insert<T:EcsData + Send>(self, component:T) -> Self {
+    self.buffer.insert_component(self.entity, component);
+    self
+  }
+
+  /// Add the components in the [`Bundle`] to the entity.
+  pub fn insert_bundle<B:ErasedBundle + Send>(self, components:B) -> Self {
+    self.buffer.insert_components(self.entity, components);
+    self
+  }
+
+  /// Remove the component specified by the generic parameter from the entity.
+  pub fn remove<T:EcsData>(self) -> Self {
+    self.buffer.remove_component::<T>(self.entity);
+    self
+  }
+
+  /// Delete the entity.
+  pub fn despawn(self) -> Self {
+    self.buffer.delete_entity(self.entity);
+    self
+  }
+}
+
+/// Handle for queuing commands on a [`World`]'s internal [`CommandBuffer`]
+/// through a shared borrow.
+///
+/// The commands are applied when [`World::flush`] is called.
+pub struct Commands<'w> {
+  world:&'w World
+}
+
+impl<'w> Commands<'w> {
+  /// Create a new [`Commands`] handle.
+  pub fn new(world:&'w World) -> Self {
+    Commands { world }
+  }
+
+  /// Create a new entity with the provided components.
+  ///
+  /// The entity's id is reserved immediately. The components are inserted
+  /// when the world is flushed.
+  pub fn spawn_entity<B:ErasedBundle + Send>(&self, components:B) -> Entity {
+    self.buffer().spawn_entity(self.world, components)
+  }
+
+  /// Delete the specified entity.
+  pub fn delete_entity(&self, entity:Entity) {
+    self.buffer().delete_entity(entity);
+  }
+
+  /// Add a commponent to the specified entity.
+  pub fn insert_component<T:EcsData + Send>(&self, entity:Entity, component:T) {
+    self.buffer().insert_component(entity, component);
+  }
+
+  /// Add commponents to the specified entity.
+  pub fn insert_components<B:ErasedBundle + Send>(&self, entity:Entity, components:B) {
+    self.buffer().insert_components(entity, components);
+  }
+
+  /// Removes the component specified by the generic parameter.
+  pub fn remove_component<T:EcsData>(&self, entity:Entity) {
+    self.buffer().remove_component::<T>(entity);
+  }
+
+  /// Removes the components specified by the generic parameter.
+  pub fn remove_components<T:Bundle>(&self, entity:Entity) {
+    self.buffer().remove_components::<T>(entity);
+  }
+
+  /// Queue a closure which is run with the [`World`] in order with the other
+  /// queued commands.
+  pub fn add(&self, f:impl FnOnce(&mut World) + Send + 'static) {
+    self.buffer().add(f);
+  }
+
+  fn buffer(&self) -> MutexGuard<'_, CommandBuffer> {
+    self.world.commands.lock().unwrap()
+  }
+}
+
 /// A buffered command
 enum Command {
-  InsertOrSpawn(InsertInfo),
+  Insert(InsertInfo),
+  InsertErased(InsertErasedInfo),
   RemoveComponent(RemoveInfo),
-  DeleteEntity(Entity)
+  DeleteEntity(Entity),
+  Closure(ClosureCommand)
 }
 
+/// A boxed closure queued with [`CommandBuffer::add`].
+type ClosureCommand = Box<dyn FnOnce(&mut World) + Send>;
+
 struct RemoveInfo {
   entity:Entity,
   tys:Vec<TypeInfo>
 }
 
 struct InsertInfo {
-  entity:Option<Entity>,
-  components:NoDropTuple
+  entity:Entity,
+  components:NoDropTuple,
+  /// The number of components moved into the world. The rest are dropped
+  /// with the command.
+  taken:usize
+}
+
+// `InsertInfo` is only constructed from `Send` bundles
+unsafe impl Send for InsertInfo {}
+
+impl Drop for InsertInfo {
+  fn drop(&mut self) {
+    // Drop the components the world never took, e.g. when the buffer is
+    // cleared or dropped without running
+    for index in self.taken..self.components.len() {
+      let (ty, ptr) = self.components.get(index);
+      unsafe { ty.drop(ptr) }
+    }
+  }
+}
+
+struct InsertErasedInfo {
+  entity:Entity,
+  component:ErasedBox
 }
 
+// Callers of `insert_component_erased` must only insert `Send` types
+unsafe impl Send for InsertErasedInfo {}
+
 #[cfg(test)]
 mod tests {
-  use crate::world::{command_buffer::CommandBuffer, World};
+  use crate::{
+    storage::TypeInfo,
+    world::{command_buffer::CommandBuffer, World}
+  };
+  use std::{
+    mem,
+    sync::{
+      atomic::{AtomicU32, Ordering},
+      Arc
+    }
+  };
 
-  //miri is still erroring but the test passes,
-  // somehow the pointer to the string it tries to drop is incorrect (zero) unsure
-  // how that happens as I believe it should invalidate other operations too...
-  // only happens for vecs
-  // Seems to only happen on dropping the world according to the backtrace.
+  // Run under Miri: the padded slots the buffer inserts `String`s into hold
+  // zeroed bytes which must never be dropped.
   #[test]
   fn insert_into_entities() {
     let mut world = World::new();
@@ -121,15 +354,15 @@ mod tests {
 
     let mut buffer = CommandBuffer::new();
 
-    buffer.spawn_entity((true, "a".to_string()));
-    buffer.spawn_entity((1_u32, 1.0_f32));
-    buffer.spawn_entity((true, "a".to_string()));
-    buffer.spawn_entity((1.0_f32, "a".to_string()));
+    buffer.spawn_entity(&world, (true, "a".to_string()));
+    buffer.spawn_entity(&world, (1_u32, 1.0_f32));
+    buffer.spawn_entity(&world, (true, "a".to_string()));
+    buffer.spawn_entity(&world, (1.0_f32, "a".to_string()));
     buffer.run(&mut world);
 
     let bool_0 = world.get_component::<bool>(0).unwrap();
     let string_0 = world.get_component::<String>(0).unwrap();
-    assert_eq!(*bool_0, true);
+    assert!(*bool_0);
     assert_eq!(*string_0, "a".to_string());
 
     let u32_1 = world.get_component::<u32>(1).unwrap();
@@ -139,7 +372,7 @@ mod tests {
 
     let bool_2 = world.get_component::<bool>(2).unwrap();
     let string_2 = world.get_component::<String>(2).unwrap();
-    assert_eq!(*bool_2, true);
+    assert!(*bool_2);
     assert_eq!(*string_2, "a".to_string());
 
     let f32_3 = world.get_component::<f32>(3).unwrap();
@@ -147,4 +380,281 @@ mod tests {
     assert_eq!(*f32_3, 1.0);
     assert_eq!(*string_3, "a".to_string());
   }
+
+  #[test]
+  fn spawned_entities_are_reserved_immediately() {
+    let mut world = World::new();
+    world.create_entity().with_component(0_u32).unwrap();
+
+    let mut buffer = CommandBuffer::new();
+    let player = buffer.spawn_entity(&world, (1_u32,));
+    let target = buffer.spawn_entity(&world, (2_u32,));
+    assert_eq!((player, target), (1, 2));
+    buffer.insert_component(player, target);
+
+    // Entities created before the buffer runs do not take the reserved ids
+    let other = world.reserve_entity();
+    assert_eq!(other, 3);
+    world.add_component(other, 3_u32).unwrap();
+
+    buffer.run(&mut world);
+    assert_eq!(*world.get_component::<u32>(player).unwrap(), 1);
+    assert_eq!(*world.get_component::<usize>(player).unwrap(), target);
+    assert_eq!(*world.get_component::<u32>(target).unwrap(), 2);
+    assert_eq!(*world.get_component::<u32>(other).unwrap(), 3);
+  }
+
+  #[test]
+  fn spawn_empty_entities_and_insert_markers() {
+    let mut world = World::new();
+
+    let mut buffer = CommandBuffer::new();
+    let empty = buffer.spawn_entity(&world, ());
+    let marked = buffer.spawn_entity(&world, (Marker, 1_u32));
+    buffer.insert_components(empty, (Marker, Other));
+    buffer.insert_components(marked, ());
+    buffer.run(&mut world);
+
+    assert_eq!(world.iter_entities().collect::<Vec<_>>(), vec![empty, marked]);
+    assert!(world.get_component::<Marker>(empty).is_ok());
+    assert!(world.get_component::<Other>(empty).is_ok());
+    assert!(world.get_component::<Marker>(marked).is_ok());
+    assert_eq!(*world.get_component::<u32>(marked).unwrap(), 1);
+  }
+
+  #[test]
+  fn closures_run_in_order_with_other_commands() {
+    let mut world = World::new();
+    world.create_entity().with_component(1_u32).unwrap();
+
+    let mut buffer = CommandBuffer::new();
+    buffer.insert_component(0, 2.0_f32);
+    buffer.add(|world| {
+      // The insert queued before the closure has already been applied
+      let value = *world.get_component::<f32>(0).unwrap();
+      world.add_resource(value);
+    });
+    buffer.delete_entity(0);
+    buffer.add(|world| {
+      let alive = world.iter_entities().count();
+      world.add_resource(alive);
+    });
+    buffer.run(&mut world);
+
+    assert_eq!(*world.get_resource::<f32>(), 2.0);
+    assert_eq!(*world.get_resource::<usize>(), 0);
+  }
+
+  #[test]
+  fn running_the_buffer_twice_applies_commands_once() {
+    let mut world = World::new();
+
+    let mut buffer = CommandBuffer::new();
+    buffer.spawn_entity(&world, ("a".to_string(),));
+    buffer.spawn_entity(&world, ("b".to_string(), 1_u32));
+    buffer.delete_entity(0);
+    buffer.run(&mut world);
+    assert!(buffer.is_empty());
+
+    // A second run has nothing to apply
+    buffer.run(&mut world);
+    assert_eq!(world.iter_entities().collect::<Vec<_>>(), vec![1]);
+    assert_eq!(world.get_component::<String>(1).unwrap(), "b");
+
+    // The buffer can be reused after running
+    buffer.insert_component(1, "c".to_string());
+    buffer.run(&mut world);
+    assert_eq!(world.get_component::<String>(1).unwrap(), "c");
+  }
+
+  #[test]
+  fn merge_buffers_recorded_on_other_threads() {
+    let mut world = World::new();
+    for _ in 0..4 {
+      world.reserve_entity();
+    }
+
+    let buffers = std::thread::scope(|scope| {
+      let workers = (0..2)
+        .map(|worker| {
+          scope.spawn(move || {
+            let mut buffer = CommandBuffer::new();
+            buffer.insert_component(worker * 2, format!("{worker}"));
+            buffer.insert_component(worker * 2 + 1, format!("{worker}"));
+            buffer.add(move |world| *world.get_resource_or_insert_with(Vec::new) = vec![worker]);
+            buffer
+          })
+        })
+        .collect::<Vec<_>>();
+      workers.into_iter().map(|worker| worker.join().unwrap()).collect::<Vec<_>>()
+    });
+
+    let mut buffer = CommandBuffer::new();
+    for other in buffers {
+      buffer.append(other);
+    }
+    assert_eq!(buffer.len(), 6);
+    buffer.run(&mut world);
+
+    assert_eq!(world.get_component::<String>(1).unwrap(), "0");
+    assert_eq!(world.get_component::<String>(2).unwrap(), "1");
+    // The second buffer's closure ran last
+    assert_eq!(*world.get_resource::<Vec<usize>>(), vec![1]);
+  }
+
+  #[test]
+  fn chain_commands_for_one_entity() {
+    let mut world = World::new();
+    world.create_entity().with_components((1_u32, true)).unwrap();
+
+    let mut buffer = CommandBuffer::new();
+    let spawned = buffer.spawn(&world).insert(2_u32).insert_bundle((false, 'a')).remove::<bool>().id();
+    assert_eq!(spawned, 1);
+    buffer.entity(0).insert('b').remove::<u32>();
+    let despawned = buffer.spawn(&world).insert(3_u32).despawn().id();
+    buffer.run(&mut world);
+
+    assert_eq!(*world.get_component::<u32>(spawned).unwrap(), 2);
+    assert_eq!(*world.get_component::<char>(spawned).unwrap(), 'a');
+    assert!(world.get_component::<bool>(spawned).is_err());
+    assert_eq!(*world.get_component::<char>(0).unwrap(), 'b');
+    assert!(world.get_component::<u32>(0).is_err());
+    assert!(world.get_component::<u32>(despawned).is_err());
+  }
+
+  #[test]
+  fn queue_commands_through_a_shared_world() {
+    let mut world = World::new();
+    world.create_entity().with_component(1_u32).unwrap();
+    world.flush();
+
+    let commands = world.commands();
+    let spawned = commands.spawn_entity(("a".to_string(),));
+    commands.insert_component(spawned, 2_u32);
+    commands.remove_component::<u32>(0);
+    commands.add(|world| {
+      // Commands queued while flushing are applied in the same flush
+      world.commands().delete_entity(0);
+      world.flush();
+    });
+
+    assert!(world.get_component::<String>(spawned).is_err());
+    world.flush();
+    assert_eq!(world.get_component::<String>(spawned).unwrap(), "a");
+    assert_eq!(*world.get_component::<u32>(spawned).unwrap(), 2);
+    assert_eq!(world.iter_entities().collect::<Vec<_>>(), vec![spawned]);
+  }
+
+  #[test]
+  fn insert_erased_components_take_ownership() {
+    let drops = Arc::new(AtomicU32::new(0));
+    let mut world = World::new();
+    world.create_entity().with_component(1_u32).unwrap();
+
+    let mut buffer = CommandBuffer::new();
+    let mut name = "a".repeat(64);
+    buffer.insert_component_erased(0, TypeInfo::of::<String>(), (&mut name as *mut String).cast());
+    mem::forget(name);
+    let mut counted = Counted(drops.clone());
+    buffer.insert_component_erased(0, TypeInfo::of::<Counted>(), (&mut counted as *mut Counted).cast());
+    mem::forget(counted);
+    buffer.run(&mut world);
+
+    assert_eq!(*world.get_component::<String>(0).unwrap(), "a".repeat(64));
+    assert_eq!(drops.load(Ordering::Relaxed), 0);
+    drop(world);
+    assert_eq!(drops.load(Ordering::Relaxed), 1);
+
+    // Values left in a buffer which never runs are dropped with it
+    let mut buffer = CommandBuffer::new();
+    let mut counted = Counted(drops.clone());
+    buffer.insert_component_erased(0, TypeInfo::of::<Counted>(), (&mut counted as *mut Counted).cast());
+    mem::forget(counted);
+    drop(buffer);
+    assert_eq!(drops.load(Ordering::Relaxed), 2);
+  }
+
+  #[test]
+  fn queued_inserts_drop_their_components() {
+    let drops = Arc::new(AtomicU32::new(0));
+    let mut world = World::new();
+    world.create_entity().with_component(1_u32).unwrap();
+
+    // Cleared and dropped buffers drop every queued component
+    let mut buffer = CommandBuffer::new();
+    buffer.insert_components(0, (Counted(drops.clone()), "a".repeat(64)));
+    buffer.clear();
+    assert_eq!(drops.load(Ordering::Relaxed), 1);
+    buffer.insert_component(0, Counted(drops.clone()));
+    drop(buffer);
+    assert_eq!(drops.load(Ordering::Relaxed), 2);
+
+    // So are commands still queued on a dropped world
+    world.commands().insert_component(0, Counted(drops.clone()));
+    drop(world);
+    assert_eq!(drops.load(Ordering::Relaxed), 3);
+
+    // Components moved into the world are not dropped with the command
+    let mut world = World::new();
+    world.create_entity().with_component(1_u32).unwrap();
+    let mut buffer = CommandBuffer::new();
+    buffer.insert_components(0, (Counted(drops.clone()), "b".repeat(64)));
+    buffer.run(&mut world);
+    assert_eq!(drops.load(Ordering::Relaxed), 3);
+    drop(world);
+    assert_eq!(drops.load(Ordering::Relaxed), 4);
+  }
+
+  #[test]
+  fn commands_targeting_deleted_entities_do_nothing() {
+    let mut world = World::new();
+    for _ in 0..2 {
+      world.create_entity().with_components((1_u32, true)).unwrap();
+    }
+
+    let mut buffer = CommandBuffer::new();
+    buffer.delete_entity(0);
+    buffer.remove_component::<u32>(0);
+    buffer.delete_entity(1);
+    buffer.delete_entity(1);
+    buffer.run(&mut world);
+
+    assert!(!world.entities.is_alive(0));
+    assert!(!world.entities.is_alive(1));
+    assert!(world.validate().is_ok());
+  }
+
+  #[test]
+  fn removed_components_are_dropped_when_the_buffer_runs() {
+    let drops = Arc::new(AtomicU32::new(0));
+
+    let mut world = World::new();
+    world.create_entity().with_components((Counted(drops.clone()), 1_u32)).unwrap();
+    world.create_entity().with_component(Counted(drops.clone())).unwrap();
+
+    let mut buffer = CommandBuffer::new();
+    buffer.remove_component::<Counted>(0);
+    buffer.delete_entity(1);
+    assert_eq!(drops.load(Ordering::Relaxed), 0);
+
+    buffer.run(&mut world);
+    assert_eq!(drops.load(Ordering::Relaxed), 2);
+    assert!(world.get_component::<Counted>(0).is_err());
+    assert_eq!(*world.get_component::<u32>(0).unwrap(), 1);
+
+    // Neither value is dropped a second time with the world
+    drop(world);
+    assert_eq!(drops.load(Ordering::Relaxed), 2);
+  }
+
+  struct Marker;
+  struct Other;
+
+  struct Counted(Arc<AtomicU32>);
+
+  impl Drop for Counted {
+    fn drop(&mut self) {
+      self.0.fetch_add(1, Ordering::Relaxed);
+    }
+  }
 }
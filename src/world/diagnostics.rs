@@ -0,0 +1,204 @@
+use super::entities::{ComponentId, EntitiesInner};
+use crate::storage::{StorageKind, TypeInfo};
+use std::fmt;
+
+/// The memory used by a [`World`](super::World)'s storage, created by
+/// [`World::diagnostics`](super::World::diagnostics).
+///
+/// Only memory the world allocates is counted. Heap memory owned by the
+/// components and resources themselves, such as a `Vec`'s buffer, is not.
+#[derive(Debug, Clone, Default)]
+pub struct WorldDiagnostics {
+  /// One entry per registered component type, in registration order.
+  pub components:Vec<ComponentDiagnostics>,
+  /// One entry per resource, in no particular order.
+  pub resources:Vec<ResourceDiagnostics>
+}
+
+impl WorldDiagnostics {
+  /// Returns the number of bytes allocated for every component storage.
+  pub fn component_bytes(&self) -> usize {
+    self.components.iter().map(|component| component.bytes).sum()
+  }
+
+  /// Returns the number of bytes held by every resource.
+  pub fn resource_bytes(&self) -> usize {
+    self.resources.iter().map(|resource| resource.bytes).sum()
+  }
+
+  pub fn total_bytes(&self) -> usize {
+    self.component_bytes() + self.resource_bytes()
+  }
+}
+
+impl fmt::Display for WorldDiagnostics {
+  /// Formats the report as a table with a row per component type, followed
+  /// by the resources and the totals.
+  fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result {
+    writeln!(
+      f,
+      "{:<40} {:>6} {:>8} {:>8} {:>8} {:>8} {:>10}",
+      "component", "kind", "size", "len", "capacity", "filled", "bytes"
+    )?;
+    for component in &self.components {
+      writeln!(f, "{component}")?;
+    }
+    for resource in &self.resources {
+      writeln!(f, "{resource}")?;
+    }
+    writeln!(f, "components: {} bytes", self.component_bytes())?;
+    writeln!(f, "resources: {} bytes", self.resource_bytes())?;
+    write!(f, "total: {} bytes", self.total_bytes())
+  }
+}
+
+/// The memory used by the storage of one component type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ComponentDiagnostics {
+  pub name:&'static str,
+  pub kind:StorageKind,
+  /// The size of a single component.
+  pub size:usize,
+  /// The number of entity slots in the storage.
+  pub len:usize,
+  /// The number of rows the storage can hold without reallocating.
+  pub capacity:usize,
+  /// The number of entities holding the component.
+  pub filled:usize,
+  /// The number of bytes allocated for the storage, see
+  /// [`ComponentStorage::allocated_bytes`].
+  pub bytes:usize
+}
+
+impl ComponentDiagnostics {
+  pub(crate) fn new(entities:&EntitiesInner, id:ComponentId) -> Self {
+    let components = &entities.components[id.index()];
+    // Every slot of a marker column reads as filled, so its holders are
+    // counted from the bitmasks
+    let filled = match components.tracks_filled() {
+      true => components.filled_count(),
+      false => entities.holder_count(id)
+    };
+    ComponentDiagnostics {
+      name:components.ty().type_name(),
+      kind:components.kind(),
+      size:components.ty().size(),
+      len:components.len(),
+      capacity:components.capacity(),
+      filled,
+      bytes:components.allocated_bytes()
+    }
+  }
+}
+
+impl fmt::Display for ComponentDiagnostics {
+  fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result {
+    let kind = match self.kind {
+      StorageKind::Table => "table",
+      StorageKind::Dense => "dense",
+      StorageKind::Sparse => "sparse"
+    };
+    write!(f, "{:<40} {:>6} {:>8} {:>8} ", self.name, kind, self.size, self.len)?;
+    // Zero-sized types report an unbounded capacity
+    match self.size {
+      0 => write!(f, "{:>8} ", "-")?,
+      _ => write!(f, "{:>8} ", self.capacity)?
+    }
+    write!(f, "{:>8} {:>10}", self.filled, self.bytes)
+  }
+}
+
+/// The memory held by one resource.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResourceDiagnostics {
+  pub name:&'static str,
+  /// The size of the resource.
+  pub bytes:usize
+}
+
+impl ResourceDiagnostics {
+  pub(crate) fn new(ty:TypeInfo) -> Self {
+    ResourceDiagnostics {
+      name:ty.type_name(),
+      bytes:ty.size()
+    }
+  }
+}
+
+impl fmt::Display for ResourceDiagnostics {
+  fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result {
+    write!(
+      f,
+      "{:<40} {:>6} {:>8} {:>8} {:>8} {:>8} {:>10}",
+      self.name, "res", self.bytes, "-", "-", "-", self.bytes
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::{errors::Result, storage::StorageKind, world::World};
+  use std::mem;
+
+  struct Position(f32, f32);
+  struct Frozen;
+  struct Gravity(f32);
+
+  #[test]
+  fn report_storage_memory() -> Result<()> {
+    let mut world = World::new();
+    world.register_component_sparse::<Frozen>();
+    for x in 0..4 {
+      world.spawn((Position(x as f32, 0.0),))?;
+    }
+    world.add_component(1, Frozen)?;
+    world.delete_component::<Position>(2)?;
+    world.add_resource(Gravity(9.8));
+
+    let diagnostics = world.diagnostics();
+    let position = diagnostics
+      .components
+      .iter()
+      .find(|component| component.name.ends_with("Position"))
+      .unwrap();
+    assert_eq!(position.size, mem::size_of::<Position>());
+    assert_eq!((position.len, position.filled), (4, 3));
+    assert!(position.capacity >= 4);
+    assert!(position.bytes >= position.capacity * position.size + 4);
+
+    let frozen = diagnostics
+      .components
+      .iter()
+      .find(|component| component.name.ends_with("Frozen"))
+      .unwrap();
+    assert_eq!((frozen.size, frozen.filled), (0, 1));
+
+    assert_eq!(diagnostics.resources.len(), 1);
+    assert_eq!(diagnostics.resource_bytes(), mem::size_of::<Gravity>());
+    assert_eq!(diagnostics.total_bytes(), diagnostics.component_bytes() + mem::size_of::<Gravity>());
+
+    let report = diagnostics.to_string();
+    assert!(report.lines().any(|line| line.contains("Position") && line.contains("table")));
+    assert!(report.lines().any(|line| line.contains("Frozen") && line.contains("sparse")));
+    assert!(report.ends_with(&format!("total: {} bytes", diagnostics.total_bytes())));
+    Ok(())
+  }
+
+  #[test]
+  fn count_table_marker_holders() -> Result<()> {
+    let mut world = World::new();
+    for x in 0..3 {
+      world.spawn((Position(x as f32, 0.0),))?;
+    }
+    world.add_component(1, Frozen)?;
+
+    let diagnostics = world.diagnostics();
+    let frozen = diagnostics
+      .components
+      .iter()
+      .find(|component| component.name.ends_with("Frozen"))
+      .unwrap();
+    assert_eq!((frozen.kind, frozen.len, frozen.filled), (StorageKind::Table, 3, 1));
+    Ok(())
+  }
+}
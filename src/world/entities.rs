@@ -1,238 +1,903 @@
 use crate::{
-  errors::EcsErrors,
-  storage::{Bundle, EcsData, ErasedVec, TypeInfo, TypeMap}
+  errors::{EcsErrors, ErasedVecErrors, IntegrityError, Result},
+  storage::{Bitmask, Bundle, ComponentStorage, DynamicBundle, EcsData, ErasedBundle, StorageKind, Tick, TypeInfo, TypeMap}
+};
+use std::{
+  cmp::Reverse,
+  collections::{BinaryHeap, HashMap},
+  fmt::Debug,
+  ptr,
+  sync::{
+    atomic::{AtomicUsize, Ordering},
+    Mutex
+  }
 };
-use eyre::Result;
 
 // Refactor:
 // -Implement tests for inserting and deleting erased
 // -Add add_components_erased, delete_components_erased, and with_components
-// -Add a reserved_entity field to hold ids that have been reserved but not
-// populated. Check if an entity is contained in that field during creation and
-// skip it when assigning a new one. Remove id from field once it has something
-// added to it.
 
 pub type Entity = usize;
 
+/// Dense index of a registered component type.
+///
+/// Looking a component's storage up by id skips hashing its [`TypeInfo`].
+/// Ids are only valid for the entities they were registered with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ComponentId(pub u32);
+
+impl ComponentId {
+  /// Returns the id as an index into the component storage.
+  pub fn index(self) -> usize {
+    self.0 as usize
+  }
+}
+
 #[derive(Default)]
 pub struct EntitiesInner {
-  pub components:TypeMap<ErasedVec>,
-  /// Contains the bitmasks for registered components.
-  bitmasks:TypeMap<u128>,
+  /// Component storage indexed by [`ComponentId`].
+  ///
+  /// A type's id is also its bit in the entity bitmasks, so each column's
+  /// [`TypeInfo`] doubles as the bit to type reverse map.
+  pub components:Vec<ComponentStorage>,
+  /// Maps registered component types to their [`ComponentId`].
+  ids:TypeMap<ComponentId>,
+  /// Contains the bitmasks for registered components indexed by
+  /// [`ComponentId`].
+  bitmasks:Vec<Bitmask>,
   /// Vector of entity bitmasks.
-  pub map:Vec<u128>,
+  pub map:Vec<Bitmask>,
+  /// Tracks which entity slots hold a live entity.
+  ///
+  /// A slot is allocated from creation until [`EntitiesInner::delete_entity`]
+  /// frees it, whether or not its bitmask holds any components yet.
+  alive:Vec<bool>,
+  /// The current change detection tick.
+  tick:Tick,
+  /// Number of ids past the end of `map` handed out by
+  /// [`EntitiesInner::reserve_entity`] which have not been flushed yet.
+  reserved:AtomicUsize,
+  /// Dead entity slots which are reused before the store grows, lowest
+  /// first.
+  free:Mutex<BinaryHeap<Reverse<Entity>>>,
+  /// Dead slots handed out by [`EntitiesInner::reserve_entity`] which have
+  /// not been flushed yet.
+  pending:Mutex<Vec<Entity>>,
+  /// The number of entities every column is sized for, including columns
+  /// registered later.
+  capacity:usize,
   inserting_into_index:Entity
 }
 
+// Components are inserted as `EcsData`, which is `Send + Sync` with the `sync`
+// feature. Runtime-defined types must uphold the same bounds
+#[cfg(feature = "sync")]
+unsafe impl Send for EntitiesInner {}
+#[cfg(feature = "sync")]
+unsafe impl Sync for EntitiesInner {}
+
 impl EntitiesInner {
   /// Register type `T` as a component type.
   ///
-  /// All types must be registered before they can be used as components.
-  pub fn register_component<T:EcsData>(&mut self) {
-    let ty = TypeInfo::of::<T>();
+  /// Types are registered automatically the first time they are inserted.
+  /// Registering a type which is already registered does nothing. Returns
+  /// the type's [`ComponentId`].
+  pub fn register_component<T:EcsData>(&mut self) -> ComponentId {
+    self.register_component_erased(TypeInfo::of::<T>())
+  }
+
+  /// Register type `T` as a component type and reserve room in its column
+  /// for at least `capacity` entities.
+  pub fn register_component_with_capacity<T:EcsData>(&mut self, capacity:usize) -> ComponentId {
+    let id = self.register_component::<T>();
+
+    let components = &mut self.components[id.index()];
+    components.reserve_exact(capacity.saturating_sub(components.len()));
+    id
+  }
+
+  /// Register type `T` as a component type stored densely, see
+  /// [`StorageKind::Dense`].
+  ///
+  /// If `T` is already registered its storage is kept.
+  pub fn register_component_dense<T:EcsData>(&mut self) -> ComponentId {
+    self.register_component_storage(TypeInfo::of::<T>(), StorageKind::Dense)
+  }
+
+  /// Register type `T` as a component type stored in a sparse set, see
+  /// [`StorageKind::Sparse`].
+  ///
+  /// If `T` is already registered its storage is kept.
+  pub fn register_component_sparse<T:EcsData>(&mut self) -> ComponentId {
+    self.register_component_storage(TypeInfo::of::<T>(), StorageKind::Sparse)
+  }
+
+  /// Register type `T` as a component type which can be cloned.
+  ///
+  /// If `T` is already registered its stored data is kept and the clone shim
+  /// is attached to the existing column.
+  pub fn register_component_cloneable<T:EcsData + Clone>(&mut self) -> ComponentId {
+    self.register_component_erased(TypeInfo::of_cloneable::<T>())
+  }
+
+  /// Register type `T` as a component type which is [`Copy`].
+  ///
+  /// If `T` is already registered its stored data is kept and the copy and
+  /// clone shims are attached to the existing column.
+  pub fn register_component_copy<T:EcsData + Copy>(&mut self) -> ComponentId {
+    self.register_component_erased(TypeInfo::of_copy::<T>())
+  }
+
+  /// Register type `T` as a component type which can be debug formatted.
+  ///
+  /// If `T` is already registered its stored data is kept and the debug shim
+  /// is attached to the existing column.
+  pub fn register_component_debug<T:EcsData + Debug>(&mut self) -> ComponentId {
+    self.register_component_erased(TypeInfo::of_debug::<T>())
+  }
+
+  /// Register a type-erased component type.
+  ///
+  /// The new column is padded so every existing entity has an empty slot.
+  /// Registering a type which is already registered only attaches any shims
+  /// `ty` carries which the existing registration lacks.
+  pub fn register_component_erased(&mut self, ty:TypeInfo) -> ComponentId {
+    self.register_component_storage(ty, StorageKind::Table)
+  }
+
+  /// Register a type-erased component type stored as `kind`.
+  ///
+  /// Behaves like [`Self::register_component_erased`]. Types which are
+  /// already registered keep their storage kind.
+  pub fn register_component_storage(&mut self, ty:TypeInfo, kind:StorageKind) -> ComponentId {
+    // Do not replace the existing storage or assign the type a second id
+    if let Some(id) = self.component_id(&ty) {
+      let components = &mut self.components[id.index()];
+      if let Some(ty) = components.ty().merge_shims(ty) {
+        components.set_ty(ty);
+      }
+      return id;
+    }
+
     // Create new component storage
-    self.components.insert(ty, ErasedVec::new::<T>());
+    let id = ComponentId(self.components.len() as u32);
+    let mut components = ComponentStorage::new_erased(ty, kind);
+    components.set_tick(self.tick);
+    components.reserve_exact(self.capacity.max(self.map.len()));
+    for _ in 0..self.map.len() {
+      components.pad();
+    }
+    self.components.push(components);
+    self.ids.insert(ty, id);
 
     // Create a new bitmask for the type
-    self.bitmasks.insert(ty, 1 << self.bitmasks.len());
+    self.bitmasks.push(Bitmask::from_bit(id.index()));
+    id
+  }
+
+  /// Reserves room for at least `additional` more entities in the entity
+  /// store and in every component column.
+  ///
+  /// Columns registered afterwards are created with the same capacity so
+  /// creating entities up to it never reallocates.
+  pub fn reserve_entities(&mut self, additional:usize) {
+    self.capacity = self.capacity.max(self.map.len() + additional);
+    let additional = self.capacity - self.map.len();
+    self.map.reserve_exact(additional);
+    self.alive.reserve_exact(additional);
+    for components in self.components.iter_mut() {
+      components.reserve_exact(additional);
+    }
+  }
+
+  /// Closes the holes left in dense storages by removed components.
+  pub fn compact(&mut self) {
+    self.components.iter_mut().for_each(ComponentStorage::compact);
+  }
+
+  /// Returns the [`ComponentId`] of a registered component type.
+  pub fn component_id(&self, ty:&TypeInfo) -> Option<ComponentId> {
+    self.ids.get(ty).copied()
+  }
+
+  /// Returns the storage of a registered component type.
+  pub fn column(&self, ty:&TypeInfo) -> Option<&ComponentStorage> {
+    self.component_id(ty).map(|id| &self.components[id.index()])
+  }
+
+  /// Mutably returns the storage of a registered component type.
+  pub fn column_mut(&mut self, ty:&TypeInfo) -> Option<&mut ComponentStorage> {
+    self.component_id(ty).map(|id| &mut self.components[id.index()])
+  }
+
+  /// Returns an iterator over every entity holding a component of type `T`
+  /// alongside the component.
+  ///
+  /// Entities are checked against their bitmask since the slots of marker
+  /// columns all read as filled.
+  pub fn iter_column<T:EcsData>(&self) -> impl Iterator<Item = (Entity, &T)> + '_ {
+    self.component_id(&TypeInfo::of::<T>()).into_iter().flat_map(move |id| {
+      let mask = &self.bitmasks[id.index()];
+      self.components[id.index()]
+        .iter::<T>()
+        .zip(&self.map)
+        .enumerate()
+        .filter_map(move |(entity, (component, map))| Some((entity, component.filter(|_| map.contains(mask))?)))
+    })
+  }
+
+  /// Returns an iterator over every entity holding a component of type `T`
+  /// alongside a mutable reference to the component.
+  ///
+  /// Every yielded component is marked as changed.
+  pub fn iter_column_mut<T:EcsData>(&mut self) -> impl Iterator<Item = (Entity, &mut T)> + '_ {
+    let id = self.component_id(&TypeInfo::of::<T>());
+    let EntitiesInner {
+      components, bitmasks, map, ..
+    } = self;
+    let (column, map) = (id.map(|id| (&mut components[id.index()], &bitmasks[id.index()])), &*map);
+    column.into_iter().flat_map(move |(components, mask)| {
+      components
+        .iter_mut::<T>()
+        .zip(map.iter())
+        .enumerate()
+        .filter_map(move |(entity, (component, map))| Some((entity, component.filter(|_| map.contains(mask))?)))
+    })
+  }
+
+  /// Returns the bitmask of the component type registered as `id`.
+  ///
+  /// # Panics
+  /// - Panics if `id` was not registered with these entities.
+  pub fn bitmask_by_id(&self, id:ComponentId) -> &Bitmask {
+    &self.bitmasks[id.index()]
+  }
+
+  /// Returns the number of entities holding the component type registered as
+  /// `id`, counted from their bitmasks.
+  ///
+  /// # Panics
+  /// - Panics if `id` was not registered with these entities.
+  pub fn holder_count(&self, id:ComponentId) -> usize {
+    let mask = &self.bitmasks[id.index()];
+    self.map.iter().filter(|map| map.contains(mask)).count()
   }
 
   /// Returns the next free entity id for insertion.
   ///
-  /// # Warning
-  /// - Entities must be initalized with a component.
+  /// The entity is alive from creation until it is deleted, even if it holds
+  /// no components.
   pub fn create_entity(&mut self) -> Entity {
-    if let Some((index, _)) = self.map.iter().enumerate().find(|(_index, mask)| **mask == 0) {
+    // Reserved ids must exist before a slot is picked so they are not reused
+    self.flush_reserved();
+
+    if let Some(Reverse(index)) = self.free.get_mut().unwrap().pop() {
       self.inserting_into_index = index;
+      self.alive[index] = true;
     }
     // If there are no free entity slots grow the entities struct
     else {
-      self.components.iter_mut().for_each(|(_key, components)| components.pad());
-      self.map.push(0);
+      self.components.iter_mut().for_each(|components| components.pad());
+      self.map.push(Bitmask::new());
+      self.alive.push(true);
       self.inserting_into_index = self.map.len() - 1;
     }
     self.inserting_into_index
   }
 
-  /// Add a component of type `T` to the entity at `inserting_into_index`.
+  /// Returns the entity [`EntitiesInner::with_component`] inserts into.
+  pub fn inserting_into(&self) -> Entity {
+    self.inserting_into_index
+  }
+
+  /// Reserves an entity id without mutable access to the entity store.
   ///
-  /// Updates the entity's bitmap.
+  /// Dead slots are reused first, otherwise the id is past the end of the
+  /// store. Every reserved id is unique, even when reserving from several
+  /// threads. The entity is created, alive and without components, when
+  /// [`EntitiesInner::flush_reserved`] runs.
+  pub fn reserve_entity(&self) -> Entity {
+    let reused = self.free.lock().unwrap().pop();
+    match reused {
+      Some(Reverse(entity)) => {
+        self.pending.lock().unwrap().push(entity);
+        entity
+      }
+      None => self.map.len() + self.reserved.fetch_add(1, Ordering::Relaxed)
+    }
+  }
+
+  /// Returns `true` if the entity was created or reserved, even if it has
+  /// since been deleted.
+  pub fn exists(&self, entity:Entity) -> bool {
+    entity < self.map.len() + self.reserved.load(Ordering::Relaxed)
+  }
+
+  /// Creates every entity reserved with [`EntitiesInner::reserve_entity`].
+  pub fn flush_reserved(&mut self) {
+    for entity in self.pending.get_mut().unwrap().drain(..) {
+      self.alive[entity] = true;
+    }
+
+    let reserved = std::mem::take(self.reserved.get_mut());
+    for _ in 0..reserved {
+      self.components.iter_mut().for_each(|components| components.pad());
+      self.map.push(Bitmask::new());
+      self.alive.push(true);
+    }
+  }
+
+  /// Makes `entity` alive without any components, creating the slots up to
+  /// it if needed.
   ///
-  /// # Panics
-  /// - Panics if `T` has not been registered.
+  /// Does nothing if the entity is already alive.
+  pub fn revive(&mut self, entity:Entity) {
+    self.flush_reserved();
+    let free = self.free.get_mut().unwrap();
+    while self.map.len() <= entity {
+      self.components.iter_mut().for_each(|components| components.pad());
+      free.push(Reverse(self.map.len()));
+      self.map.push(Bitmask::new());
+      self.alive.push(false);
+    }
+
+    if !self.alive[entity] {
+      free.retain(|Reverse(free)| *free != entity);
+      self.alive[entity] = true;
+    }
+  }
+
+  /// Add a component of type `T` to the entity at `inserting_into_index`.
+  ///
+  /// Updates the entity's bitmap. Registers `T` if it has not been registered.
   pub fn with_component<T:EcsData>(&mut self, data:T) -> Result<()> {
-    let ty = TypeInfo::of::<T>();
     let index = self.inserting_into_index;
+    let id = self.register_component::<T>();
 
-    if let Some(components) = self.components.get_mut(&ty) {
-      components.set::<T>(index, data);
-
-      let bitmask = self.bitmasks.get(&ty).unwrap();
-      self.map[index] |= *bitmask
-    }
-    // Return an error if the component type was never registered
-    else {
-      return Err(EcsErrors::CreateComponentNeverCalled { component:ty.name() }.into());
-    };
+    // Drop the component being replaced
+    self.components[id.index()].reset::<T>(index, data);
+    self.map[index].insert(&self.bitmasks[id.index()]);
     Ok(())
   }
 
-  /// Add a [`Bundle`] of components to the entity at `inserting_into_index`.
-  ///
-  /// Updates the entity's bitmap.
+  /// Add a bundle of components to the entity at `inserting_into_index`.
   ///
-  /// # Panics
-  /// - Panics if `T` has not been registered.
-  pub fn with_components<B:Bundle>(&mut self, components:B) -> Result<()> {
+  /// Updates the entity's bitmap. Registers any component types which have
+  /// not been registered.
+  pub fn with_components<B:ErasedBundle>(&mut self, components:B) -> Result<()> {
     unsafe {
-      components.put(|ptr, ty| {
+      components.put_erased(|ptr, ty| {
         let entity = self.inserting_into_index;
+        let id = self.register_component_erased(ty);
 
-        if let Some(components) = self.components.get_mut(&ty) {
-          components.set_erased(entity, ty, ptr);
-
-          let bitmask = self.bitmasks.get(&ty).unwrap();
-          self.map[entity] |= *bitmask;
-          Ok(())
-        } else {
-          return Err(EcsErrors::CreateComponentNeverCalled { component:ty.name() }.into());
-        }
+        self.components[id.index()].reset_erased(entity, ty, ptr);
+        self.map[entity].insert(&self.bitmasks[id.index()]);
+        Ok(())
       })
     }
   }
 
   /// Delete a component from the entity.
+  ///
+  /// Drops the stored value.
   pub fn delete_component<T:EcsData>(&mut self, entity:Entity) -> Result<()> {
-    let ty = TypeInfo::of::<T>();
-    if let Some(mask) = self.bitmasks.get(&ty) {
-      self.map[entity] &= !*mask;
-    }
-    Ok(())
+    self.delete_component_erased(entity, TypeInfo::of::<T>())
   }
 
   /// Delete a type-erased component from the entity.
+  ///
+  /// Drops the stored value. Returns an error if the entity is not alive.
   pub fn delete_component_erased(&mut self, entity:Entity, ty:TypeInfo) -> Result<()> {
-    if let Some(mask) = self.bitmasks.get(&ty) {
-      self.map[entity] &= !*mask;
+    self.entity_map(entity)?;
+    if let Some(id) = self.component_id(&ty) {
+      self.map[entity].remove(&self.bitmasks[id.index()]);
+      self.components[id.index()].clear(entity);
     }
     Ok(())
   }
 
   /// Add a component to the provided entity.
   ///
-  /// Updates the entity's bitmap.
+  /// Updates the entity's bitmap. Registers `T` if it has not been registered.
   ///
-  /// # Panics
-  /// - Panics if `T` has not been registered.
+  /// Returns an error if the entity does not exist or the component's storage
+  /// cannot grow. The entity is left untouched on error.
   pub fn add_component<T:EcsData>(&mut self, entity:Entity, component:T) -> Result<()> {
-    let ty = TypeInfo::of::<T>();
-
-    if let Some(mask) = self.bitmasks.get(&ty) {
-      self.map[entity] |= *mask;
-    } else {
-      return Err(EcsErrors::ComponentNotRegistered.into());
-    };
+    self.entity_map(entity)?;
+    let id = self.register_component::<T>();
+    self.components[id.index()].try_reserve_slot(entity)?;
+    self.map[entity].insert(&self.bitmasks[id.index()]);
 
-    self.components.get_mut(&ty).unwrap().set::<T>(entity, component);
+    // Drop the component being replaced
+    self.components[id.index()].reset::<T>(entity, component);
 
     Ok(())
   }
 
   /// Add a type-erased component to the entity.
   ///
-  /// Updates the entity's bitmap.
+  /// Updates the entity's bitmap. Registers `ty` if it has not been
+  /// registered.
   ///
-  /// # Panics
-  /// - Panics if `T` has not been registered.
+  /// Returns an error if the entity does not exist or the component's storage
+  /// cannot grow, in which case the value behind `ptr` is left to the caller.
   pub fn add_component_erased(&mut self, entity:Entity, ty:TypeInfo, ptr:*mut u8) -> Result<()> {
-    let has_component = self.has_component_erased(entity, &ty)?;
-    if let Some(components) = self.components.get_mut(&ty) {
-      // If it has the component reset the slot
-      if has_component {
-        components.reset_erased(entity, ty, ptr);
+    self.entity_map(entity)?;
+    let id = self.register_component_erased(ty);
+    self.components[id.index()].try_reserve_slot(entity)?;
+
+    // The slot's `filled` flag, not the bitmask, decides whether a value is
+    // dropped before the new one is written
+    self.components[id.index()].reset_erased(entity, ty, ptr);
+    self.map[entity].insert(&self.bitmasks[id.index()]);
+    Ok(())
+  }
+
+  /// Add a bundle of components to the provided entity.
+  ///
+  /// Registers any component types which have not been registered.
+  pub fn add_components<B:ErasedBundle>(&mut self, entity:Entity, components:B) -> Result<()> {
+    unsafe { components.put_erased(|ptr, ty| self.add_component_erased(entity, ty, ptr)) }
+  }
+
+  /// Deletes an entity from the entities list matching the index.
+  ///
+  /// The next entity added will overwrite the emptied slot.
+  pub fn delete_entity(&mut self, entity:Entity) -> Result<()> {
+    if self.is_alive(entity) {
+      // Drop the entity's components
+      self.components.iter_mut().for_each(|components| components.clear(entity));
+      self.map[entity].clear();
+      self.alive[entity] = false;
+      self.free.get_mut().unwrap().push(Reverse(entity));
+    } else {
+      return Err(EcsErrors::EntityDoesNotExist { entity }.into());
+    }
+
+    Ok(())
+  }
+
+  /// Deletes the entity and moves every component it holds into a
+  /// [`DynamicBundle`].
+  ///
+  /// The bundle owns the components, so they are dropped with it unless it
+  /// is inserted into an entity.
+  pub fn despawn_take(&mut self, entity:Entity) -> Result<DynamicBundle> {
+    if !self.is_alive(entity) {
+      return Err(EcsErrors::EntityDoesNotExist { entity }.into());
+    }
+
+    let mut bundle = DynamicBundle::new();
+    for ty in self.component_types(entity) {
+      // The bundle copies the value out before the slot can be reused
+      let ptr = self.take_component_erased(entity, ty)?;
+      bundle.push_erased(ty, ptr);
+    }
+    self.delete_entity(entity)?;
+    Ok(bundle)
+  }
+
+  /// Creates a new entity holding clones of every component `src` holds.
+  ///
+  /// Returns an error if `src` is not alive or holds a component which was
+  /// not registered as cloneable. Nothing is created on error.
+  pub fn clone_entity(&mut self, src:Entity) -> Result<Entity> {
+    self.cloneable_map(src)?;
+    let dst = self.create_entity();
+    self.clone_entity_into(src, dst)?;
+    Ok(dst)
+  }
+
+  /// Overwrites `dst` with clones of every component `src` holds.
+  ///
+  /// Components `dst` holds which `src` lacks are dropped. Returns an error if
+  /// either entity is not alive or `src` holds a component which was not
+  /// registered as cloneable. `dst` is left untouched on error.
+  pub fn clone_entity_into(&mut self, src:Entity, dst:Entity) -> Result<()> {
+    let src_map = self.cloneable_map(src)?;
+    if !self.is_alive(dst) {
+      return Err(EcsErrors::EntityDoesNotExist { entity:dst }.into());
+    }
+    if src == dst {
+      return Ok(());
+    }
+
+    for (components, mask) in self.components.iter_mut().zip(&self.bitmasks) {
+      if src_map.contains(mask) {
+        components.clone_within(src, dst)?;
+      } else {
+        components.clear(dst);
       }
-      // Otherwise set the slot
-      else {
-        components.set_erased(entity, ty, ptr);
+    }
+    self.map[dst] = src_map;
+    Ok(())
+  }
+
+  /// Returns the bitmask of `src` if it is alive and every component it holds
+  /// can be cloned.
+  fn cloneable_map(&self, src:Entity) -> Result<Bitmask> {
+    if !self.is_alive(src) {
+      return Err(EcsErrors::EntityDoesNotExist { entity:src }.into());
+    }
+
+    let src_map = self.map[src].clone();
+    for (components, mask) in self.components.iter().zip(&self.bitmasks) {
+      if src_map.contains(mask) && !components.ty().is_cloneable() {
+        return Err(ErasedVecErrors::NotCloneable(components.ty().name()).into());
       }
+    }
+    Ok(src_map)
+  }
 
-      let bitmask = self.bitmasks.get(&ty).unwrap();
-      self.map[entity] |= *bitmask;
-      Ok(())
-    } else {
-      return Err(EcsErrors::CreateComponentNeverCalled { component:ty.name() }.into());
+  /// Moves the entity's component of type `ty` out of its column.
+  ///
+  /// Unsets the component's bit and marks the slot unfilled without dropping
+  /// the value. The caller takes ownership of the value behind the returned
+  /// pointer and must move it out before the slot is written to again.
+  pub fn take_component_erased(&mut self, entity:Entity, ty:TypeInfo) -> Result<*mut u8> {
+    if !self.has_component_erased(entity, &ty)? {
+      return Err(EcsErrors::ComponentDataDoesNotExist { entity, ty:ty.name() }.into());
     }
+
+    let id = self.component_id(&ty).unwrap();
+    self.map[entity].remove(&self.bitmasks[id.index()]);
+    Ok(self.components[id.index()].take_erased(entity))
   }
 
-  /// Add a [`Bundle`] of components to the provided entity.
+  /// Removes every component in the [`Bundle`] `B` from the entity and
+  /// returns them.
   ///
-  /// # Panics
-  /// - Panics if a component's type has not been registered.
-  pub fn add_components<B:Bundle>(&mut self, entity:Entity, components:B) -> Result<()> {
+  /// Returns an error if the entity is not alive or is missing any component
+  /// in `B`. Nothing is removed on error.
+  pub fn take_bundle<B:Bundle>(&mut self, entity:Entity) -> Result<B> {
+    if !self.is_alive(entity) {
+      return Err(EcsErrors::EntityDoesNotExist { entity }.into());
+    }
+
+    // Check every component up front so a missing one does not leave the
+    // entity partially stripped
+    for ty in B::types() {
+      if !self.has_component_erased(entity, &ty)? {
+        return Err(EcsErrors::ComponentDataDoesNotExist { entity, ty:ty.name() }.into());
+      }
+    }
+
     unsafe {
-      components.put(|ptr, ty| {
-        let has_component = self.has_component_erased(entity, &ty)?;
-        if let Some(components) = self.components.get_mut(&ty) {
-          // If it has the component reset the slot
-          if has_component {
-            components.reset_erased(entity, ty, ptr);
-          }
-          // Otherwise set the slot
-          else {
-            components.set_erased(entity, ty, ptr);
+      B::take(|out, ty| {
+        let ptr = self.take_component_erased(entity, ty)?;
+        ptr::copy_nonoverlapping(ptr, out, ty.size());
+        Ok(())
+      })
+    }
+  }
+
+  /// Deep copies the stored entities and the components of every cloneable
+  /// type.
+  ///
+  /// Columns of types without a clone shim are left empty in the copy and
+  /// their bits are removed from the copied entity bitmasks.
+  pub fn snapshot(&self) -> EntitiesInner {
+    let mut skipped = Bitmask::new();
+    let components = self
+      .components
+      .iter()
+      .zip(&self.bitmasks)
+      .map(|(components, mask)| {
+        components.try_clone().unwrap_or_else(|| {
+          skipped.insert(mask);
+          let mut copy = ComponentStorage::new_erased(components.ty(), components.kind());
+          for _ in 0..components.len() {
+            copy.pad();
           }
+          copy
+        })
+      })
+      .collect();
+
+    let map = self
+      .map
+      .iter()
+      .map(|mask| {
+        let mut mask = mask.clone();
+        mask.remove(&skipped);
+        mask
+      })
+      .collect();
+
+    EntitiesInner {
+      components,
+      ids:self.ids.clone(),
+      bitmasks:self.bitmasks.clone(),
+      map,
+      alive:self.alive.clone(),
+      tick:self.tick,
+      reserved:AtomicUsize::new(self.reserved.load(Ordering::Relaxed)),
+      free:Mutex::new(self.free.lock().unwrap().clone()),
+      pending:Mutex::new(self.pending.lock().unwrap().clone()),
+      capacity:self.capacity,
+      inserting_into_index:self.inserting_into_index
+    }
+  }
+
+  ///Returns the current change detection tick.
+  pub fn tick(&self) -> Tick {
+    self.tick
+  }
+
+  ///Advances the change detection tick and returns the new tick.
+  ///
+  /// Components written from now on are stamped with the new tick.
+  pub fn increment_tick(&mut self) -> Tick {
+    self.tick = self.tick.wrapping_add(1);
+    for components in self.components.iter_mut() {
+      components.set_tick(self.tick);
+    }
+    self.tick
+  }
+
+  ///Returns `true` if the entity has been created and not deleted.
+  pub fn is_alive(&self, entity:Entity) -> bool {
+    self.alive.get(entity).copied().unwrap_or(false)
+  }
 
-          let bitmask = self.bitmasks.get(&ty).unwrap();
-          self.map[entity] |= *bitmask;
-          Ok(())
-        } else {
-          return Err(EcsErrors::CreateComponentNeverCalled { component:ty.name() }.into());
+  ///Returns the [`TypeInfo`] of every component the entity holds, in
+  /// registration order.
+  pub fn component_types(&self, entity:Entity) -> Vec<TypeInfo> {
+    self.map[entity].iter().filter_map(|bit| self.component_type(bit)).collect()
+  }
+
+  /// Returns the [`TypeInfo`] of the component type whose bitmask bit is
+  /// `bit`, including any shims attached since it was registered.
+  pub fn component_type(&self, bit:usize) -> Option<TypeInfo> {
+    self.components.get(bit).map(|components| components.ty())
+  }
+
+  /// Iterates over the [`TypeInfo`] of every registered component type, in
+  /// registration order.
+  pub fn registered_components(&self) -> impl Iterator<Item = TypeInfo> + '_ {
+    self.components.iter().map(|components| components.ty())
+  }
+
+  /// Returns the component of type `T` held by the entity.
+  pub fn get_component<T:EcsData>(&self, entity:Entity) -> Result<&T> {
+    self.get_component_by_id::<T>(entity, self.registered_id::<T>()?)
+  }
+
+  /// Mutably returns the component of type `T` held by the entity.
+  pub fn get_component_mut<T:EcsData>(&self, entity:Entity) -> Result<&mut T> {
+    self.get_component_mut_by_id::<T>(entity, self.registered_id::<T>()?)
+  }
+
+  /// Returns the component registered as `id` held by the entity.
+  ///
+  /// # Panics
+  /// - Panics if `id` was not registered with these entities.
+  /// - Panics if `id` was not registered for `T`.
+  pub fn get_component_by_id<T:EcsData>(&self, entity:Entity, id:ComponentId) -> Result<&T> {
+    let components = &self.components[id.index()];
+
+    if self.entity_map(entity)?.contains(&self.bitmasks[id.index()]) {
+      // The slot is checked in case the bitmask and storage disagree
+      components.try_get::<T>(entity).ok_or_else(|| {
+        EcsErrors::InconsistentStorage {
+          entity,
+          ty:components.ty().name()
         }
+        .into()
       })
+    } else {
+      Err(
+        EcsErrors::ComponentDataDoesNotExist {
+          entity,
+          ty:components.ty().name()
+        }
+        .into()
+      )
     }
   }
 
-  /// Deletes an entity from the entities list matching the index.
+  /// Mutably returns the component registered as `id` held by the entity.
   ///
-  /// The next entity added will overwrite the emptied slot.
-  pub fn delete_entity(&mut self, entity:Entity) -> Result<()> {
-    if let Some(map) = self.map.get_mut(entity) {
-      *map = 0;
+  /// # Panics
+  /// - Panics if `id` was not registered with these entities.
+  /// - Panics if `id` was not registered for `T`.
+  pub fn get_component_mut_by_id<T:EcsData>(&self, entity:Entity, id:ComponentId) -> Result<&mut T> {
+    let components = &self.components[id.index()];
+
+    if self.entity_map(entity)?.contains(&self.bitmasks[id.index()]) {
+      // The slot is checked in case the bitmask and storage disagree
+      components.try_get_mut::<T>(entity).ok_or_else(|| {
+        EcsErrors::InconsistentStorage {
+          entity,
+          ty:components.ty().name()
+        }
+        .into()
+      })
     } else {
-      return Err(EcsErrors::EntityDoesNotExist.into());
+      Err(
+        EcsErrors::ComponentDataDoesNotExist {
+          entity,
+          ty:components.ty().name()
+        }
+        .into()
+      )
     }
+  }
 
+  /// Returns the entity's [`Bitmask`] or an error if the entity is not
+  /// alive.
+  fn entity_map(&self, entity:Entity) -> Result<&Bitmask> {
+    match self.map.get(entity) {
+      Some(map) if self.is_alive(entity) => Ok(map),
+      _ => Err(EcsErrors::EntityDoesNotExist { entity }.into())
+    }
+  }
+
+  /// Returns the [`ComponentId`] of `T` or an error if `T` was never
+  /// registered.
+  fn registered_id<T:EcsData>(&self) -> Result<ComponentId> {
+    let ty = TypeInfo::of::<T>();
+    self
+      .component_id(&ty)
+      .ok_or_else(|| EcsErrors::ComponentNotRegistered { component:ty.name() }.into())
+  }
+
+  /// Returns a pointer to the type-erased component held by the entity
+  /// alongside the [`TypeInfo`] registered for it.
+  ///
+  /// The component is marked as changed.
+  pub fn get_component_ptr(&self, entity:Entity, ty:&TypeInfo) -> Result<(*mut u8, TypeInfo)> {
+    if self.has_component_erased(entity, ty)? {
+      let components = self.column(ty).unwrap();
+      if !components.is_filled(entity) {
+        return Err(EcsErrors::InconsistentStorage { entity, ty:ty.name() }.into());
+      }
+      components.mark_changed(entity);
+      Ok((unsafe { components.indexed_ptr::<u8>(entity) }, components.ty()))
+    } else {
+      Err(EcsErrors::ComponentDataDoesNotExist { entity, ty:ty.name() }.into())
+    }
+  }
+
+  /// Overwrites the type-erased component held by the entity with the value
+  /// behind `ptr`, dropping the value it held.
+  ///
+  /// Returns an error if the entity does not hold a component of type `ty`.
+  ///
+  /// # Warning
+  /// - The value behind `ptr` is moved into the store. Must call
+  ///   [`mem::forget`](std::mem::forget) on it or wrap it in a
+  ///   [`ManuallyDrop`](std::mem::ManuallyDrop) or a double free will occur.
+  pub fn set_component_erased(&mut self, entity:Entity, ty:TypeInfo, ptr:*mut u8) -> Result<()> {
+    if !self.has_component_erased(entity, &ty)? {
+      return Err(EcsErrors::ComponentDataDoesNotExist { entity, ty:ty.name() }.into());
+    }
+    let id = self.component_id(&ty).unwrap();
+    self.components[id.index()].reset_erased(entity, ty, ptr);
     Ok(())
   }
 
-  ///Returns an [`Option<u128>`] containing the `bitmask`of a given
+  /// Checks the storage invariants the rest of the entity store relies on.
+  ///
+  /// - Every column has a slot per entity.
+  /// - Every entity's bitmask agrees with the `filled` flags of each column.
+  ///   Markers are skipped since only the bitmasks track them.
+  /// - No reserved entity is also on the free list.
+  /// - Every component's bitmask is a distinct single bit.
+  ///
+  /// Returns every broken invariant found.
+  pub fn validate(&self) -> Result<(), Vec<IntegrityError>> {
+    let mut errors = Vec::new();
+
+    for (components, mask) in self.components.iter().zip(&self.bitmasks) {
+      let ty = components.ty().name();
+      if components.len() != self.map.len() {
+        errors.push(IntegrityError::ColumnLength {
+          ty:ty.clone(),
+          len:components.len(),
+          entities:self.map.len()
+        });
+      }
+
+      if components.tracks_filled() {
+        for (entity, entity_map) in self.map.iter().enumerate() {
+          match (entity_map.contains(mask), components.is_filled(entity)) {
+            (true, false) => errors.push(IntegrityError::MissingComponent { entity, ty:ty.clone() }),
+            (false, true) => errors.push(IntegrityError::UntrackedComponent { entity, ty:ty.clone() }),
+            _ => {}
+          }
+        }
+      }
+    }
+
+    let free = self.free.lock().unwrap();
+    for &entity in self.pending.lock().unwrap().iter() {
+      if free.iter().any(|Reverse(free)| *free == entity) {
+        errors.push(IntegrityError::ReservedAndFree { entity });
+      }
+    }
+
+    let mut bits = HashMap::new();
+    for (components, mask) in self.components.iter().zip(&self.bitmasks) {
+      let ty = components.ty().name();
+      let mut set = mask.iter();
+      match (set.next(), set.next()) {
+        (Some(bit), None) => {
+          if let Some(other) = bits.insert(bit, ty.clone()) {
+            errors.push(IntegrityError::SharedBitmask { ty, other });
+          }
+        }
+        _ => errors.push(IntegrityError::InvalidBitmask { ty })
+      }
+    }
+
+    match errors.is_empty() {
+      true => Ok(()),
+      false => Err(errors)
+    }
+  }
+
+  ///Returns an [`Option<&Bitmask>`] containing the `bitmask`of a given
   /// [`TypeInfo`].
-  pub fn get_bitmask(&self, ty:&TypeInfo) -> Option<u128> {
-    self.bitmasks.get(ty).copied()
+  pub fn get_bitmask(&self, ty:&TypeInfo) -> Option<&Bitmask> {
+    self.component_id(ty).map(|id| &self.bitmasks[id.index()])
   }
 
-  ///Checks whether an entity has a component of type `T` and returns a
-  /// [`Result<bool>`].
+  ///Checks whether an entity has a component of type `T`.
   ///
-  /// # Panics
-  /// - Panics if the component was never registered;
+  /// Returns an error if the component was never registered or the entity
+  /// was never created.
   pub fn has_component<T:EcsData>(&self, entity:Entity) -> Result<bool> {
     let ty = TypeInfo::of::<T>();
 
     match self.get_bitmask(&ty) {
-      Some(mask) => Ok((self.map[entity] & mask) != 0),
-      None => Err(EcsErrors::ComponentNotRegistered.into())
+      Some(mask) => Ok(self.entity_map(entity)?.contains(mask)),
+      None => Err(EcsErrors::ComponentNotRegistered { component:ty.name() }.into())
     }
   }
 
-  ///Checks whether an entity has a component and returns a [`Result<bool>`].
+  ///Returns the combined [`Bitmask`] of every component in the [`Bundle`]
+  /// `B`.
   ///
-  /// # Panics
-  /// - Panics if the component was never registered;
+  /// Returns an error if a component in `B` was never registered.
+  pub fn bundle_mask<B:Bundle>(&self) -> Result<Bitmask> {
+    let mut mask = Bitmask::new();
+    for ty in B::types() {
+      match self.get_bitmask(&ty) {
+        Some(bit) => mask.insert(bit),
+        None => return Err(EcsErrors::ComponentNotRegistered { component:ty.name() }.into())
+      }
+    }
+    Ok(mask)
+  }
+
+  ///Checks whether an entity has every component in the [`Bundle`] `B`.
+  ///
+  /// Returns an error if a component in `B` was never registered or the
+  /// entity is not alive.
+  pub fn has_components<B:Bundle>(&self, entity:Entity) -> Result<bool> {
+    let mask = self.bundle_mask::<B>()?;
+    self.has_components_by_mask(entity, &mask)
+  }
+
+  ///Checks whether an entity has every component set in `mask`.
+  ///
+  /// Returns an error if the entity is not alive.
+  pub fn has_components_by_mask(&self, entity:Entity, mask:&Bitmask) -> Result<bool> {
+    if !self.is_alive(entity) {
+      return Err(EcsErrors::EntityDoesNotExist { entity }.into());
+    }
+    Ok(self.map[entity].contains(mask))
+  }
+
+  ///Checks whether an entity has a component.
+  ///
+  /// Returns an error if the component was never registered or the entity
+  /// was never created.
   pub fn has_component_erased(&self, entity:Entity, ty:&TypeInfo) -> Result<bool> {
-    match self.get_bitmask(&ty) {
-      Some(mask) => Ok((self.map[entity] & mask) != 0),
-      None => Err(EcsErrors::ComponentNotRegistered.into())
+    match self.get_bitmask(ty) {
+      Some(mask) => Ok(self.entity_map(entity)?.contains(mask)),
+      None => Err(EcsErrors::ComponentNotRegistered { component:ty.name() }.into())
     }
   }
 }
@@ -241,13 +906,15 @@ impl EntitiesInner {
 #[allow(clippy::float_cmp)]
 mod tests {
   use super::*;
+  use crate::errors::NinaError;
+  use std::{mem, sync::Arc};
 
   #[test]
   fn register_an_entity() {
     let mut entities:EntitiesInner = EntitiesInner::default();
     let ty = TypeInfo::of::<Health>();
     entities.register_component::<Health>();
-    let health_components = entities.components.get(&ty).unwrap();
+    let health_components = entities.column(&ty).unwrap();
     assert_eq!(health_components.len(), 0);
   }
 
@@ -257,13 +924,70 @@ mod tests {
 
     entities.register_component::<Health>();
     let typeid = TypeInfo::of::<Health>();
-    let mask = entities.bitmasks.get(&typeid).unwrap();
-    assert_eq!(*mask, 1);
+    let mask = entities.get_bitmask(&typeid).unwrap();
+    assert_eq!(*mask, Bitmask::from_bit(0));
 
     entities.register_component::<Speed>();
     let typeid = TypeInfo::of::<Speed>();
-    let mask = entities.bitmasks.get(&typeid).unwrap();
-    assert_eq!(*mask, 2);
+    let mask = entities.get_bitmask(&typeid).unwrap();
+    assert_eq!(*mask, Bitmask::from_bit(1));
+  }
+
+  #[test]
+  fn registering_a_component_twice_keeps_its_data() -> Result<()> {
+    let mut entities:EntitiesInner = EntitiesInner::default();
+    entities.register_component::<Health>();
+    entities.register_component::<Speed>();
+
+    entities.create_entity();
+    entities.with_component(Health(100))?;
+    entities.create_entity();
+    entities.with_component(Health(50))?;
+
+    entities.register_component::<Health>();
+
+    // The bitmask was not reassigned
+    let mask = entities.get_bitmask(&TypeInfo::of::<Health>()).unwrap();
+    assert_eq!(*mask, Bitmask::from_bit(0));
+    assert_eq!(entities.bitmasks.len(), 2);
+
+    // The stored data was not destroyed
+    let healths = entities.column(&TypeInfo::of::<Health>()).unwrap();
+    assert_eq!(healths.len(), 2);
+    assert_eq!(healths.get::<Health>(0).0, 100);
+    assert_eq!(healths.get::<Health>(1).0, 50);
+    assert!(entities.has_component::<Health>(1)?);
+    Ok(())
+  }
+
+  #[test]
+  fn components_are_registered_on_first_insertion() -> Result<()> {
+    let mut entities:EntitiesInner = EntitiesInner::default();
+    entities.register_component::<Health>();
+
+    entities.create_entity();
+    entities.with_component(Health(100))?;
+    entities.create_entity();
+    entities.with_components((Health(50), Speed(10)))?;
+    entities.add_component(0, Damage(5))?;
+
+    // The new columns were padded to the existing entity count
+    let speeds = entities.column(&TypeInfo::of::<Speed>()).unwrap();
+    assert_eq!(speeds.len(), 2);
+    assert_eq!(speeds.get::<Speed>(1).0, 10);
+    let damages = entities.column(&TypeInfo::of::<Damage>()).unwrap();
+    assert_eq!(damages.len(), 2);
+    assert_eq!(damages.get::<Damage>(0).0, 5);
+
+    assert_eq!(entities.map[0], Bitmask::from_iter([0, 2]));
+    assert_eq!(entities.map[1], Bitmask::from_iter([0, 1]));
+    assert!(!entities.has_component::<Damage>(1)?);
+
+    // Entities created afterwards pad the new columns too
+    entities.create_entity();
+    let speeds = entities.column(&TypeInfo::of::<Speed>()).unwrap();
+    assert_eq!(speeds.len(), 3);
+    Ok(())
   }
 
   #[test]
@@ -272,16 +996,16 @@ mod tests {
     entities.register_component::<Health>();
     entities.register_component::<Speed>();
     entities.create_entity();
-    let healths = entities.components.get(&TypeInfo::of::<Health>()).unwrap();
-    let speeds = entities.components.get(&TypeInfo::of::<Speed>()).unwrap();
+    let healths = entities.column(&TypeInfo::of::<Health>()).unwrap();
+    let speeds = entities.column(&TypeInfo::of::<Speed>()).unwrap();
 
     //Confirm the entity's slot is padded
     assert!(healths.len() == speeds.len() && healths.len() == 1);
 
-    let health_data = unsafe { healths.get_unchecked::<[u8; 4]>(0).clone() };
+    let health_data = unsafe { *healths.data().get_unchecked::<[u8; 4]>(0) };
     assert_eq!(health_data, [0; 4]);
 
-    let speed_data = unsafe { speeds.get_unchecked::<[u8; 4]>(0).clone() };
+    let speed_data = unsafe { *speeds.data().get_unchecked::<[u8; 4]>(0) };
     assert_eq!(speed_data, [0; 4]);
   }
 
@@ -295,10 +1019,10 @@ mod tests {
     entities.with_component(Health(100))?;
     entities.with_component(Speed(15))?;
 
-    let borrowed_healths = entities.components.get(&TypeInfo::of::<Health>()).unwrap();
+    let borrowed_healths = entities.column(&TypeInfo::of::<Health>()).unwrap();
     let health = borrowed_healths.get::<Health>(0);
     assert_eq!(health.0, 100);
-    let borrowed_speeds = entities.components.get(&TypeInfo::of::<Speed>()).unwrap();
+    let borrowed_speeds = entities.column(&TypeInfo::of::<Speed>()).unwrap();
     let speed = borrowed_speeds.get::<Speed>(0);
     assert_eq!(speed.0, 15);
     Ok(())
@@ -319,19 +1043,19 @@ mod tests {
     // overwriting
     entities.add_components(0, (Health(100), Speed(15), vec![15_u16, 12_u16])).unwrap();
 
-    let borrowed_healths = entities.components.get(&TypeInfo::of::<Health>()).unwrap();
+    let borrowed_healths = entities.column(&TypeInfo::of::<Health>()).unwrap();
     let health = borrowed_healths.get::<Health>(0);
     assert_eq!(health.0, 100);
-    let borrowed_speeds = entities.components.get(&TypeInfo::of::<Speed>()).unwrap();
+    let borrowed_speeds = entities.column(&TypeInfo::of::<Speed>()).unwrap();
     let speed = borrowed_speeds.get::<Speed>(0);
     assert_eq!(speed.0, 15);
-    let borrowed_vec = entities.components.get(&TypeInfo::of::<Vec<u16>>()).unwrap();
+    let borrowed_vec = entities.column(&TypeInfo::of::<Vec<u16>>()).unwrap();
     let vec = borrowed_vec.get_mut::<Vec<u16>>(0);
     assert_eq!(vec[0], 15_u16);
     assert_eq!(vec[1], 12_u16);
     vec[0] = 20;
 
-    let borrowed_vec = entities.components.get(&TypeInfo::of::<Vec<u16>>()).unwrap();
+    let borrowed_vec = entities.column(&TypeInfo::of::<Vec<u16>>()).unwrap();
     let vec = borrowed_vec.get_mut::<Vec<u16>>(0);
     assert_eq!(vec[0], 20_u16);
     assert_eq!(vec[1], 12_u16);
@@ -350,14 +1074,14 @@ mod tests {
     entities.with_component(Health(100))?;
     entities.with_component(Speed(15))?;
 
-    let entity_map = entities.map[0];
-    assert_eq!(entity_map, 3);
+    let entity_map = &entities.map[0];
+    assert_eq!(*entity_map, Bitmask::from_iter([0, 1]));
 
     entities.create_entity();
     entities.with_component(Speed(15))?;
 
-    let entity_map = entities.map[1];
-    assert_eq!(entity_map, 2);
+    let entity_map = &entities.map[1];
+    assert_eq!(*entity_map, Bitmask::from_bit(1));
 
     Ok(())
   }
@@ -375,12 +1099,31 @@ mod tests {
     entities.with_component(Speed(50))?;
     entities.with_component(Damage(50))?;
 
-    assert_eq!(entities.map[0], 7);
+    assert_eq!(entities.map[0], Bitmask::from_iter([0, 1, 2]));
 
     entities.delete_component::<Health>(0)?;
 
-    assert_eq!(entities.map[0], 6);
+    assert_eq!(entities.map[0], Bitmask::from_iter([1, 2]));
+
+    Ok(())
+  }
+
+  // Miri reports running out of memory instead of the allocator failing
+  #[cfg_attr(miri, ignore)]
+  #[test]
+  fn adding_to_a_storage_which_cannot_grow_errors() -> Result<()> {
+    unsafe fn drop_huge(_:*mut u8) {}
+    let layout = std::alloc::Layout::from_size_align(1 << 61, 1).unwrap();
+    let huge = TypeInfo::from_raw_parts(1, layout, drop_huge, "Huge");
+
+    let mut entities = EntitiesInner::default();
+    entities.register_component_storage(huge, StorageKind::Dense);
+    let entity = entities.create_entity();
 
+    // The storage fails to grow before the value would be read
+    let result = entities.add_component_erased(entity, huge, ptr::null_mut());
+    assert!(matches!(result, Err(NinaError::ErasedVec(ErasedVecErrors::ErasedVecAllocError))));
+    assert!(!entities.has_component_erased(entity, &huge)?);
     Ok(())
   }
 
@@ -397,11 +1140,11 @@ mod tests {
     entities.with_component(Speed(50))?;
     entities.with_component(Damage(50))?;
 
-    assert_eq!(entities.map[0], 7);
+    assert_eq!(entities.map[0], Bitmask::from_iter([0, 1, 2]));
 
     entities.delete_component_erased(0, TypeInfo::of::<Health>())?;
 
-    assert_eq!(entities.map[0], 6);
+    assert_eq!(entities.map[0], Bitmask::from_iter([1, 2]));
 
     Ok(())
   }
@@ -420,9 +1163,9 @@ mod tests {
       entities.with_component(Health(100))?;
       entities.add_component(0, Speed(50))?;
 
-      let borrowed_speeds = entities.components.get(&speed_ty).unwrap();
+      let borrowed_speeds = entities.column(&speed_ty).unwrap();
       let speed = borrowed_speeds.get::<Speed>(0);
-      assert_eq!(entities.map[0], 3);
+      assert_eq!(entities.map[0], Bitmask::from_iter([0, 1]));
       assert_eq!(speed.0, 50);
     }
 
@@ -441,13 +1184,13 @@ mod tests {
     entities.add_component_erased(1, speed_ty, (&mut Speed(90) as *mut Speed).cast())?;
 
     // Check Entity speeds
-    let borrowed_speeds = entities.components.get(&speed_ty).unwrap();
+    let borrowed_speeds = entities.column(&speed_ty).unwrap();
     let speed_1 = borrowed_speeds.get::<Speed>(0);
-    assert_eq!(entities.map[0], 3);
+    assert_eq!(entities.map[0], Bitmask::from_iter([0, 1]));
     assert_eq!(speed_1.0, 50);
 
     let speed_2 = borrowed_speeds.get::<Speed>(1);
-    assert_eq!(entities.map[1], 3);
+    assert_eq!(entities.map[1], Bitmask::from_iter([0, 1]));
     assert_eq!(speed_2.0, 90);
 
     Ok(())
@@ -469,10 +1212,10 @@ mod tests {
     entities.create_entity();
     entities.add_component_erased(1, speed_ty, (&mut Speed(131) as *mut Speed).cast::<u8>())?;
 
-    assert_eq!(entities.map[0], 3);
+    assert_eq!(entities.map[0], Bitmask::from_iter([0, 1]));
 
     let speed_ty = TypeInfo::of::<Speed>();
-    let borrowed_speeds = entities.components.get(&speed_ty).unwrap();
+    let borrowed_speeds = entities.column(&speed_ty).unwrap();
     let speed_1 = borrowed_speeds.get::<Speed>(0);
 
     assert_eq!(speed_1.0, 50);
@@ -484,6 +1227,45 @@ mod tests {
     Ok(())
   }
 
+  #[test]
+  fn dead_entities_reject_components() -> Result<()> {
+    let mut entities = EntitiesInner::default();
+    let speed_ty = TypeInfo::of::<Speed>();
+    entities.register_component::<Speed>();
+    entities.create_entity();
+    entities.with_component(Health(100))?;
+    entities.delete_entity(0)?;
+
+    assert!(matches!(
+      entities.add_component(0, Speed(5)),
+      Err(NinaError::Ecs(EcsErrors::EntityDoesNotExist { entity:0 }))
+    ));
+    assert!(matches!(
+      entities.add_component_erased(0, speed_ty, (&mut Speed(5) as *mut Speed).cast()),
+      Err(NinaError::Ecs(EcsErrors::EntityDoesNotExist { entity:0 }))
+    ));
+
+    // The reused slot starts out empty
+    assert_eq!(entities.create_entity(), 0);
+    assert!(!entities.has_component_erased(0, &speed_ty)?);
+    assert!(!entities.column(&speed_ty).unwrap().is_filled(0));
+    assert!(entities.validate().is_ok());
+    Ok(())
+  }
+
+  #[test]
+  fn deleting_from_missing_entities_errors() {
+    let mut entities = EntitiesInner::default();
+    entities.register_component::<Health>();
+    entities.create_entity();
+
+    assert!(matches!(
+      entities.delete_component::<Health>(99),
+      Err(NinaError::Ecs(EcsErrors::EntityDoesNotExist { entity:99 }))
+    ));
+    assert!(entities.delete_component::<Health>(0).is_ok());
+  }
+
   #[test]
   fn delete_entity_by_id() -> Result<()> {
     let mut entities = EntitiesInner::default();
@@ -495,7 +1277,7 @@ mod tests {
 
     entities.delete_entity(0)?;
 
-    assert_eq!(entities.map[0], 0);
+    assert!(entities.map[0].is_empty());
 
     Ok(())
   }
@@ -517,10 +1299,10 @@ mod tests {
     entities.create_entity();
     entities.with_component(Health(25))?;
 
-    assert_eq!(entities.map[0], 1);
+    assert_eq!(entities.map[0], Bitmask::from_bit(0));
 
     let ty = TypeInfo::of::<Health>();
-    let borrowed_healths = entities.components.get(&ty).unwrap();
+    let borrowed_healths = entities.column(&ty).unwrap();
     let health = borrowed_healths.get::<Health>(0);
 
     assert_eq!(health.0, 25);
@@ -528,6 +1310,128 @@ mod tests {
     Ok(())
   }
 
+  #[test]
+  fn replacing_a_component_drops_the_old_value() -> Result<()> {
+    let drops = Arc::new(AtomicUsize::new(0));
+    let mut entities = EntitiesInner::default();
+
+    entities.create_entity();
+    entities.with_component(Counted(drops.clone()))?;
+    entities.with_component(Counted(drops.clone()))?;
+    assert_eq!(drops.load(Ordering::Relaxed), 1);
+
+    entities.with_components((Counted(drops.clone()), Health(1)))?;
+    assert_eq!(drops.load(Ordering::Relaxed), 2);
+
+    let mut counted = mem::ManuallyDrop::new(Counted(drops.clone()));
+    entities.add_component_erased(0, TypeInfo::of::<Counted>(), (&mut *counted as *mut Counted).cast())?;
+    assert_eq!(drops.load(Ordering::Relaxed), 3);
+
+    assert!(entities.validate().is_ok());
+    drop(entities);
+    assert_eq!(drops.load(Ordering::Relaxed), 4);
+    Ok(())
+  }
+
+  #[test]
+  fn validate_detects_corrupted_bookkeeping() {
+    let mut entities = EntitiesInner::default();
+    entities.register_component::<Health>();
+    entities.register_component::<Speed>();
+    entities.create_entity();
+    entities.create_entity();
+    entities.delete_entity(0).unwrap();
+    let reserved = entities.reserve_entity();
+    assert!(entities.validate().is_ok());
+
+    entities.free.get_mut().unwrap().push(Reverse(reserved));
+    entities.bitmasks[1] = entities.bitmasks[0].clone();
+    entities.bitmasks.push(Bitmask::from_iter([3, 4]));
+    entities
+      .components
+      .push(ComponentStorage::new_erased(TypeInfo::of::<u8>(), StorageKind::Table));
+    for _ in 0..2 {
+      entities.components[2].pad();
+    }
+
+    let name = |ty:TypeInfo| ty.name();
+    assert_eq!(
+      entities.validate(),
+      Err(vec![
+        IntegrityError::ReservedAndFree { entity:reserved },
+        IntegrityError::SharedBitmask {
+          ty:name(TypeInfo::of::<Speed>()),
+          other:name(TypeInfo::of::<Health>())
+        },
+        IntegrityError::InvalidBitmask {
+          ty:name(TypeInfo::of::<u8>())
+        }
+      ])
+    );
+  }
+
+  #[test]
+  fn padded_slots_are_never_dropped() -> Result<()> {
+    let mut entities = EntitiesInner::default();
+    entities.register_component::<String>();
+    entities.register_component::<Vec<u32>>();
+
+    // Padded slots of reused and reserved entities hold zeroed bytes
+    entities.create_entity();
+    entities.with_component(Health(1))?;
+    entities.create_entity();
+    entities.with_component("a".to_string())?;
+    entities.delete_entity(0)?;
+    let reserved = entities.reserve_entity();
+    entities.flush_reserved();
+
+    for entity in [0, reserved] {
+      let mut name = mem::ManuallyDrop::new(entity.to_string());
+      entities.add_component_erased(entity, TypeInfo::of::<String>(), (&mut *name as *mut String).cast())?;
+      entities.add_component(entity, vec![entity as u32])?;
+    }
+    assert!(entities.validate().is_ok());
+
+    assert_eq!(entities.get_component::<String>(reserved)?, &reserved.to_string());
+    assert_eq!(entities.get_component::<Vec<u32>>(0)?, &vec![0]);
+    assert!(entities.get_component::<Vec<u32>>(1).is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn created_entities_without_components_keep_their_slots() -> Result<()> {
+    let mut entities = EntitiesInner::default();
+    entities.create_entity();
+    entities.with_component(Health(1))?;
+    entities.delete_entity(0)?;
+
+    // Neither entity holds a component yet but both slots are allocated
+    let first = entities.create_entity();
+    let second = entities.create_entity();
+    let third = entities.reserve_entity();
+    assert_eq!((first, second, third), (0, 1, 2));
+
+    entities.add_component(first, Health(10))?;
+    entities.add_component(second, Health(20))?;
+    assert_eq!(entities.get_component::<Health>(first)?.0, 10);
+    assert_eq!(entities.get_component::<Health>(second)?.0, 20);
+
+    // Slots are only freed by deleting the entity
+    entities.delete_component::<Health>(first)?;
+    assert_eq!(entities.create_entity(), 3);
+    entities.delete_entity(first)?;
+    assert_eq!(entities.create_entity(), first);
+    Ok(())
+  }
+
+  struct Counted(Arc<AtomicUsize>);
+
+  impl Drop for Counted {
+    fn drop(&mut self) {
+      self.0.fetch_add(1, Ordering::Relaxed);
+    }
+  }
+
   struct Health(pub u32);
   struct Speed(pub u32);
   struct Damage(pub u32);
@@ -0,0 +1,181 @@
+use super::{entities::Entity, Entities};
+use crate::{
+  errors::{EcsErrors, Result},
+  storage::{EcsData, ErasedBundle, TypeInfo}
+};
+
+/// Structure which provides read access to a single entity's components.
+pub struct EntityRef<'a> {
+  id:Entity,
+  entities:&'a Entities
+}
+
+impl<'a> EntityRef<'a> {
+  /// Create a new [`EntityRef`].
+  ///
+  /// Returns an error if the entity does not exist.
+  pub fn new(id:Entity, entities:&'a Entities) -> Result<Self> {
+    if !entities.is_alive(id) {
+      return Err(EcsErrors::EntityDoesNotExist { entity:id }.into());
+    }
+    Ok(Self { id, entities })
+  }
+
+  /// Returns the referenced [`Entity`].
+  pub fn id(&self) -> Entity {
+    self.id
+  }
+
+  /// Fetches the entity's component of type `T`.
+  pub fn get<T:EcsData>(&self) -> Result<&T> {
+    self.entities.get_component::<T>(self.id)
+  }
+
+  /// Returns `true` if the entity holds a component of type `T`.
+  ///
+  /// Returns `false` if `T` was never registered.
+  pub fn contains<T:EcsData>(&self) -> bool {
+    self.entities.has_component::<T>(self.id).unwrap_or(false)
+  }
+
+  /// Returns the [`TypeInfo`] of every component the entity holds, in no
+  /// particular order.
+  pub fn component_types(&self) -> Vec<TypeInfo> {
+    self.entities.component_types(self.id)
+  }
+}
+
+/// Structure which provides read and write access to a single entity's
+/// components.
+pub struct EntityMut<'a> {
+  id:Entity,
+  entities:&'a mut Entities
+}
+
+impl<'a> EntityMut<'a> {
+  /// Create a new [`EntityMut`].
+  ///
+  /// Returns an error if the entity does not exist.
+  pub fn new(id:Entity, entities:&'a mut Entities) -> Result<Self> {
+    if !entities.is_alive(id) {
+      return Err(EcsErrors::EntityDoesNotExist { entity:id }.into());
+    }
+    Ok(Self { id, entities })
+  }
+
+  /// Returns the referenced [`Entity`].
+  pub fn id(&self) -> Entity {
+    self.id
+  }
+
+  /// Fetches the entity's component of type `T`.
+  pub fn get<T:EcsData>(&self) -> Result<&T> {
+    self.entities.get_component::<T>(self.id)
+  }
+
+  /// Mutably fetches the entity's component of type `T`.
+  pub fn get_mut<T:EcsData>(&mut self) -> Result<&mut T> {
+    self.entities.get_component_mut::<T>(self.id)
+  }
+
+  /// Returns `true` if the entity holds a component of type `T`.
+  ///
+  /// Returns `false` if `T` was never registered.
+  pub fn contains<T:EcsData>(&self) -> bool {
+    self.entities.has_component::<T>(self.id).unwrap_or(false)
+  }
+
+  /// Returns the [`TypeInfo`] of every component the entity holds, in no
+  /// particular order.
+  pub fn component_types(&self) -> Vec<TypeInfo> {
+    self.entities.component_types(self.id)
+  }
+
+  /// Add a component to the entity, replacing the existing component of the
+  /// same type.
+  pub fn insert<T:EcsData>(&mut self, component:T) -> Result<&mut Self> {
+    self.entities.add_component(self.id, component)?;
+    Ok(self)
+  }
+
+  /// Add a bundle of components to the entity, replacing existing
+  /// components of the same types.
+  pub fn insert_bundle<B:ErasedBundle>(&mut self, components:B) -> Result<&mut Self> {
+    self.entities.add_components(self.id, components)?;
+    Ok(self)
+  }
+
+  /// Remove the component of type `T` from the entity.
+  pub fn remove<T:EcsData>(&mut self) -> Result<&mut Self> {
+    self.entities.delete_component::<T>(self.id)?;
+    Ok(self)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::errors::Result;
+  use crate::{storage::TypeInfo, world::World};
+
+  #[test]
+  fn read_entity_through_entity_ref() -> Result<()> {
+    let mut world = World::new();
+    world.create_entity().with_components((Health(100), Armor(5)))?;
+    world.create_entity().with_component(Health(30))?;
+
+    let entity = world.entity(0)?;
+    assert_eq!(entity.id(), 0);
+    assert_eq!(entity.get::<Health>()?.0, 100);
+    assert!(entity.contains::<Armor>());
+    assert!(!entity.contains::<String>());
+
+    let mut tys = entity.component_types();
+    tys.sort();
+    let mut expected = vec![TypeInfo::of::<Health>(), TypeInfo::of::<Armor>()];
+    expected.sort();
+    assert_eq!(tys, expected);
+
+    let entity = world.entity(1)?;
+    assert!(entity.get::<Armor>().is_err());
+    assert_eq!(entity.component_types(), vec![TypeInfo::of::<Health>()]);
+    Ok(())
+  }
+
+  #[test]
+  fn modify_entity_through_entity_mut() -> Result<()> {
+    let mut world = World::new();
+    world.create_entity().with_component(Health(100))?;
+
+    let mut entity = world.entity_mut(0)?;
+    entity.get_mut::<Health>()?.0 -= 10;
+    entity
+      .insert(Armor(5))?
+      .insert_bundle((Health(200), String::from("a")))?
+      .remove::<Armor>()?;
+
+    assert_eq!(world.get_component::<Health>(0)?.0, 200);
+    assert_eq!(world.get_component::<String>(0)?, "a");
+    assert!(world.get_component::<Armor>(0).is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn dead_entities_cannot_be_accessed() -> Result<()> {
+    let mut world = World::new();
+    world.create_entity().with_component(Health(100))?;
+    world.delete_entity(0)?;
+
+    let error = world.entity(0).err().unwrap();
+    assert_eq!(error.to_string(), "Attempted to reference entity \"0\" which does not exist");
+    assert!(world.entity(1).is_err());
+    assert!(world.entity_mut(0).is_err());
+
+    // Entities are alive from creation even without components
+    let entity = world.reserve_entity();
+    assert!(world.entity_mut(entity)?.insert(Armor(1)).is_ok());
+    Ok(())
+  }
+
+  struct Health(i32);
+  struct Armor(i32);
+}
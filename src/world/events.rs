@@ -0,0 +1,207 @@
+use std::{marker::PhantomData, mem};
+
+/// A double buffered queue of events of type `T`, stored as a resource.
+///
+/// Events are sent into the current buffer. [`Events::update`] swaps the
+/// buffers and drops the events sent before the previous update, so an event
+/// stays readable until the second update after it was sent.
+///
+/// Add it with [`World::add_event`](super::World::add_event) to have every
+/// [`Schedule`](super::schedule::Schedule) run update it.
+///
+/// ```
+/// # use nina::world::events::{EventReader, Events};
+/// let mut events = Events::new();
+/// let mut reader = EventReader::new();
+/// events.send("jump");
+///
+/// assert_eq!(reader.read(&events).collect::<Vec<_>>(), vec![&"jump"]);
+/// assert_eq!(reader.read(&events).count(), 0);
+/// ```
+pub struct Events<T> {
+  /// Events sent before the last update.
+  previous:Vec<T>,
+  /// Events sent since the last update.
+  current:Vec<T>,
+  /// The id of the first event in `previous`.
+  start:usize
+}
+
+impl<T> Default for Events<T> {
+  fn default() -> Self {
+    Events {
+      previous:Vec::new(),
+      current:Vec::new(),
+      start:0
+    }
+  }
+}
+
+impl<T> Events<T> {
+  pub fn new() -> Self {
+    Events::default()
+  }
+
+  /// Sends an event to every [`EventReader`].
+  pub fn send(&mut self, event:T) {
+    self.current.push(event);
+  }
+
+  /// Drops the events sent before the previous update and starts a new
+  /// buffer.
+  pub fn update(&mut self) {
+    self.start += self.previous.len();
+    self.previous = mem::take(&mut self.current);
+  }
+
+  /// Drops every event.
+  pub fn clear(&mut self) {
+    self.start += self.previous.len() + self.current.len();
+    self.previous.clear();
+    self.current.clear();
+  }
+
+  /// Returns the number of events which can still be read.
+  pub fn len(&self) -> usize {
+    self.previous.len() + self.current.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  /// Returns the id the next event sent will have.
+  fn end(&self) -> usize {
+    self.start + self.len()
+  }
+
+  /// Iterates over every event which can still be read, oldest first.
+  pub fn iter(&self) -> impl Iterator<Item = &T> {
+    self.previous.iter().chain(&self.current)
+  }
+}
+
+/// Reads [`Events`], keeping track of which events it has already read.
+///
+/// Each reader has its own cursor so several systems can read the same
+/// events independently. A new reader starts at the oldest event which can
+/// still be read.
+pub struct EventReader<T> {
+  /// The id of the next event to read.
+  next:usize,
+  _event:PhantomData<fn() -> T>
+}
+
+impl<T> Default for EventReader<T> {
+  fn default() -> Self {
+    EventReader { next:0, _event:PhantomData }
+  }
+}
+
+impl<T> EventReader<T> {
+  pub fn new() -> Self {
+    EventReader::default()
+  }
+
+  /// Iterates over the events sent since the reader last read `events`.
+  ///
+  /// Events which expired before the reader read them are skipped.
+  pub fn read<'a>(&mut self, events:&'a Events<T>) -> impl Iterator<Item = &'a T> {
+    let unread = self.next.saturating_sub(events.start);
+    self.next = events.end();
+    events.iter().skip(unread)
+  }
+
+  /// Returns the number of events sent since the reader last read `events`.
+  pub fn len(&self, events:&Events<T>) -> usize {
+    events.end() - self.next.max(events.start)
+  }
+
+  pub fn is_empty(&self, events:&Events<T>) -> bool {
+    self.len(events) == 0
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{EventReader, Events};
+  use crate::world::{resources::Res, schedule::Schedule, World};
+  use std::sync::{Arc, Mutex};
+
+  #[derive(Debug, Clone, Copy, PartialEq)]
+  struct Collision(u32);
+
+  fn read(reader:&mut EventReader<Collision>, events:&Events<Collision>) -> Vec<u32> {
+    reader.read(events).map(|collision| collision.0).collect()
+  }
+
+  #[test]
+  fn readers_drain_events_independently() {
+    let mut events = Events::new();
+    let mut first = EventReader::new();
+    let mut second = EventReader::new();
+
+    events.send(Collision(1));
+    assert_eq!(read(&mut first, &events), vec![1]);
+
+    events.send(Collision(2));
+    assert_eq!(first.len(&events), 1);
+    assert_eq!(read(&mut first, &events), vec![2]);
+    assert_eq!(read(&mut second, &events), vec![1, 2]);
+    assert!(second.is_empty(&events));
+  }
+
+  #[test]
+  fn late_readers_see_unexpired_events() {
+    let mut events = Events::new();
+    events.send(Collision(1));
+    events.update();
+    events.send(Collision(2));
+
+    assert_eq!(read(&mut EventReader::new(), &events), vec![1, 2]);
+
+    events.update();
+    assert_eq!(read(&mut EventReader::new(), &events), vec![2]);
+  }
+
+  #[test]
+  fn events_expire_after_two_updates() {
+    let mut events = Events::new();
+    let mut reader = EventReader::new();
+    events.send(Collision(1));
+
+    events.update();
+    assert_eq!(events.len(), 1);
+    events.update();
+    assert!(events.is_empty());
+
+    // The reader never saw the event but does not read it after it expired
+    events.send(Collision(2));
+    assert_eq!(reader.len(&events), 1);
+    assert_eq!(read(&mut reader, &events), vec![2]);
+  }
+
+  #[test]
+  fn schedules_update_added_events() {
+    let mut world = World::new();
+    world.add_event::<Collision>();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+
+    let mut schedule = Schedule::new();
+    let mut reader = EventReader::new();
+    let observed = seen.clone();
+    schedule.add_system(move |events:Res<Events<Collision>>| {
+      observed.lock().unwrap().push(read(&mut reader, &events));
+    });
+
+    world.send_event(Collision(1));
+    schedule.run(&mut world);
+    assert_eq!(world.get_resource::<Events<Collision>>().len(), 1);
+    world.send_event(Collision(2));
+    schedule.run(&mut world);
+    schedule.run(&mut world);
+
+    assert_eq!(*seen.lock().unwrap(), vec![vec![1], vec![2], vec![]]);
+    assert!(world.get_resource::<Events<Collision>>().is_empty());
+  }
+}
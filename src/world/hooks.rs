@@ -0,0 +1,127 @@
+use super::{command_buffer::CommandBuffer, entities::Entity, World};
+use crate::storage::{TypeInfo, TypeMap};
+use std::sync::Arc;
+
+/// A callback run when a component is added to or removed from an entity.
+pub type ComponentHook = Arc<dyn Fn(&mut World, Entity) + Send + Sync>;
+
+/// The hooks registered for each component type.
+#[derive(Default)]
+pub struct ComponentHooks(TypeMap<Vec<ComponentHook>>);
+
+impl ComponentHooks {
+  /// Registers a hook for components of type `ty`.
+  pub fn add(&mut self, ty:TypeInfo, hook:ComponentHook) {
+    self.0.entry(ty).or_default().push(hook);
+  }
+
+  /// Returns `true` if a hook is registered for components of type `ty`.
+  pub fn contains(&self, ty:&TypeInfo) -> bool {
+    self.0.contains_key(ty)
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.0.is_empty()
+  }
+
+  /// Queues the hooks registered for each type in `tys` into `buffer`, in
+  /// registration order.
+  pub fn queue(&self, buffer:&mut CommandBuffer, entity:Entity, tys:&[TypeInfo]) {
+    for hook in tys.iter().filter_map(|ty| self.0.get(ty)).flatten() {
+      let hook = hook.clone();
+      buffer.add(move |world| hook(world, entity));
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::{
+    errors::Result,
+    world::{entities::Entity, World}
+  };
+  use std::sync::{Arc, Mutex};
+
+  struct RigidBody;
+  struct Collider;
+
+  #[derive(Default)]
+  struct Physics {
+    bodies:Vec<Entity>,
+    colliders:Vec<Entity>
+  }
+
+  fn physics_world() -> World {
+    let mut world = World::new();
+    world.init_resource::<Physics>();
+    world
+      .on_add::<RigidBody>(|world, entity| world.get_resource_mut::<Physics>().bodies.push(entity))
+      .on_remove::<RigidBody>(|world, entity| world.get_resource_mut::<Physics>().bodies.retain(|body| *body != entity))
+      .on_add::<Collider>(|world, entity| world.get_resource_mut::<Physics>().colliders.push(entity))
+      .on_remove::<Collider>(|world, entity| world.get_resource_mut::<Physics>().colliders.retain(|body| *body != entity));
+    world
+  }
+
+  #[test]
+  fn hooks_run_when_the_world_flushes() -> Result<()> {
+    let mut world = physics_world();
+    let entity = world.spawn((1_u32,))?;
+
+    world.add_component(entity, RigidBody)?;
+    assert!(world.get_resource::<Physics>().bodies.is_empty());
+    world.flush();
+    assert_eq!(world.get_resource::<Physics>().bodies, vec![entity]);
+
+    // Replacing a component does not add it again
+    world.add_component(entity, RigidBody)?;
+    world.flush();
+    assert_eq!(world.get_resource::<Physics>().bodies, vec![entity]);
+
+    world.delete_component::<RigidBody>(entity)?;
+    world.delete_component::<RigidBody>(entity)?;
+    world.flush();
+    assert!(world.get_resource::<Physics>().bodies.is_empty());
+    Ok(())
+  }
+
+  #[test]
+  fn hooks_run_for_bundles_and_deleted_entities() -> Result<()> {
+    let mut world = physics_world();
+    let spawned = world.spawn((RigidBody, Collider))?;
+    world.create_entity().with_components((Collider, 1_u32))?;
+    let built = spawned + 1;
+    world.flush();
+
+    let physics = world.get_resource::<Physics>();
+    assert_eq!(physics.bodies, vec![spawned]);
+    assert_eq!(physics.colliders, vec![spawned, built]);
+    drop(physics);
+
+    world.delete_entity(spawned)?;
+    world.take_bundle::<(Collider,)>(built)?;
+    world.flush();
+
+    let physics = world.get_resource::<Physics>();
+    assert!(physics.bodies.is_empty());
+    assert!(physics.colliders.is_empty());
+    Ok(())
+  }
+
+  #[test]
+  fn hooks_run_for_commands_and_can_queue_commands() -> Result<()> {
+    let mut world = physics_world();
+    let order = Arc::new(Mutex::new(Vec::new()));
+    let added = order.clone();
+    world.on_add::<u32>(move |world, entity| {
+      added.lock().unwrap().push(entity);
+      world.commands().insert_components(entity, (RigidBody,));
+    });
+
+    let entity = world.commands().spawn_entity((1_u32,));
+    world.flush();
+
+    assert_eq!(*order.lock().unwrap(), vec![entity]);
+    assert_eq!(world.get_resource::<Physics>().bodies, vec![entity]);
+    Ok(())
+  }
+}
@@ -1,18 +1,56 @@
 use self::{
-  entities::{EntitiesInner, Entity},
-  query::query::Query,
-  resources::Resources
+  access::Access,
+  command_buffer::{CommandBuffer, Commands},
+  diagnostics::{ComponentDiagnostics, ResourceDiagnostics, WorldDiagnostics},
+  entities::{ComponentId, EntitiesInner, Entity},
+  entity_ref::{EntityMut, EntityRef},
+  events::Events,
+  hooks::ComponentHooks,
+  prefab::Prefab,
+  query::{
+    fetch::Fetch,
+    prepared_query::PreparedQuery,
+    query::Query,
+    query_entity::{DebugMap, QueryEntity},
+    tuple_query::TupleQuery
+  },
+  resource_fetch::ResourceFetch,
+  resources::{Res, ResMut, Resources},
+  snapshot::{ComponentChange, SnapshotDiff, WorldSnapshot},
+  system::RunSystem,
+  tags::Tags
 };
 use crate::{
-  errors::EcsErrors,
-  storage::{Bundle, EcsData, TypeInfo}
+  errors::{EcsErrors, ErasedVecErrors, IntegrityError, Result},
+  storage::{Bitmask, Bundle, DynamicBundle, EcsData, ErasedBox, ErasedBundle, Tick, TypeInfo, TypeMap}
+};
+use hashbrown::HashMap;
+use std::{
+  borrow::Cow,
+  fmt::{self, Debug},
+  mem,
+  ptr::NonNull,
+  sync::{Arc, Mutex}
 };
-use eyre::Result;
 
+pub mod access;
 pub mod command_buffer;
+pub mod diagnostics;
 pub mod entities;
+pub mod entity_ref;
+pub mod events;
+pub mod hooks;
+pub mod prefab;
 pub mod query;
+pub mod resource_fetch;
 pub mod resources;
+pub mod schedule;
+#[cfg(feature = "serde")]
+pub mod serialization;
+pub mod snapshot;
+pub mod system;
+pub mod system_param;
+pub mod tags;
 
 //World must have mutation through &World
 // Refactor:
@@ -27,7 +65,26 @@ pub mod resources;
 
 pub struct World {
   resources:Resources,
-  entities:Entities
+  entities:Entities,
+  /// Commands queued through [`World::commands`] until the next flush.
+  commands:Mutex<CommandBuffer>,
+  /// Updates the [`Events`] added through [`World::add_event`].
+  event_updates:TypeMap<fn(&mut World)>,
+  /// Hooks run after a component is added to an entity.
+  on_add:ComponentHooks,
+  /// Hooks run after a component is removed from an entity.
+  on_remove:ComponentHooks,
+  /// Entities which lost a component of each type since the trackers were
+  /// last cleared.
+  removed:TypeMap<Vec<Entity>>,
+  /// Entities grouped by tag. Kept on the world rather than as a resource so
+  /// [`World::tagged`] can hand out a slice.
+  tags:Tags,
+  /// Prefabs by name.
+  prefabs:HashMap<String, Prefab>,
+  /// Components registered for serialization.
+  #[cfg(feature = "serde")]
+  serde:serialization::SerdeRegistry
 }
 
 //Resource Implementation
@@ -36,50 +93,291 @@ impl World {
   pub fn new() -> Self {
     World {
       resources:Default::default(),
-      entities:Default::default()
+      entities:Default::default(),
+      commands:Default::default(),
+      event_updates:Default::default(),
+      on_add:Default::default(),
+      on_remove:Default::default(),
+      removed:Default::default(),
+      tags:Default::default(),
+      prefabs:Default::default(),
+      #[cfg(feature = "serde")]
+      serde:Default::default()
     }
   }
 
+  /// Generates an empty [`World`] with room for `entities` entities.
+  ///
+  /// Every component column, including ones registered later, is sized for
+  /// the same number of entities.
+  pub fn with_capacity(entities:usize) -> Self {
+    let mut world = World::new();
+    world.reserve_entities_capacity(entities);
+    world
+  }
+
+  /// Reserves room for at least `additional` more entities in the entity
+  /// store and every component column.
+  pub fn reserve_entities_capacity(&mut self, additional:usize) -> &mut Self {
+    self.entities.reserve_entities(additional);
+    self
+  }
+
   /// Add a new resource to the world.
   pub fn add_resource(&mut self, data:impl EcsData) -> &mut Self {
     self.resources.add_resource(data);
     self
   }
 
-  /// Query a resource by type and get a reference.
+  /// Add a new resource to the world, returning the resource of the same
+  /// type it replaced.
+  pub fn insert_resource<T:EcsData>(&mut self, data:T) -> Option<T> {
+    self.resources.insert(data)
+  }
+
+  /// Add a new resource to the world which is included in snapshots.
+  pub fn add_resource_cloneable<T:EcsData + Clone>(&mut self, data:T) -> &mut Self {
+    self.resources.add_resource_cloneable(data);
+    self
+  }
+
+  /// Add `T::default()` as a resource unless a resource of type `T` has
+  /// already been added.
+  pub fn init_resource<T:EcsData + Default>(&mut self) -> &mut Self {
+    self.resources.get_or_insert_with(T::default);
+    self
+  }
+
+  /// Query a resource by type and get a mutable reference, first adding the
+  /// value returned by `f` if the resource has not been added.
+  pub fn get_resource_or_insert_with<T:EcsData>(&mut self, f:impl FnOnce() -> T) -> &mut T {
+    self.resources.get_or_insert_with(f)
+  }
+
+  /// Query a resource by type and borrow it.
+  ///
+  /// The borrow is released when the returned [`Res`] is dropped.
   ///
   /// # Panics
   ///
-  /// Panics if the resource has not been added.
-  pub fn get_resource<T:EcsData>(&self) -> &T {
+  /// Panics if the resource has not been added or is mutably borrowed.
+  pub fn get_resource<T:EcsData>(&self) -> Res<'_, T> {
     self.resources.get::<T>()
   }
 
-  /// Query a resource by type and get a mutable reference.
+  /// Query a resource by type and mutably borrow it.
+  ///
+  /// The borrow is released when the returned [`ResMut`] is dropped.
   ///
   /// # Panics
   ///
-  /// Panics if the resource has not been added.
-  pub fn get_resource_mut<T:EcsData>(&self) -> &mut T {
+  /// Panics if the resource has not been added or is already borrowed.
+  pub fn get_resource_mut<T:EcsData>(&self) -> ResMut<'_, T> {
     self.resources.get_mut::<T>()
   }
 
-  /// Remove a resource from the [`World`].
-  pub fn remove_resource<T:EcsData>(&mut self) {
+  /// Query a resource by type and borrow it. Never panics.
+  ///
+  /// Returns an error if the resource has not been added or is mutably
+  /// borrowed.
+  pub fn try_get_resource<T:EcsData>(&self) -> Result<Res<'_, T>> {
+    self.resources.try_borrow::<T>()
+  }
+
+  /// Query a resource by type and mutably borrow it. Never panics.
+  ///
+  /// Returns an error if the resource has not been added or is already
+  /// borrowed.
+  pub fn try_get_resource_mut<T:EcsData>(&self) -> Result<ResMut<'_, T>> {
+    self.resources.try_borrow_mut::<T>()
+  }
+
+  /// Borrows several resources in one call.
+  ///
+  /// `Q` is a tuple such as `(&Time, &mut Score, Option<&Settings>)`.
+  /// Resources behind `Option` are `None` when they have not been added.
+  ///
+  /// Returns an error if a required resource has not been added, if `Q`
+  /// borrows a resource mutably alongside any other borrow of the same type,
+  /// or if a resource is already borrowed in a conflicting way.
+  pub fn get_resources<Q:ResourceFetch>(&self) -> Result<Q::Item<'_>> {
+    Q::access(&mut Access::new())?;
+    Q::fetch(&self.resources)
+  }
+
+  /// Temporarily removes the resource of type `T` and runs `f` with both the
+  /// [`World`] and the resource, then puts the resource back.
+  ///
+  /// The resource is put back even if `f` panics.
+  ///
+  /// # Panics
+  ///
+  /// - Panics if the resource has not been added.
+  /// - Panics if `f` adds a resource of type `T`.
+  pub fn resource_scope<T:EcsData, R>(&mut self, f:impl FnOnce(&mut World, &mut T) -> R) -> R {
+    let ty = TypeInfo::of::<T>();
+    let (ty, data) = self
+      .resources
+      .remove_erased(&ty)
+      .unwrap_or_else(|| panic!("{}", EcsErrors::ResourceDataDoesNotExist { component:ty.name() }));
+
+    let scope = ResourceScope {
+      world:self,
+      ty,
+      data:Some(data)
+    };
+    let value = scope.data.as_ref().unwrap().get_mut::<T>();
+    let result = f(scope.world, value);
+
+    assert!(
+      !scope.world.resources.contains_erased(&ty),
+      "Resource {:?} was added while it was removed by `resource_scope`",
+      ty.name()
+    );
+    result
+  }
+
+  /// Returns `true` if a resource of type `T` has been added.
+  pub fn contains_resource<T:EcsData>(&self) -> bool {
+    self.resources.contains::<T>()
+  }
+
+  /// Remove a resource from the [`World`] and return it.
+  ///
+  /// Returns `None` if the resource has not been added.
+  pub fn remove_resource<T:EcsData>(&mut self) -> Option<T> {
     self.resources.remove::<T>()
   }
 }
 
+// Event implementation
+impl World {
+  /// Add an [`Events<T>`] resource unless it has already been added and
+  /// have every [`Schedule`](schedule::Schedule) run update it once after
+  /// its systems run.
+  pub fn add_event<T:EcsData>(&mut self) -> &mut Self {
+    self.init_resource::<Events<T>>();
+    self
+      .event_updates
+      .entry(TypeInfo::of::<Events<T>>())
+      .or_insert(|world| world.get_resource_or_insert_with(Events::<T>::new).update());
+    self
+  }
+
+  /// Send an event through the [`Events<T>`] resource.
+  ///
+  /// # Panics
+  /// - Panics if the resource has not been added or is already borrowed.
+  pub fn send_event<T:EcsData>(&self, event:T) {
+    self.get_resource_mut::<Events<T>>().send(event);
+  }
+
+  /// Updates every [`Events`] added through [`World::add_event`], dropping
+  /// the events sent before the previous update.
+  pub fn update_events(&mut self) {
+    let updates = self.event_updates.values().copied().collect::<Vec<_>>();
+    for update in updates {
+      update(self);
+    }
+  }
+}
+
 //Entity/Components Implementation
 impl World {
   /// Register type `T` as a component type.
   ///
-  /// All types must be registered before they can be used as components.
+  /// Types are registered automatically the first time they are inserted but
+  /// must be registered before they can be queried. Registering a type which
+  /// is already registered does nothing.
   pub fn register_component<T:EcsData>(&mut self) -> &mut Self {
     self.entities.register_component::<T>();
     self
   }
 
+  /// Register every component type in the [`Bundle`] `B`.
+  ///
+  /// Types which are already registered keep their storage and id.
+  pub fn register_components<B:Bundle>(&mut self) -> &mut Self {
+    for ty in B::types() {
+      self.register_component_raw(ty);
+    }
+    self
+  }
+
+  /// Register type `T` as a component type and reserve room in its storage
+  /// for at least `capacity` entities.
+  ///
+  /// Useful before spawning entities in bulk.
+  pub fn register_component_with_capacity<T:EcsData>(&mut self, capacity:usize) -> &mut Self {
+    self.entities.register_component_with_capacity::<T>(capacity);
+    self
+  }
+
+  /// Register type `T` as a component type stored densely.
+  ///
+  /// Dense components are packed into the front of their storage instead of
+  /// sitting at their entity's index, so iterating them with
+  /// [`World::dense_components`] touches no padding once the [`World`] is
+  /// [compacted](World::compact). If `T` is already registered its storage is
+  /// kept.
+  pub fn register_component_dense<T:EcsData>(&mut self) -> &mut Self {
+    self.entities.register_component_dense::<T>();
+    self
+  }
+
+  /// Register type `T` as a component type stored in a sparse set.
+  ///
+  /// Sparse components are packed like dense ones, but removing one moves
+  /// the last component into its place right away instead of leaving a hole.
+  /// Prefer it for components added and removed every few frames. Queries
+  /// read sparse components like any other. If `T` is already registered its
+  /// storage is kept.
+  pub fn register_component_sparse<T:EcsData>(&mut self) -> &mut Self {
+    self.entities.register_component_sparse::<T>();
+    self
+  }
+
+  /// Register type `T` as a component type which can be cloned.
+  ///
+  /// Only cloneable components are included in snapshots. If `T` is already
+  /// registered its stored data is kept.
+  pub fn register_component_cloneable<T:EcsData + Clone>(&mut self) -> &mut Self {
+    self.entities.register_component_cloneable::<T>();
+    self
+  }
+
+  /// Register type `T` as a component type which is [`Copy`].
+  ///
+  /// Only copy components are compared by [`WorldSnapshot::diff`]. If `T` is
+  /// already registered its stored data is kept.
+  pub fn register_component_copy<T:EcsData + Copy>(&mut self) -> &mut Self {
+    self.entities.register_component_copy::<T>();
+    self
+  }
+
+  /// Register a component type defined at runtime and return its
+  /// [`ComponentId`].
+  ///
+  /// Create `ty` with [`TypeInfo::from_raw_parts`]. Components of the type
+  /// are inserted with [`World::add_component_erased`] and queried through
+  /// their [`TypeInfo`] or [`ComponentId`].
+  ///
+  /// With the `sync` feature the [`World`] is `Send + Sync`, so the type's
+  /// data must be safe to send and share across threads.
+  pub fn register_component_raw(&mut self, ty:TypeInfo) -> ComponentId {
+    self.entities.register_component_erased(ty)
+  }
+
+  /// Register type `T` as a component type which can be debug formatted.
+  ///
+  /// Components registered without a debug shim are printed as
+  /// `TypeName(opaque)` by [`World::debug_entity`]. If `T` is already
+  /// registered its stored data is kept.
+  pub fn register_component_debug<T:EcsData + Debug>(&mut self) -> &mut Self {
+    self.entities.register_component_debug::<T>();
+    self
+  }
+
   /// Prepares the ECS for the insertion of data into a new `Entity`.
   ///
   /// The entity is initalized without any associated components.
@@ -91,87 +389,331 @@ impl World {
     self
   }
 
-  /// Reserves and returns a new `Entity`.
-  pub fn reserve_entity(&mut self) -> Entity {
-    self.entities.create_entity()
+  /// Reserves and returns a new `Entity` through a shared reference.
+  ///
+  /// Ids are unique even when reserved from several threads. The entity is
+  /// created, alive and without components, by the first component inserted
+  /// into it, [`World::flush_reservations`] or [`World::flush`].
+  pub fn reserve_entity(&self) -> Entity {
+    self.entities.reserve_entity()
   }
 
-  /// Add a component of type `T` to the entity at `inserting_into_index`.
+  /// Creates every entity reserved with [`World::reserve_entity`].
+  pub fn flush_reservations(&mut self) {
+    self.entities.flush_reserved();
+  }
+
+  /// Creates a new `Entity` holding the [`Bundle`] of components and returns
+  /// it.
   ///
-  /// Updates the entity's bitmap.
+  /// Registers any component types which have not been registered.
+  pub fn spawn<B:ErasedBundle>(&mut self, bundle:B) -> Result<Entity> {
+    let entity = self.entities.create_entity();
+    self.add_components(entity, bundle)?;
+    Ok(entity)
+  }
+
+  /// Creates an entity holding each [`Bundle`] yielded by `bundles` and
+  /// returns them in order.
   ///
-  /// # Panics
+  /// Room for the iterator's lower size bound is reserved up front. Unlike
+  /// [`World::spawn`] the bundle's component types must already be
+  /// registered, otherwise an error is returned before any entity is
+  /// created.
+  ///
+  /// ```
+  /// # use nina::world::World;
+  /// # struct Pos(f32);
+  /// # struct Vel(f32);
+  /// let mut world = World::new();
+  /// world.register_components::<(Pos, Vel)>();
+  /// let entities = world.spawn_iter(vec![(Pos(0.0), Vel(1.0)), (Pos(2.0), Vel(3.0))]).unwrap();
+  /// assert_eq!(entities, vec![0, 1]);
+  /// ```
+  pub fn spawn_iter<B:Bundle>(&mut self, bundles:impl IntoIterator<Item = B>) -> Result<Vec<Entity>> {
+    if let Some(ty) = B::types().into_iter().find(|ty| self.entities.component_id(ty).is_none()) {
+      return Err(EcsErrors::ComponentNotRegistered { component:ty.name() }.into());
+    }
+
+    let bundles = bundles.into_iter();
+    self.reserve_entities_capacity(bundles.size_hint().0);
+    bundles.map(|bundle| self.spawn(bundle)).collect()
+  }
+
+  /// Add a component of type `T` to the entity at `inserting_into_index`.
   ///
-  /// Panics if `T` has not been registered.
+  /// Updates the entity's bitmap. Registers `T` if it has not been registered.
   pub fn with_component<T:EcsData>(&mut self, data:T) -> Result<&mut Self> {
-    self.entities.with_component(data).unwrap();
+    let entity = self.entities.inserting_into();
+    self.adding(entity, vec![TypeInfo::of::<T>()], |entities| entities.with_component(data))?;
     Ok(self)
   }
 
   /// Add a [`Bundle`] of components to the entity at `inserting_into_index`.
   ///
-  /// Updates the entity's bitmap.
-  ///
-  /// # Panics
-  ///
-  /// Panics if `T` has not been registered.
-  pub fn with_components<B:Bundle>(&mut self, bundle:B) -> Result<()> {
-    self.entities.with_components(bundle)
+  /// Updates the entity's bitmap. Registers any component types which have
+  /// not been registered.
+  pub fn with_components<B:ErasedBundle>(&mut self, bundle:B) -> Result<&mut Self> {
+    let entity = self.entities.inserting_into();
+    self.adding(entity, bundle.component_types(), |entities| entities.with_components(bundle))?;
+    Ok(self)
   }
 
   /// Add a component to the entity.
+  ///
+  /// # Panics
+  /// - Panics if the entity was never created. See
+  ///   [`World::try_add_component`].
   pub fn add_component<T:EcsData>(&mut self, entity:Entity, data:T) -> Result<()> {
-    self.entities.add_component(entity, data)
+    self.assert_entity_exists(entity);
+    self.try_add_component(entity, data)
+  }
+
+  /// Add a component to the entity. Never panics.
+  ///
+  /// Returns an error if the entity was never created or if the component's
+  /// storage cannot grow to hold it.
+  pub fn try_add_component<T:EcsData>(&mut self, entity:Entity, data:T) -> Result<()> {
+    self.adding(entity, vec![TypeInfo::of::<T>()], |entities| entities.add_component(entity, data))
   }
 
   /// Add a component to the entity.
   pub fn add_component_erased(&mut self, entity:Entity, ty:TypeInfo, ptr:*mut u8) -> Result<()> {
-    self.entities.add_component_erased(entity, ty, ptr)
+    self.adding(entity, vec![ty], |entities| entities.add_component_erased(entity, ty, ptr))
   }
 
   /// Add a [`Bundle`] of components to the entity.
-  pub fn add_components<B:Bundle>(&mut self, entity:Entity, components:B) -> Result<()> {
-    self.entities.add_components(entity, components)
+  pub fn add_components<B:ErasedBundle>(&mut self, entity:Entity, components:B) -> Result<()> {
+    self.adding(entity, components.component_types(), |entities| {
+      entities.add_components(entity, components)
+    })
+  }
+
+  /// Checks whether the entity has every component in the [`Bundle`] `B`.
+  ///
+  /// Returns an error if a component in `B` was never registered or the
+  /// entity is not alive.
+  pub fn has_components<B:Bundle>(&self, entity:Entity) -> Result<bool> {
+    self.entities.has_components::<B>(entity)
+  }
+
+  /// Checks whether the entity has every component set in `mask`, such as
+  /// the mask of a [`PreparedQuery`].
+  ///
+  /// Returns an error if the entity is not alive.
+  pub fn has_components_by_mask(&self, entity:Entity, mask:&Bitmask) -> Result<bool> {
+    self.entities.has_components_by_mask(entity, mask)
+  }
+
+  /// Removes every component in the [`Bundle`] `B` from the entity and
+  /// returns them.
+  ///
+  /// Returns an error if the entity is not alive or is missing any component
+  /// in `B`. Nothing is removed on error.
+  pub fn take_bundle<B:Bundle>(&mut self, entity:Entity) -> Result<B> {
+    self.removing(entity, B::types(), |entities| entities.take_bundle(entity))
   }
 
   /// Returns the component from the queried entity.
   ///
+  /// Returns an error if the component was never registered or the entity
+  /// does not hold it.
+  ///
   /// # Panics
-  /// - Panics if the entity does not have the requested component.
+  /// - Panics if the entity was never created. See
+  ///   [`World::try_get_component`].
   pub fn get_component<T:EcsData>(&self, entity:Entity) -> Result<&T> {
-    let ty = TypeInfo::of::<T>();
-    if self.entities.has_component::<T>(entity)? {
-      return Ok(
-        self
-          .entities
-          .components
-          .get(&ty)
-          .ok_or(EcsErrors::ComponentNotRegistered)?
-          .get::<T>(entity)
-      );
-    } else {
-      return Err(EcsErrors::ComponentDataDoesNotExist { entity, ty:ty.name() }.into());
-    }
+    self.assert_entity_exists(entity);
+    self.try_get_component(entity)
+  }
+
+  /// Returns the component from the queried entity. Never panics.
+  ///
+  /// Returns an error if the component was never registered, the entity was
+  /// never created or the entity does not hold it.
+  pub fn try_get_component<T:EcsData>(&self, entity:Entity) -> Result<&T> {
+    self.entities.get_component::<T>(entity)
+  }
+
+  /// Returns the component registered as `id` from the queried entity.
+  ///
+  /// Skips looking the component type up. Get the id from
+  /// [`World::component_id`].
+  ///
+  /// # Panics
+  /// - Panics if `id` was not registered with this [`World`] or was registered
+  ///   for another type.
+  pub fn get_component_by_id<T:EcsData>(&self, entity:Entity, id:ComponentId) -> Result<&T> {
+    self.entities.get_component_by_id::<T>(entity, id)
+  }
+
+  /// Mutably returns the component registered as `id` from the queried
+  /// entity.
+  ///
+  /// # Panics
+  /// - Panics if `id` was not registered with this [`World`] or was registered
+  ///   for another type.
+  pub fn get_component_mut_by_id<T:EcsData>(&self, entity:Entity, id:ComponentId) -> Result<&mut T> {
+    self.entities.get_component_mut_by_id::<T>(entity, id)
+  }
+
+  /// Returns the [`ComponentId`] of `T`, or `None` if `T` was never
+  /// registered.
+  pub fn component_id<T:EcsData>(&self) -> Option<ComponentId> {
+    self.entities.component_id(&TypeInfo::of::<T>())
   }
 
   /// Mutably returns the component from the queried entity.
   ///
+  /// Returns an error if the component was never registered or the entity
+  /// does not hold it.
+  ///
   /// # Panics
-  /// - Panics if the entity does not have the requested component.
-  /// - Panics if the component is already borrowed in scope.
+  /// - Panics if the entity was never created. See
+  ///   [`World::try_get_component_mut`].
   pub fn get_component_mut<T:EcsData>(&self, entity:Entity) -> Result<&mut T> {
-    let ty = TypeInfo::of::<T>();
-    if self.entities.has_component::<T>(entity)? {
-      return Ok(
-        self
-          .entities
-          .components
-          .get(&ty)
-          .ok_or(EcsErrors::ComponentNotRegistered)?
-          .get_mut::<T>(entity)
-      );
-    } else {
-      return Err(EcsErrors::ComponentDataDoesNotExist { entity, ty:ty.name() }.into());
+    self.assert_entity_exists(entity);
+    self.try_get_component_mut(entity)
+  }
+
+  /// Mutably returns the component from the queried entity. Never panics.
+  ///
+  /// Returns an error if the component was never registered, the entity was
+  /// never created or the entity does not hold it.
+  pub fn try_get_component_mut<T:EcsData>(&self, entity:Entity) -> Result<&mut T> {
+    self.entities.get_component_mut::<T>(entity)
+  }
+
+  /// Panics with a readable message if the entity was never created or
+  /// reserved.
+  fn assert_entity_exists(&self, entity:Entity) {
+    assert!(self.entities.exists(entity), "{}", EcsErrors::EntityDoesNotExist { entity });
+  }
+
+  /// Returns the [`TypeInfo`] of every component the entity holds, in
+  /// registration order.
+  ///
+  /// Returns an empty vector if the entity is not alive.
+  pub fn components_of(&self, entity:Entity) -> Vec<TypeInfo> {
+    match self.entities.is_alive(entity) {
+      true => self.entities.component_types(entity),
+      false => Vec::new()
+    }
+  }
+
+  /// Returns a pointer to the entity's component of type `ty`.
+  ///
+  /// Use the [`TypeInfo`] returned by [`World::components_of`] to interpret
+  /// the pointer, for example through its debug shim. The component is
+  /// marked as changed.
+  ///
+  /// Returns an error if the entity does not hold a component of type `ty`.
+  ///
+  /// # Safety
+  /// The pointer is only valid while the component stays in place. Any
+  /// call taking `&mut World`, such as adding or removing components or
+  /// flushing commands, may move or drop it and leaves the pointer
+  /// dangling.
+  ///
+  /// The pointer is not tracked by the borrow checker. Reading through it
+  /// while the component is borrowed mutably, or writing through it while
+  /// the component is borrowed at all, for example by a query or another
+  /// pointer, is undefined behavior.
+  pub fn get_component_erased(&self, entity:Entity, ty:TypeInfo) -> Result<NonNull<u8>> {
+    let (ptr, _) = self.entities.get_component_ptr(entity, &ty)?;
+    // Columns hand out pointers into their allocation, or dangling but
+    // aligned pointers for zero-sized types
+    Ok(NonNull::new(ptr).unwrap())
+  }
+
+  /// Overwrites the entity's component of type `ty` with the value behind
+  /// `ptr`, dropping the value it held.
+  ///
+  /// The component is marked as changed and add hooks do not run. Returns an
+  /// error if the entity does not hold a component of type `ty`, in which
+  /// case the value behind `ptr` is left untouched.
+  ///
+  /// # Warning
+  /// - The value behind `ptr` is moved into the world. Must call
+  ///   [`mem::forget`] on it or wrap it in a [`mem::ManuallyDrop`] or a double
+  ///   free will occur.
+  pub fn set_component_erased(&mut self, entity:Entity, ty:TypeInfo, ptr:*mut u8) -> Result<()> {
+    self.entities.set_component_erased(entity, ty, ptr)
+  }
+
+  /// Iterates over the [`TypeInfo`] of every registered component type, in
+  /// registration order.
+  pub fn registered_components(&self) -> impl Iterator<Item = TypeInfo> + '_ {
+    self.entities.registered_components()
+  }
+
+  /// Formats the entity's id alongside the names and values of its
+  /// components.
+  pub fn debug_entity(&self, entity:Entity) -> String {
+    format!("{:?}", QueryEntity::new(entity, &self.entities))
+  }
+
+  /// Creates a new entity holding deep clones of every component `src`
+  /// holds and returns it.
+  ///
+  /// Every component `src` holds must have been registered with
+  /// [`World::register_component_cloneable`].
+  pub fn clone_entity(&mut self, src:Entity) -> Result<Entity> {
+    let entity = self.entities.clone_entity(src)?;
+    if !self.on_add.is_empty() {
+      let tys = self.entities.component_types(entity);
+      self.on_add.queue(self.commands.get_mut().unwrap(), entity, &tys);
+    }
+    Ok(entity)
+  }
+
+  /// Overwrites `dst` with deep clones of every component `src` holds.
+  ///
+  /// Components `dst` holds which `src` lacks are removed. Every component
+  /// `src` holds must have been registered with
+  /// [`World::register_component_cloneable`], otherwise `dst` is left
+  /// untouched and the error names the first component which cannot be
+  /// cloned.
+  pub fn clone_entity_into(&mut self, src:Entity, dst:Entity) -> Result<()> {
+    let tys = match self.entities.is_alive(src) {
+      true => self.entities.component_types(src),
+      false => Vec::new()
+    };
+    let dropped = match self.entities.is_alive(dst) && src != dst {
+      true => self.entities.component_types(dst).into_iter().filter(|ty| !tys.contains(ty)).collect(),
+      false => Vec::new()
+    };
+
+    let added = self.hooked(dst, tys);
+    self.removing(dst, dropped, |entities| entities.clone_entity_into(src, dst))?;
+    self.on_add.queue(self.commands.get_mut().unwrap(), dst, &added);
+    Ok(())
+  }
+
+  /// Debug check of the storage invariants.
+  ///
+  /// Checks that every component storage has a slot per entity, that every
+  /// entity's bitmask agrees with the storage on which components it holds,
+  /// that no reserved entity is also free, and that every component's
+  /// bitmask is a distinct single bit.
+  ///
+  /// Returns an [`IntegrityError`] for each broken invariant. With the
+  /// `debug-validate` feature, debug builds run it after every
+  /// [`flush`](World::flush) and panic if it fails.
+  pub fn validate(&self) -> Result<(), Vec<IntegrityError>> {
+    self.entities.validate()
+  }
+
+  /// Reports the memory used by each component storage and each resource.
+  ///
+  /// Only the report itself is allocated, so it is cheap enough to run every
+  /// frame.
+  pub fn diagnostics(&self) -> WorldDiagnostics {
+    WorldDiagnostics {
+      components:(0..self.entities.components.len())
+        .map(|index| ComponentDiagnostics::new(&self.entities, ComponentId(index as u32)))
+        .collect(),
+      resources:self.resources.types().map(ResourceDiagnostics::new).collect()
     }
   }
 
@@ -179,76 +721,1535 @@ impl World {
   ///
   /// The next entity added will overwrite the emptied slot.
   pub fn delete_entity(&mut self, entity:Entity) -> Result<()> {
-    self.entities.delete_entity(entity)?;
-    Ok(())
+    self.despawning(entity, |entities| entities.delete_entity(entity))
+  }
+
+  /// Deletes the entity and returns its components in a [`DynamicBundle`].
+  ///
+  /// The components are moved, not copied, so the bundle can be inserted
+  /// into another entity or another [`World`]. Remove hooks and trackers see
+  /// the components as removed.
+  pub fn despawn_take(&mut self, entity:Entity) -> Result<DynamicBundle> {
+    self.despawning(entity, |entities| entities.despawn_take(entity))
   }
 
   /// Delete a component from the entity.
   pub fn delete_component<T:EcsData>(&mut self, entity:Entity) -> Result<()> {
-    self.entities.delete_component::<T>(entity)
+    self.delete_component_erased(entity, TypeInfo::of::<T>())
   }
 
   /// Delete a type-erased component from the entity.
   pub fn delete_component_erased(&mut self, entity:Entity, ty:TypeInfo) -> Result<()> {
-    self.entities.delete_component_erased(entity, ty)
+    self.removing(entity, vec![ty], |entities| entities.delete_component_erased(entity, ty))
   }
-}
 
-// Query implementation
-impl World {
-  pub fn query(&self) -> Query {
-    Query::new(&self.entities)
+  /// Moves every entity in `other` into the [`World`].
+  ///
+  /// Each live entity in `other` is given a fresh entity and its components are
+  /// moved, not copied, into the matching columns. Component types `other`
+  /// holds which are not registered are registered. Resources are not merged.
+  ///
+  /// Returns an `(old, new)` pair for each of `other`'s live entities in
+  /// ascending order of their old ids, for remapping stored entity
+  /// references.
+  pub fn merge(&mut self, mut other:World) -> Vec<(Entity, Entity)> {
+    let entities = other.iter_entities().collect::<Vec<_>>();
+    let mut new_entities = Vec::with_capacity(entities.len());
+
+    for entity in entities {
+      let new_entity = self.entities.create_entity();
+      for ty in other.entities.component_types(entity) {
+        // The value is moved into `self` and `other` no longer owns it
+        let ptr = other.entities.take_component_erased(entity, ty).unwrap();
+        self.add_component_erased(new_entity, ty, ptr).unwrap();
+      }
+      new_entities.push((entity, new_entity));
+    }
+
+    new_entities
   }
-}
 
-// CommandBuffer implementation
-impl World {
-  pub fn command_buffer(&self) {}
-}
+  /// Moves the entity and every component it holds into a fresh entity in
+  /// `dest`, returning the new entity.
+  ///
+  /// The components are moved, not cloned, and the entity is deleted from the
+  /// [`World`]. Remove hooks and trackers see the components as removed and
+  /// `dest`'s add hooks run on its next flush. Tags are not carried over.
+  ///
+  /// Returns an error if the entity is not alive or if `dest` has not
+  /// registered one of its component types, naming the first such type.
+  /// Neither world is changed on error.
+  pub fn transfer(&mut self, entity:Entity, dest:&mut World) -> Result<Entity> {
+    if !self.entities.is_alive(entity) {
+      return Err(EcsErrors::EntityDoesNotExist { entity }.into());
+    }
+    let tys = self.entities.component_types(entity);
+    if let Some(ty) = tys.iter().find(|ty| dest.entities.component_id(ty).is_none()) {
+      return Err(EcsErrors::ComponentNotRegistered { component:ty.name() }.into());
+    }
 
-type Entities = EntitiesInner;
+    // Taking the components marks the source slots unfilled so only `dest`
+    // drops them
+    let components = self.despawn_take(entity)?;
+    dest.spawn(components)
+  }
 
-#[cfg(test)]
-mod tests {
-  use super::World;
+  /// Returns an [`EntityRef`] for reading the entity's components.
+  ///
+  /// Returns an error if the entity does not exist.
+  pub fn entity(&self, entity:Entity) -> Result<EntityRef<'_>> {
+    EntityRef::new(entity, &self.entities)
+  }
 
-  #[test]
-  fn systems_work() {
-    let mut world = World::new();
-    world.register_component::<Health>().register_component::<Armor>();
-    world.add_resource(Resource(100));
+  /// Returns an [`EntityMut`] for reading and modifying the entity's
+  /// components.
+  ///
+  /// Returns an error if the entity does not exist.
+  pub fn entity_mut(&mut self, entity:Entity) -> Result<EntityMut<'_>> {
+    self.entities.flush_reserved();
+    EntityMut::new(entity, &mut self.entities)
+  }
 
-    world.create_entity().with_components((Health(100.2), Armor(44))).unwrap();
-    world.create_entity().with_component(Health(540.2)).unwrap();
+  /// Returns an iterator over every live entity in the [`World`].
+  ///
+  /// Deleted entities are skipped. A deleted slot which has been reused is
+  /// yielded once.
+  pub fn iter_entities(&self) -> impl Iterator<Item = Entity> + '_ {
+    self.iter_entities_with_mask().map(|(entity, _)| entity)
+  }
 
-    let p1_health = world.get_component::<Health>(0).unwrap();
-    assert_eq!(p1_health.0, 100.2);
+  /// Returns an iterator over every entity holding a component of type `T`
+  /// alongside the component.
+  ///
+  /// Walks `T`'s storage directly instead of looking up each entity. Yields
+  /// nothing if `T` was never registered.
+  pub fn iter_components<T:EcsData>(&self) -> impl Iterator<Item = (Entity, &T)> + '_ {
+    self.entities.iter_column::<T>()
+  }
 
-    let p1_health = world.get_component_mut::<Health>(1).unwrap();
-    p1_health.0 = 100.0;
-    assert_eq!(p1_health.0, 100.0);
+  /// Returns an iterator over every entity holding a component of type `T`
+  /// alongside a mutable reference to the component.
+  ///
+  /// Every yielded component is marked as changed. Yields nothing if `T` was
+  /// never registered.
+  pub fn iter_components_mut<T:EcsData>(&mut self) -> impl Iterator<Item = (Entity, &mut T)> + '_ {
+    self.entities.iter_column_mut::<T>()
+  }
 
-    // some_system(&world);
+  /// Closes the holes removed components left in dense storages, moving the
+  /// last components into them.
+  ///
+  /// Dense and sparse storages are compacted, table storages are fixed to
+  /// their entities and left as is.
+  pub fn compact(&mut self) {
+    self.entities.compact();
   }
 
-  fn some_system(world:&World) {
-    let mut query = world.query();
-    let entities = query.with_component::<Health>().unwrap().without_component::<Armor>().unwrap().run();
+  /// Returns the components of the dense or sparse type `T` as a slice
+  /// alongside the entity holding each of them.
+  ///
+  /// Returns an error if `T` was not registered with
+  /// [`World::register_component_dense`] or
+  /// [`World::register_component_sparse`], or if the storage holds holes
+  /// because it was not [compacted](World::compact) since components were
+  /// removed.
+  pub fn dense_components<T:EcsData>(&self) -> Result<(&[Entity], &[T])> {
+    let ty = TypeInfo::of::<T>();
+    let components = self
+      .entities
+      .column(&ty)
+      .ok_or_else(|| EcsErrors::ComponentNotRegistered { component:ty.name() })?;
+    let entities = components.row_entities().ok_or_else(|| EcsErrors::NotDense { component:ty.name() })?;
+    Ok((entities, components.data().as_slice::<T>()?))
+  }
 
-    // Check resources can be fetched and mutated
-    let resource = world.get_resource_mut::<Resource>();
-    resource.0 = 1002;
+  /// Returns the only entity holding a component of type `T` alongside the
+  /// component.
+  ///
+  /// Returns [`EcsErrors::ExpectedSingleEntity`] if no entity or more than
+  /// one entity holds a `T`.
+  pub fn single<T:EcsData>(&self) -> Result<(Entity, &T)> {
+    let entity = self.single_entity::<T>()?;
+    let components = self.entities.column(&TypeInfo::of::<T>()).unwrap();
+    Ok((entity, components.get::<T>(entity)))
+  }
 
-    // Check querying works
-    for entity in entities {
-      let health = entity.get_component::<Health>().unwrap();
-      assert_eq!(health.0, 540.2)
+  /// Returns the only entity holding a component of type `T` alongside a
+  /// mutable reference to the component, marking it as changed.
+  ///
+  /// Returns [`EcsErrors::ExpectedSingleEntity`] if no entity or more than
+  /// one entity holds a `T`.
+  pub fn single_mut<T:EcsData>(&mut self) -> Result<(Entity, &mut T)> {
+    let entity = self.single_entity::<T>()?;
+    let components = self.entities.column_mut(&TypeInfo::of::<T>()).unwrap();
+    Ok((entity, components.get_mut::<T>(entity)))
+  }
+
+  /// Scans `T`'s storage for the only entity holding a `T`.
+  fn single_entity<T:EcsData>(&self) -> Result<Entity> {
+    let mut entities = self.entities.iter_column::<T>().map(|(entity, _)| entity);
+
+    match (entities.next(), entities.count()) {
+      (Some(entity), 0) => Ok(entity),
+      (first, rest) => Err(
+        EcsErrors::ExpectedSingleEntity {
+          component:TypeInfo::of::<T>().name(),
+          count:first.map_or(0, |_| rest + 1)
+        }
+        .into()
+      )
     }
+  }
 
-    let mut query = world.query();
-    let entities = query.with_component::<Health>().unwrap().run();
+  /// Returns an iterator over every live entity in the [`World`] alongside
+  /// the [`Bitmask`] of the components it holds.
+  pub fn iter_entities_with_mask(&self) -> impl Iterator<Item = (Entity, &Bitmask)> + '_ {
+    self.entities.map.iter().enumerate().filter(|(entity, _)| self.entities.is_alive(*entity))
+  }
+}
 
-    for entity in entities {
+// Hook and tracker implementation
+impl World {
+  /// Registers a hook run after a component of type `T` is added to an
+  /// entity which did not hold one.
+  ///
+  /// Hooks are queued through the [`World`]'s commands and run when it is
+  /// flushed, so they can freely mutate the [`World`]. They run for every
+  /// insert made through the [`World`], including bundle inserts and
+  /// commands, but not for inserts made through an [`EntityMut`].
+  ///
+  /// ```
+  /// # use nina::world::World;
+  /// struct RigidBody;
+  ///
+  /// let mut world = World::new();
+  /// world.add_resource(0_u32);
+  /// world.on_add::<RigidBody>(|world, _| *world.get_resource_mut::<u32>() += 1);
+  ///
+  /// world.spawn((RigidBody,)).unwrap();
+  /// world.flush();
+  /// assert_eq!(*world.get_resource::<u32>(), 1);
+  /// ```
+  pub fn on_add<T:EcsData>(&mut self, f:impl Fn(&mut World, Entity) + Send + Sync + 'static) -> &mut Self {
+    self.on_add.add(TypeInfo::of::<T>(), Arc::new(f));
+    self
+  }
+
+  /// Registers a hook run after a component of type `T` is removed from an
+  /// entity, including when the entity is deleted.
+  ///
+  /// Hooks run when the [`World`] is flushed, after the component is gone.
+  /// See [`World::on_add`].
+  pub fn on_remove<T:EcsData>(&mut self, f:impl Fn(&mut World, Entity) + Send + Sync + 'static) -> &mut Self {
+    self.on_remove.add(TypeInfo::of::<T>(), Arc::new(f));
+    self
+  }
+
+  /// Runs `f`, then queues the add hooks of the types in `tys` the entity
+  /// did not hold beforehand.
+  fn adding<R>(&mut self, entity:Entity, tys:Vec<TypeInfo>, f:impl FnOnce(&mut Entities) -> Result<R>) -> Result<R> {
+    // Reserved entities are created by their first component
+    self.entities.flush_reserved();
+    let added = self.hooked(entity, tys);
+    let result = f(&mut self.entities)?;
+    self.on_add.queue(self.commands.get_mut().unwrap(), entity, &added);
+    Ok(result)
+  }
+
+  /// Runs `f`, then records the removal of the types in `tys` the entity
+  /// held beforehand and queues their remove hooks.
+  fn removing<R>(&mut self, entity:Entity, tys:Vec<TypeInfo>, f:impl FnOnce(&mut Entities) -> Result<R>) -> Result<R> {
+    let removed = tys
+      .into_iter()
+      .filter(|ty| self.entities.has_component_erased(entity, ty).unwrap_or(false))
+      .collect::<Vec<_>>();
+    let result = f(&mut self.entities)?;
+
+    for ty in &removed {
+      self.removed.entry(*ty).or_default().push(entity);
+    }
+    self.on_remove.queue(self.commands.get_mut().unwrap(), entity, &removed);
+    Ok(result)
+  }
+
+  /// Runs `f` to delete the entity, recording every component it held as
+  /// removed and removing its tags.
+  fn despawning<R>(&mut self, entity:Entity, f:impl FnOnce(&mut Entities) -> Result<R>) -> Result<R> {
+    let tys = match self.entities.is_alive(entity) {
+      true => self.entities.component_types(entity),
+      false => Vec::new()
+    };
+    let result = self.removing(entity, tys, f)?;
+    self.tags.remove_entity(entity);
+    Ok(result)
+  }
+
+  /// Returns the types in `tys` with add hooks which the entity does not
+  /// hold.
+  fn hooked(&self, entity:Entity, tys:Vec<TypeInfo>) -> Vec<TypeInfo> {
+    if self.on_add.is_empty() {
+      return Vec::new();
+    }
+    tys
+      .into_iter()
+      .filter(|ty| self.on_add.contains(ty) && !self.entities.has_component_erased(entity, ty).unwrap_or(false))
+      .collect()
+  }
+
+  /// Returns every entity which lost its component of type `T` since
+  /// [`World::clear_trackers`] was last called, in the order they lost it.
+  ///
+  /// Removals through [`World::delete_component`],
+  /// [`World::delete_entity`], [`World::take_bundle`] and commands are
+  /// tracked. An entity is yielded once for each removal.
+  pub fn removed<T:EcsData>(&self) -> impl Iterator<Item = Entity> + '_ {
+    self.removed.get(&TypeInfo::of::<T>()).into_iter().flatten().copied()
+  }
+
+  /// Forgets every removal reported by [`World::removed`].
+  ///
+  /// [`Schedule`](schedule::Schedule) runs call this after their last stage.
+  pub fn clear_trackers(&mut self) {
+    self.removed.values_mut().for_each(Vec::clear);
+  }
+}
+
+// Query implementation
+impl World {
+  pub fn query(&self) -> Query<'_> {
+    Query::new(&self.entities)
+  }
+
+  /// Creates a [`PreparedQuery`] matching entities which hold every
+  /// component in the [`Bundle`] `B`.
+  ///
+  /// Returns an error if a component in `B` was never registered.
+  pub fn prepare_query<B:Bundle>(&self) -> Result<PreparedQuery> {
+    PreparedQuery::with::<B>(&self.entities)
+  }
+
+  /// Returns the current change detection tick.
+  pub fn tick(&self) -> Tick {
+    self.entities.tick()
+  }
+
+  /// Advances the change detection tick and returns the new tick.
+  ///
+  /// Call once per frame so `changed` and `added` query filters only see
+  /// writes made since.
+  pub fn increment_tick(&mut self) -> Tick {
+    self.entities.increment_tick()
+  }
+
+  /// Creates a [`TupleQuery`] yielding the components in `Q` for every
+  /// entity which holds them.
+  ///
+  /// `Q` is a tuple such as `(&A, &mut B, Option<&C>)`. Returns an error if a
+  /// required component was never registered or if `Q` borrows a component
+  /// mutably alongside any other borrow of the same type.
+  pub fn query_tuple<Q:Fetch>(&self) -> Result<TupleQuery<'_, Q>> {
+    TupleQuery::new(&self.entities)
+  }
+
+  /// Fetches several of the entity's components in one call.
+  ///
+  /// `Q` is a tuple such as `(&A, &mut B, Option<&C>)`. Components behind
+  /// `Option` are `None` when the entity does not hold them.
+  ///
+  /// Returns an error if the entity does not exist, does not hold a required
+  /// component, or if `Q` borrows a component mutably alongside any other
+  /// borrow of the same type.
+  pub fn get_components<Q:Fetch>(&self, entity:Entity) -> Result<Q::Item<'_>> {
+    Q::access(&mut Access::new())?;
+
+    if !self.entities.is_alive(entity) {
+      return Err(EcsErrors::EntityDoesNotExist { entity }.into());
+    }
+
+    let state = Q::prepare(&self.entities)?;
+    let mut required = Bitmask::new();
+    Q::required(&state, &mut required);
+
+    let mask = &self.entities.map[entity];
+    // Name the first required component the entity is missing
+    let missing = required.iter().find(|bit| !mask.is_set(*bit));
+    match missing {
+      None => Ok(unsafe { Q::fetch(&state, entity, mask) }),
+      Some(bit) => Err(
+        EcsErrors::ComponentDataDoesNotExist {
+          entity,
+          ty:self.entities.component_type(bit).unwrap().name()
+        }
+        .into()
+      )
+    }
+  }
+}
+
+/// Puts a resource removed by [`World::resource_scope`] back when dropped.
+struct ResourceScope<'w> {
+  world:&'w mut World,
+  ty:TypeInfo,
+  data:Option<ErasedBox>
+}
+
+impl<'w> Drop for ResourceScope<'w> {
+  fn drop(&mut self) {
+    if let Some(data) = self.data.take() {
+      self.world.resources.insert_erased(self.ty, data);
+    }
+  }
+}
+
+// Tag implementation
+impl World {
+  /// Tags the entity with `tag`. Does nothing if it is already tagged.
+  ///
+  /// Deleting the entity removes it from every tag. Returns an error if the
+  /// entity is not alive.
+  pub fn tag(&mut self, entity:Entity, tag:u64) -> Result<()> {
+    if !self.entities.is_alive(entity) {
+      return Err(EcsErrors::EntityDoesNotExist { entity }.into());
+    }
+    self.tags.insert(entity, tag);
+    Ok(())
+  }
+
+  /// Removes `tag` from the entity. Returns `false` if the entity was not
+  /// tagged with it.
+  pub fn untag(&mut self, entity:Entity, tag:u64) -> bool {
+    self.tags.remove(entity, tag)
+  }
+
+  /// Returns the entities tagged with `tag` in the order they were tagged.
+  pub fn tagged(&self, tag:u64) -> &[Entity] {
+    self.tags.get(tag)
+  }
+}
+
+// Prefab implementation
+impl World {
+  /// Registers `bundle` as the prefab `name`, replacing any prefab of the
+  /// same name.
+  ///
+  /// Every component type in the bundle must have been registered with
+  /// [`World::register_component_cloneable`] so the prefab can be copied
+  /// through the clone shims. Returns an error naming the first which was
+  /// not, in which case the bundle is dropped.
+  pub fn register_prefab<B:Bundle>(&mut self, name:&str, bundle:B) -> Result<()> {
+    let tys = B::types()
+      .into_iter()
+      .map(|ty| self.entities.column(&ty).map_or(ty, |components| components.ty()))
+      .collect::<Vec<_>>();
+    if let Some(ty) = tys.iter().find(|ty| !ty.is_cloneable()) {
+      return Err(ErasedVecErrors::NotCloneable(ty.name()).into());
+    }
+
+    // The components are handed over in the order of `B::types`
+    let mut components = Vec::with_capacity(tys.len());
+    let mut tys = tys.into_iter();
+    unsafe {
+      bundle.put(|ptr, _| {
+        components.push(ErasedBox::from_raw_parts(tys.next().unwrap(), ptr));
+        Ok(())
+      })?
+    };
+    self.register_prefab_erased(name, Prefab::from_components(components)?);
+    Ok(())
+  }
+
+  /// Registers `prefab` as the prefab `name`, replacing any prefab of the
+  /// same name.
+  pub fn register_prefab_erased(&mut self, name:&str, prefab:Prefab) {
+    self.prefabs.insert(name.to_string(), prefab);
+  }
+
+  /// Creates an entity holding clones of the components of the prefab
+  /// `name`.
+  ///
+  /// Component types which are not registered yet are registered. Returns an
+  /// error if no prefab named `name` was registered.
+  pub fn instantiate(&mut self, name:&str) -> Result<Entity> {
+    let prefab = self
+      .prefabs
+      .get(name)
+      .ok_or_else(|| EcsErrors::PrefabDoesNotExist { name:name.to_string() })?;
+    let components = prefab.to_bundle();
+    self.spawn(components)
+  }
+}
+
+impl Default for World {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl Debug for World {
+  /// Summarizes the world with counts. The alternate form lists every
+  /// registered component by type name with its column length, and every
+  /// resource by type name.
+  fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result {
+    let alternate = f.alternate();
+    let mut debug = f.debug_struct("World");
+    debug.field("entities", &self.iter_entities().count());
+    if alternate {
+      let components = self
+        .entities
+        .components
+        .iter()
+        .map(|components| (components.ty().type_name(), components.len()))
+        .collect::<Vec<_>>();
+      let mut resources = self.resources.types().map(|ty| ty.type_name()).collect::<Vec<_>>();
+      resources.sort_unstable();
+      debug.field("components", &DebugMap(components)).field("resources", &resources);
+    } else {
+      debug
+        .field("components", &self.entities.components.len())
+        .field("resources", &self.resources.types().count());
+    }
+    debug.finish()
+  }
+}
+
+// Snapshot implementation
+impl World {
+  /// Deep copies every entity along with its cloneable components and every
+  /// cloneable resource.
+  ///
+  /// Components and resources which were not registered as cloneable are left
+  /// out of the snapshot.
+  pub fn snapshot(&self) -> WorldSnapshot {
+    WorldSnapshot {
+      entities:self.entities.snapshot(),
+      resources:self.resources.snapshot(),
+      names:self
+        .entities
+        .components
+        .iter()
+        .map(|components| self.component_name(&components.ty()))
+        .collect()
+    }
+  }
+
+  /// Returns the name `ty` was registered under for serialization, or its
+  /// type name.
+  fn component_name(&self, ty:&TypeInfo) -> Cow<'static, str> {
+    #[cfg(feature = "serde")]
+    if let Some(name) = self.serde.name(ty) {
+      return name.clone();
+    }
+    Cow::Borrowed(ty.type_name())
+  }
+
+  /// Returns the registered component type named `name` by
+  /// [`World::component_name`].
+  fn component_named(&self, name:&str) -> Option<TypeInfo> {
+    #[cfg(feature = "serde")]
+    if let Some(ty) = self.serde.type_named(name) {
+      // The column's type carries the flags it was registered with
+      return self.entities.column(&ty).map(|components| components.ty());
+    }
+    self
+      .entities
+      .components
+      .iter()
+      .map(|components| components.ty())
+      .find(|ty| ty.type_name() == name)
+  }
+
+  /// Restores the [`World`] to the state captured in `snapshot`.
+  ///
+  /// The current entities and their components are dropped and replaced,
+  /// and every tag is removed. Resources held in the snapshot are replaced,
+  /// other resources are left untouched.
+  pub fn restore(&mut self, snapshot:&WorldSnapshot) {
+    let mut entities = snapshot.entities.snapshot();
+
+    // Keep component types registered since the snapshot was taken
+    // Ids are assigned in registration order so the restored ids are kept
+    for components in &self.entities.components {
+      entities.register_component_erased(components.ty());
+    }
+
+    self.entities = entities;
+    // Tags are not captured by snapshots and belonged to the dropped entities
+    self.tags.clear();
+    self.resources.restore(&snapshot.resources);
+  }
+
+  /// Applies a [`SnapshotDiff`] created by [`WorldSnapshot::diff`].
+  ///
+  /// Applying the diff between snapshots `a` and `b` to a [`World`] restored
+  /// from `a` leaves its entities and copy components equal to `b`'s. Changes
+  /// go through the usual insertion and deletion paths so hooks and removal
+  /// trackers see them.
+  ///
+  /// Components are matched by name, so the diff can come from another
+  /// [`World`] which registered the same names. Errors if a changed
+  /// component's type is not registered as a copy component of the diffed
+  /// size.
+  pub fn apply_diff(&mut self, diff:&SnapshotDiff) -> Result<()> {
+    for &entity in diff.despawned() {
+      self.delete_entity(entity)?;
+    }
+    for &entity in diff.spawned() {
+      self.entities.revive(entity);
+    }
+
+    for component in diff.changes() {
+      let ty = self.component_named(&component.name).ok_or_else(|| EcsErrors::ComponentNotRegistered {
+        component:component.name.to_string()
+      })?;
+      match &component.change {
+        ComponentChange::Added(bytes) | ComponentChange::Changed(bytes) => {
+          if !ty.is_copy() || ty.size() != bytes.len() {
+            return Err(
+              EcsErrors::InvalidComponentDiff {
+                name:component.name.to_string()
+              }
+              .into()
+            );
+          }
+          // The component is Copy so its bytes can be moved in directly
+          let mut bytes = bytes.clone();
+          self.add_component_erased(component.entity, ty, bytes.as_mut_ptr())?
+        }
+        ComponentChange::Removed => self.delete_component_erased(component.entity, ty)?
+      }
+    }
+    Ok(())
+  }
+}
+
+// CommandBuffer implementation
+impl World {
+  /// Returns a [`Commands`] handle for queuing changes to the [`World`]
+  /// through a shared borrow.
+  ///
+  /// The queued commands are applied by [`World::flush`].
+  pub fn commands(&self) -> Commands<'_> {
+    Commands::new(self)
+  }
+
+  /// Same as [`World::commands`].
+  pub fn command_buffer(&self) -> Commands<'_> {
+    self.commands()
+  }
+
+  /// Applies every command queued through [`World::commands`] in the order
+  /// they were queued.
+  ///
+  /// Commands queued while flushing, for example by a closure command, are
+  /// applied before `flush` returns. Entities reserved with
+  /// [`World::reserve_entity`] are created first.
+  pub fn flush(&mut self) {
+    self.entities.flush_reserved();
+    loop {
+      let mut buffer = mem::take(self.commands.get_mut().unwrap());
+      if buffer.is_empty() {
+        return;
+      }
+      buffer.run(self);
+
+      #[cfg(all(feature = "debug-validate", debug_assertions))]
+      if let Err(errors) = self.validate() {
+        panic!("The world's storage is inconsistent after a flush: {errors:?}");
+      }
+    }
+  }
+}
+
+// System implementation
+impl World {
+  /// Runs a system once without adding it to a
+  /// [`Schedule`](schedule::Schedule) and returns its result.
+  ///
+  /// Accepts any [`System`](system::System) and any function whose
+  /// parameters are all [`SystemParam`](system_param::SystemParam)s. The
+  /// commands the system queues are flushed before returning.
+  ///
+  /// ```
+  /// # use nina::world::{query::tuple_query::TupleQuery, World};
+  /// struct Health(u32);
+  ///
+  /// let mut world = World::new();
+  /// world.spawn((Health(3),)).unwrap();
+  ///
+  /// let total = world.run_system(|query:TupleQuery<(&Health,)>| query.iter().map(|(_, (health,))| health.0).sum::<u32>());
+  /// assert_eq!(total, 3);
+  /// ```
+  ///
+  /// # Panics
+  /// - Panics if the system's parameters conflict or cannot be fetched.
+  pub fn run_system<Marker, S:RunSystem<Marker>>(&mut self, system:S) -> S::Out {
+    let out = system.run_once(self);
+    self.flush();
+    out
+  }
+}
+
+type Entities = EntitiesInner;
+
+#[cfg(test)]
+mod tests {
+  use super::World;
+  use crate::{
+    errors::{EcsErrors, IntegrityError, NinaError},
+    storage::{Bitmask, TypeInfo}
+  };
+  use std::{
+    alloc::Layout,
+    mem,
+    sync::{
+      atomic::{AtomicU32, AtomicUsize, Ordering},
+      Arc
+    }
+  };
+
+  #[test]
+  fn systems_work() {
+    let mut world = World::new();
+    world.register_component::<Health>().register_component::<Armor>();
+    world.add_resource(Resource(100));
+
+    world.create_entity().with_components((Health(100.2), Armor(44))).unwrap();
+    world.create_entity().with_component(Health(540.2)).unwrap();
+
+    let p1_health = world.get_component::<Health>(0).unwrap();
+    assert_eq!(p1_health.0, 100.2);
+
+    let p1_health = world.get_component_mut::<Health>(1).unwrap();
+    p1_health.0 = 100.0;
+    assert_eq!(p1_health.0, 100.0);
+
+    // some_system(&world);
+  }
+
+  #[test]
+  fn init_resource_keeps_modified_state() {
+    let mut world = World::new();
+    world.init_resource::<Score>();
+    world.get_resource_mut::<Score>().0 = 10;
+
+    world.init_resource::<Score>();
+    assert_eq!(world.get_resource::<Score>().0, 10);
+
+    world.get_resource_or_insert_with(|| Score(99)).0 += 1;
+    assert_eq!(world.get_resource::<Score>().0, 11);
+    assert_eq!(world.get_resource_or_insert_with(|| Resource(3)).0, 3);
+  }
+
+  #[test]
+  fn remove_resource_returns_the_value() {
+    let mut world = World::new();
+    world.add_resource(String::from("window"));
+
+    let window = world.remove_resource::<String>().unwrap();
+    assert_eq!(window, "window");
+    assert!(!world.contains_resource::<String>());
+    assert!(world.remove_resource::<String>().is_none());
+  }
+
+  #[test]
+  fn resource_scope_lends_the_resource_and_the_world() {
+    let mut world = World::new();
+    world.add_resource(Score(2));
+
+    let spawned = world.resource_scope(|world, score:&mut Score| {
+      assert!(!world.contains_resource::<Score>());
+      score.0 += 1;
+      world.reserve_entity()
+    });
+    assert_eq!(spawned, 0);
+    assert_eq!(world.get_resource::<Score>().0, 3);
+
+    // The resource is put back when the closure panics
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+      world.resource_scope(|_, score:&mut Score| {
+        score.0 = 10;
+        panic!("closure failed")
+      })
+    }));
+    assert!(result.is_err());
+    assert_eq!(world.get_resource::<Score>().0, 10);
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+      world.resource_scope(|world, _:&mut Score| {
+        world.add_resource(Score(0));
+      })
+    }));
+    assert!(result.is_err());
+    assert_eq!(world.get_resource::<Score>().0, 10);
+  }
+
+  #[test]
+  #[should_panic(expected = "which does not exist")]
+  fn resource_scope_panics_on_missing_resources() {
+    let mut world = World::new();
+    world.resource_scope(|_, _:&mut Score| {});
+  }
+
+  #[test]
+  fn validate_detects_masks_without_storage() {
+    let mut world = World::new();
+    world.register_component::<Armor>();
+    world.create_entity().with_component(Health(1.0)).unwrap();
+    world.create_entity().with_component(Armor(1)).unwrap();
+    world.delete_entity(1).unwrap();
+    assert!(world.validate().is_ok());
+
+    // Set a bit for a component the entity's slot does not hold
+    let mask = world.entities.get_bitmask(&TypeInfo::of::<Armor>()).unwrap().clone();
+    world.entities.map[0].insert(&mask);
+    assert_eq!(
+      world.validate(),
+      Err(vec![IntegrityError::MissingComponent {
+        entity:0,
+        ty:TypeInfo::of::<Armor>().name()
+      }])
+    );
+    assert!(world.get_component::<Armor>(0).is_err());
+    assert!(world.get_component_mut::<Armor>(0).is_err());
+  }
+
+  #[test]
+  fn validate_reports_every_corrupted_column() {
+    let mut world = World::new();
+    world.create_entity().with_component(Health(1.0)).unwrap();
+    world.create_entity().with_component(Armor(1)).unwrap();
+
+    // Fill a slot behind the bitmask's back and grow a column past the world
+    let armor = world.entities.column_mut(&TypeInfo::of::<Armor>()).unwrap();
+    let mut value = Armor(2);
+    armor.set_erased(0, TypeInfo::of::<Armor>(), (&mut value as *mut Armor).cast());
+    world.entities.column_mut(&TypeInfo::of::<Health>()).unwrap().pad();
+
+    let health = TypeInfo::of::<Health>().name();
+    let armor = TypeInfo::of::<Armor>().name();
+    assert_eq!(
+      world.validate(),
+      Err(vec![
+        IntegrityError::ColumnLength {
+          ty:health,
+          len:3,
+          entities:2
+        },
+        IntegrityError::UntrackedComponent { entity:0, ty:armor }
+      ])
+    );
+  }
+
+  #[test]
+  #[cfg(all(feature = "debug-validate", debug_assertions))]
+  #[should_panic(expected = "inconsistent after a flush")]
+  fn debug_validate_checks_flushes() {
+    let mut world = World::new();
+    world.create_entity().with_component(Armor(1)).unwrap();
+    world.commands().add(|world| {
+      let mask = world.entities.get_bitmask(&TypeInfo::of::<Armor>()).unwrap().clone();
+      world.entities.map[0].remove(&mask);
+    });
+    world.flush();
+  }
+
+  #[test]
+  fn iter_components_skips_deleted_entities() {
+    let mut world = World::new();
+    for health in 0..4 {
+      world.create_entity().with_component(Health(health as f32)).unwrap();
+    }
+    world.create_entity().with_component(Armor(1)).unwrap();
+    world.delete_entity(1).unwrap();
+    world.delete_component::<Health>(3).unwrap();
+
+    let healths = world
+      .iter_components::<Health>()
+      .map(|(entity, health)| (entity, health.0))
+      .collect::<Vec<_>>();
+    assert_eq!(healths, vec![(0, 0.0), (2, 2.0)]);
+    assert_eq!(world.iter_components::<String>().count(), 0);
+  }
+
+  #[test]
+  fn iter_components_mut_writes_are_visible() {
+    let mut world = World::new();
+    for health in 0..5 {
+      world.create_entity().with_component(Health(health as f32)).unwrap();
+    }
+    world.delete_entity(1).unwrap();
+    world.delete_entity(3).unwrap();
+    world.create_entity().with_component(Armor(1)).unwrap();
+
+    world.increment_tick();
+    for (entity, health) in world.iter_components_mut::<Health>() {
+      health.0 += entity as f32 * 10.0;
+    }
+
+    assert_eq!(world.get_component::<Health>(2).unwrap().0, 22.0);
+    assert_eq!(world.get_component::<Health>(4).unwrap().0, 44.0);
+    assert!(world.get_component::<Health>(1).is_err());
+    let mut query = world.query();
+    assert_eq!(query.changed::<Health>().unwrap().count(), 3);
+    assert_eq!(world.iter_components_mut::<String>().count(), 0);
+  }
+
+  #[test]
+  fn single_requires_exactly_one_match() {
+    let mut world = World::new();
+    world.register_component::<Health>();
+    assert!(matches!(
+      world.single::<Health>(),
+      Err(NinaError::Ecs(EcsErrors::ExpectedSingleEntity { count:0, .. }))
+    ));
+    assert!(matches!(
+      world.single::<String>(),
+      Err(NinaError::Ecs(EcsErrors::ExpectedSingleEntity { count:0, .. }))
+    ));
+
+    world.create_entity().with_component(Armor(1)).unwrap();
+    world.create_entity().with_component(Health(10.0)).unwrap();
+    let player = 1;
+    let (entity, health) = world.single::<Health>().unwrap();
+    assert_eq!((entity, health.0), (player, 10.0));
+
+    world.increment_tick();
+    world.single_mut::<Health>().unwrap().1 .0 = 5.0;
+    assert_eq!(world.get_component::<Health>(player).unwrap().0, 5.0);
+    let mut query = world.query();
+    assert_eq!(query.changed::<Health>().unwrap().count(), 1);
+
+    world.create_entity().with_component(Health(20.0)).unwrap();
+    assert!(matches!(
+      world.single_mut::<Health>(),
+      Err(NinaError::Ecs(EcsErrors::ExpectedSingleEntity { count:2, .. }))
+    ));
+    world.delete_entity(player).unwrap();
+    assert_eq!(world.single::<Health>().unwrap().1 .0, 20.0);
+  }
+
+  #[test]
+  fn markers_are_tracked_by_bitmasks() {
+    struct Player;
+    let mut world = World::new();
+    world.register_component::<Player>();
+    world.create_entity().with_component(Armor(1)).unwrap();
+    world.create_entity().with_components((Player, Armor(2))).unwrap();
+    world.create_entity().with_component(Health(1.0)).unwrap();
+
+    assert!(!world.entities.column(&TypeInfo::of::<Player>()).unwrap().tracks_filled());
+    assert_eq!(world.iter_components::<Player>().map(|(entity, _)| entity).collect::<Vec<_>>(), vec![1]);
+    assert_eq!(world.single::<Player>().unwrap().0, 1);
+    assert!(world.get_component::<Player>(0).is_err());
+    assert!(world.get_component::<Player>(1).is_ok());
+    let mut query = world.query();
+    assert_eq!(query.with_component::<Player>().unwrap().run().len(), 1);
+    assert!(world.validate().is_ok());
+
+    world.delete_entity(1).unwrap();
+    assert_eq!(world.iter_components_mut::<Player>().count(), 0);
+  }
+
+  #[test]
+  fn dense_components_resolve_after_compaction() {
+    let mut world = World::new();
+    world.register_component_dense::<Name>().register_component_cloneable::<Name>();
+    for index in 0..8 {
+      world.spawn((Name(index.to_string()), Armor(index))).unwrap();
+    }
+    world.delete_entity(1).unwrap();
+    world.delete_component::<Name>(4).unwrap();
+    world.delete_entity(6).unwrap();
+    assert!(world.dense_components::<Name>().is_err());
+
+    world.compact();
+    let (entities, names) = world.dense_components::<Name>().unwrap();
+    assert_eq!(names.len(), 5);
+    for (entity, name) in entities.iter().zip(names) {
+      assert_eq!(name.0, entity.to_string());
+    }
+
+    for entity in [0, 2, 3, 5, 7] {
+      assert_eq!(world.get_component::<Name>(entity).unwrap().0, entity.to_string());
+    }
+    assert!(world.get_component::<Name>(4).is_err());
+    let mut query = world.query();
+    let entities = query.with_component::<Name>().unwrap().run();
+    assert_eq!(entities.iter().map(|entity| entity.id).collect::<Vec<_>>(), vec![0, 2, 3, 5, 7]);
+    for entity in entities {
+      assert_eq!(entity.get_component::<Name>().unwrap().0, entity.id.to_string());
+    }
+    assert_eq!(world.iter_components::<Name>().count(), 5);
+
+    // Entities reusing freed slots get a new row
+    let entity = world.spawn((Name("new".to_string()),)).unwrap();
+    assert_eq!(entity, 1);
+    let clone = world.clone_entity(entity).unwrap();
+    world.get_component_mut::<Name>(entity).unwrap().0.push('!');
+    assert_eq!(world.get_component::<Name>(entity).unwrap().0, "new!");
+    assert_eq!(world.get_component::<Name>(clone).unwrap().0, "new");
+    assert!(world.validate().is_ok());
+    assert!(matches!(
+      world.dense_components::<Armor>(),
+      Err(NinaError::Ecs(EcsErrors::NotDense { .. }))
+    ));
+  }
+
+  #[test]
+  fn dense_markers_are_compacted() {
+    struct Marker;
+
+    let mut world = World::new();
+    world.register_component_dense::<Marker>();
+    for _ in 0..3 {
+      world.spawn((Marker,)).unwrap();
+    }
+    world.delete_component::<Marker>(0).unwrap();
+    world.delete_entity(1).unwrap();
+
+    world.compact();
+    let (entities, markers) = world.dense_components::<Marker>().unwrap();
+    assert_eq!((entities, markers.len()), (&[2][..], 1));
+    assert!(world.validate().is_ok());
+  }
+
+  #[test]
+  fn queries_read_sparse_and_table_components() {
+    let mut world = World::new();
+    world.register_component_sparse::<Name>();
+    for index in 0..6 {
+      world.spawn((Armor(index),)).unwrap();
+    }
+    for entity in [5, 1, 3] {
+      world.add_component(entity, Name(entity.to_string())).unwrap();
+    }
+    world.delete_component::<Name>(1).unwrap();
+
+    let mut query = world.query();
+    let entities = query.with_component::<Name>().unwrap().with_component::<Armor>().unwrap().run();
+    assert_eq!(entities.iter().map(|entity| entity.id).collect::<Vec<_>>(), vec![3, 5]);
+    for entity in &entities {
+      assert_eq!(entity.get_component::<Name>().unwrap().0, entity.id.to_string());
+      entity.get_component_mut::<Name>().unwrap().0.push('!');
+    }
+    let (entities, names) = world.dense_components::<Name>().unwrap();
+    assert_eq!(entities, &[5, 3]);
+    assert_eq!(names.iter().map(|name| name.0.as_str()).collect::<Vec<_>>(), vec!["5!", "3!"]);
+  }
+
+  #[test]
+  fn sparse_components_survive_churn() {
+    let mut world = World::new();
+    world.register_component_sparse::<Name>();
+    let mut expected = vec![None; 64];
+    for index in 0..64 {
+      world.spawn((Armor(index),)).unwrap();
+    }
+
+    // A linear congruential generator keeps the churn reproducible
+    let mut seed = 7_u64;
+    for frame in 0..2000 {
+      seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+      let entity = (seed >> 33) as usize % 64;
+      if expected[entity].is_some() && seed.is_multiple_of(3) {
+        world.delete_component::<Name>(entity).unwrap();
+        expected[entity] = None;
+      } else {
+        world.add_component(entity, Name(frame.to_string())).unwrap();
+        expected[entity] = Some(frame.to_string());
+      }
+    }
+
+    for (entity, name) in expected.iter().enumerate() {
+      assert_eq!(world.get_component::<Name>(entity).ok().map(|name| name.0.clone()), name.clone());
+    }
+    let (_, names) = world.dense_components::<Name>().unwrap();
+    assert_eq!(names.len(), expected.iter().flatten().count());
+    assert!(world.validate().is_ok());
+  }
+
+  #[test]
+  fn spawn_iter_requires_registered_types() {
+    let mut world = World::new();
+    world.register_component::<Armor>();
+    assert!(matches!(
+      world.spawn_iter(vec![(Armor(1), Name("a".to_string()))]),
+      Err(NinaError::Ecs(EcsErrors::ComponentNotRegistered { .. }))
+    ));
+    assert_eq!(world.iter_entities().count(), 0);
+
+    world.register_component::<Name>();
+    let names = ["a", "b", "c"].map(|name| (Armor(1), Name(name.to_string())));
+    let entities = world.spawn_iter(names).unwrap();
+    assert_eq!(entities, vec![0, 1, 2]);
+    assert_eq!(world.get_component::<Name>(2).unwrap().0, "c");
+    assert!(world.entities.column(&TypeInfo::of::<Armor>()).unwrap().data().capacity() >= 3);
+    assert!(world.spawn_iter(Vec::<(Armor,)>::new()).unwrap().is_empty());
+  }
+
+  #[test]
+  fn debug_summarizes_the_world() {
+    let mut world = World::new();
+    world.spawn((Health(1.0), Armor(2))).unwrap();
+    world.spawn((Armor(3),)).unwrap();
+    world.add_resource(Resource(1));
+    world.delete_entity(0).unwrap();
+
+    assert_eq!(format!("{world:?}"), "World { entities: 1, components: 2, resources: 1 }");
+
+    let pretty = format!("{world:#?}");
+    let health = std::any::type_name::<Health>();
+    let armor = std::any::type_name::<Armor>();
+    let resource = std::any::type_name::<Resource>();
+    assert!(pretty.contains(&format!("\"{health}\": 2,")));
+    assert!(pretty.contains(&format!("\"{armor}\": 2,")));
+    assert!(pretty.contains(&format!("resources: [\n        \"{resource}\",")));
+  }
+
+  #[test]
+  fn clone_entity_deep_copies_components() {
+    let mut world = World::new();
+    world.register_component_cloneable::<Name>().register_component_cloneable::<Vec<u32>>();
+    world.create_entity().with_components((Name("a".to_string()), vec![1_u32, 2])).unwrap();
+    world.create_entity().with_component(Armor(1)).unwrap();
+
+    let clone = world.clone_entity(0).unwrap();
+    assert_eq!(clone, 2);
+    world.get_component_mut::<Vec<u32>>(clone).unwrap().push(3);
+    world.get_component_mut::<Name>(clone).unwrap().0.push('b');
+    assert_eq!(*world.get_component::<Vec<u32>>(0).unwrap(), vec![1, 2]);
+    assert_eq!(world.get_component::<Name>(0).unwrap().0, "a");
+    assert_eq!(*world.get_component::<Vec<u32>>(clone).unwrap(), vec![1, 2, 3]);
+    assert!(world.get_component::<Armor>(clone).is_err());
+
+    // Entities with components which cannot be cloned are left alone
+    assert!(world.clone_entity(1).is_err());
+    assert_eq!(world.iter_entities().count(), 3);
+  }
+
+  #[test]
+  fn clone_entity_into_overwrites_the_destination() {
+    let mut world = World::new();
+    world.register_component_cloneable::<Name>().register_component_cloneable::<Vec<u32>>();
+    let template = world.spawn((Name("bullet".to_string()), vec![1_u32])).unwrap();
+    let drops = Arc::new(AtomicU32::new(0));
+    let target = world.spawn((Name("old".to_string()), Counted(drops.clone()))).unwrap();
+
+    world.clone_entity_into(template, target).unwrap();
+    assert_eq!(world.get_component::<Name>(target).unwrap().0, "bullet");
+    assert_eq!(*world.get_component::<Vec<u32>>(target).unwrap(), vec![1]);
+    assert!(world.get_component::<Counted>(target).is_err());
+    assert_eq!(drops.load(Ordering::Relaxed), 1);
+    assert_eq!(world.removed::<Counted>().collect::<Vec<_>>(), vec![target]);
+    world.validate().unwrap();
+
+    // A source holding a component without a clone shim leaves `dst` alone
+    let opaque = world.spawn((Name("opaque".to_string()), Armor(1))).unwrap();
+    let error = world.clone_entity_into(opaque, target).unwrap_err();
+    assert!(error.to_string().contains("Armor"));
+    assert_eq!(world.get_component::<Name>(target).unwrap().0, "bullet");
+    assert!(world.clone_entity_into(template, 100).is_err());
+  }
+
+  #[test]
+  fn debug_entity_lists_components() {
+    let mut world = World::new();
+    world.register_component_debug::<Health>().register_component_debug::<Vec<u32>>();
+    world.create_entity().with_components((Health(1.0), vec![1_u32], Armor(2))).unwrap();
+
+    let debug = world.debug_entity(0);
+    assert_eq!(
+      debug,
+      "QueryEntity { id: 0, components: {\"alloc::vec::Vec<u32>\": [1], \"nina::world::tests::Armor\": nina::world::tests::Armor(opaque), \"nina::world::tests::Health\": Health(1.0)} }"
+    );
+  }
+
+  #[test]
+  fn access_components_by_id() {
+    let mut world = World::new();
+    world.register_component::<Health>();
+    world.create_entity().with_components((Health(1.0), Armor(2))).unwrap();
+    world.create_entity().with_component(Health(3.0)).unwrap();
+
+    let health = world.component_id::<Health>().unwrap();
+    let armor = world.component_id::<Armor>().unwrap();
+    assert_eq!((health.index(), armor.index()), (0, 1));
+    assert!(world.component_id::<Name>().is_none());
+
+    world.get_component_mut_by_id::<Health>(1, health).unwrap().0 += 1.0;
+    assert_eq!(world.get_component_by_id::<Health>(1, health).unwrap().0, 4.0);
+    assert!(world.get_component_by_id::<Armor>(1, armor).is_err());
+
+    let ids = world
+      .query()
+      .with_id(health)
+      .without_id(armor)
+      .run()
+      .iter()
+      .map(|entity| entity.id)
+      .collect::<Vec<_>>();
+    assert_eq!(ids, vec![1]);
+  }
+
+  #[test]
+  fn store_runtime_defined_components() {
+    static DROPS:AtomicUsize = AtomicUsize::new(0);
+    unsafe fn drop_blob(_:*mut u8) {
+      DROPS.fetch_add(1, Ordering::Relaxed);
+    }
+
+    let layout = Layout::new::<[u32; 4]>();
+    let blob = TypeInfo::from_raw_parts(7, layout, drop_blob, "Blob");
+    let other = TypeInfo::from_raw_parts(8, layout, drop_blob, "Other");
+
+    let mut world = World::new();
+    let blob_id = world.register_component_raw(blob);
+    let other_id = world.register_component_raw(other);
+    assert_ne!(blob_id, other_id);
+
+    for entity in 0..3_u32 {
+      world.create_entity();
+      let mut bytes = [entity; 4];
+      world.add_component_erased(entity as usize, blob, bytes.as_mut_ptr().cast()).unwrap();
+    }
+    let mut bytes = [9_u32; 4];
+    world.add_component_erased(1, other, bytes.as_mut_ptr().cast()).unwrap();
+
+    let matched = world.query().with_id(blob_id).without_type(other).unwrap().run();
+    let values = matched
+      .iter()
+      .map(|entity| {
+        let (ptr, ty) = entity.get_component_ptr(blob).unwrap();
+        assert_eq!(ty.name(), "Blob");
+        unsafe { *ptr.cast::<[u32; 4]>() }
+      })
+      .collect::<Vec<_>>();
+    assert_eq!(values, vec![[0; 4], [2; 4]]);
+
+    world.delete_entity(1).unwrap();
+    assert_eq!(DROPS.load(Ordering::Relaxed), 2);
+  }
+
+  #[test]
+  fn try_variants_do_not_panic() {
+    let mut world = World::new();
+    let entity = world.spawn((Armor(1),)).unwrap();
+
+    assert_eq!(world.try_get_component::<Armor>(entity).unwrap().0, 1);
+    world.try_get_component_mut::<Armor>(entity).unwrap().0 = 2;
+    assert_eq!(world.get_component::<Armor>(entity).unwrap().0, 2);
+
+    assert!(matches!(
+      world.try_get_component::<Armor>(10),
+      Err(NinaError::Ecs(EcsErrors::EntityDoesNotExist { .. }))
+    ));
+    assert!(world.try_get_component_mut::<Armor>(10).is_err());
+    assert!(world.try_add_component(10, Armor(3)).is_err());
+    assert!(world.entities.has_component::<Armor>(10).is_err());
+    assert!(world.try_get_resource::<Score>().is_err());
+    assert!(world.try_get_resource_mut::<Score>().is_err());
+  }
+
+  #[test]
+  #[should_panic(expected = "Attempted to reference entity \"0\" which does not exist")]
+  fn get_component_panics_on_an_entity_which_was_never_created() {
+    let world = World::new();
+    let _ = world.get_component::<Armor>(0);
+  }
+
+  #[test]
+  fn errors_name_the_entity_and_component() {
+    let mut world = World::new();
+    let entity = world.spawn((Armor(1),)).unwrap();
+
+    let error = world.get_component::<Score>(entity).err().unwrap();
+    assert!(error.to_string().contains("Attempted to use the unregistered component"));
+    assert!(error.to_string().contains("Score"));
+
+    world.register_component::<Score>();
+    let error = world.get_component::<Score>(entity).err().unwrap();
+    assert!(error
+      .to_string()
+      .starts_with("Attempted to use component data that does not exist. Entity \"0\""));
+    assert!(error.to_string().contains("Score"));
+
+    world.delete_entity(entity).unwrap();
+    let error = world.entity(entity).err().unwrap();
+    assert_eq!(error.to_string(), "Attempted to reference entity \"0\" which does not exist");
+    let error = world.try_get_component::<Armor>(7).err().unwrap();
+    assert_eq!(error.to_string(), "Attempted to reference entity \"7\" which does not exist");
+  }
+
+  #[test]
+  fn check_an_entity_has_a_bundle() {
+    let mut world = World::new();
+    let armored = world.spawn((Armor(1), Health(10.0))).unwrap();
+    let unarmored = world.spawn((Health(5.0),)).unwrap();
+
+    assert!(world.has_components::<(Armor, Health)>(armored).unwrap());
+    assert!(!world.has_components::<(Armor, Health)>(unarmored).unwrap());
+    assert!(world.has_components::<()>(unarmored).unwrap());
+
+    let query = world.prepare_query::<(Armor,)>().unwrap();
+    assert!(world.has_components_by_mask(armored, query.mask()).unwrap());
+    assert!(!world.has_components_by_mask(unarmored, query.mask()).unwrap());
+  }
+
+  #[test]
+  fn has_components_errors_on_unregistered_types_and_dead_entities() {
+    let mut world = World::new();
+    let entity = world.spawn((Armor(1),)).unwrap();
+    assert!(matches!(
+      world.has_components::<(Armor, Score)>(entity),
+      Err(NinaError::Ecs(EcsErrors::ComponentNotRegistered { .. }))
+    ));
+
+    world.delete_entity(entity).unwrap();
+    assert!(matches!(
+      world.has_components::<(Armor,)>(entity),
+      Err(NinaError::Ecs(EcsErrors::EntityDoesNotExist { .. }))
+    ));
+    assert!(world.has_components::<(Armor,)>(entity + 1).is_err());
+  }
+
+  #[test]
+  fn take_bundle_moves_components_out() {
+    let mut world = World::new();
+    let entity = world.spawn((Name("sword".to_string()), vec![1_u32, 2, 3], Armor(4))).unwrap();
+
+    let (name, stats) = world.take_bundle::<(Name, Vec<u32>)>(entity).unwrap();
+    assert_eq!(name.0, "sword");
+    assert_eq!(stats, vec![1, 2, 3]);
+
+    assert!(!world.entities.has_component::<Name>(entity).unwrap());
+    assert!(!world.entities.has_component::<Vec<u32>>(entity).unwrap());
+    assert_eq!(world.get_component::<Armor>(entity).unwrap().0, 4);
+    world.validate().unwrap();
+  }
+
+  #[test]
+  fn taken_dense_components_are_compacted() {
+    let mut world = World::new();
+    world.register_component_dense::<Name>();
+    for index in 0..3 {
+      world.spawn((Name(index.to_string()), Armor(index))).unwrap();
+    }
+
+    let (name,) = world.take_bundle::<(Name,)>(1).unwrap();
+    assert_eq!(name.0, "1");
+    world.compact();
+
+    let (entities, names) = world.dense_components::<Name>().unwrap();
+    assert_eq!(entities.len(), 2);
+    for (entity, name) in entities.iter().zip(names) {
+      assert_eq!(name.0, entity.to_string());
+    }
+    assert!(world.get_component::<Name>(1).is_err());
+    world.validate().unwrap();
+  }
+
+  #[test]
+  fn take_bundle_with_a_missing_component_removes_nothing() {
+    let mut world = World::new();
+    world.register_component::<Armor>();
+    let entity = world.spawn((Name("shield".to_string()), vec![1_u32])).unwrap();
+
+    assert!(world.take_bundle::<(Name, Armor)>(entity).is_err());
+    assert_eq!(world.get_component::<Name>(entity).unwrap().0, "shield");
+
+    world.delete_entity(entity).unwrap();
+    assert!(world.take_bundle::<(Name,)>(entity).is_err());
+  }
+
+  #[test]
+  fn get_resources_fetches_a_tuple() {
+    let mut world = World::new();
+    world.add_resource(Score(1)).add_resource(Resource(5));
+
+    {
+      let (resource, mut score, missing) = world.get_resources::<(&Resource, &mut Score, Option<&String>)>().unwrap();
+      score.0 += resource.0;
+      assert!(missing.is_none());
+
+      // The guards are still held
+      assert!(world.try_get_resource::<Score>().is_err());
+    }
+    assert_eq!(world.get_resource::<Score>().0, 6);
+
+    let error = world.get_resources::<(&mut Score, &mut Score)>().err().unwrap();
+    assert!(error.to_string().starts_with("Conflicting borrows of"));
+    assert!(world.get_resources::<(&Score, &mut Score)>().is_err());
+    assert!(world.get_resources::<(&Score, &String)>().is_err());
+
+    // Failed fetches release the borrows they had taken
+    assert!(world.try_get_resource_mut::<Score>().is_ok());
+  }
+
+  #[test]
+  fn iter_entities_skips_deleted_entities() {
+    let mut world = World::new();
+    world.create_entity().with_component(Health(1.0)).unwrap();
+    world.create_entity().with_component(Health(2.0)).unwrap();
+    world.create_entity().with_component(Armor(3)).unwrap();
+
+    world.delete_entity(1).unwrap();
+    assert_eq!(world.iter_entities().collect::<Vec<_>>(), vec![0, 2]);
+
+    // The reused slot is only yielded once
+    world.create_entity().with_component(Armor(4)).unwrap();
+    assert_eq!(world.iter_entities().collect::<Vec<_>>(), vec![0, 1, 2]);
+
+    let masks = world
+      .iter_entities_with_mask()
+      .map(|(entity, mask)| (entity, mask.clone()))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      masks,
+      vec![(0, Bitmask::from_bit(0)), (1, Bitmask::from_bit(1)), (2, Bitmask::from_bit(1))]
+    );
+  }
+
+  #[test]
+  fn get_components_fetches_a_tuple() {
+    let mut world = World::new();
+    world.create_entity().with_components((Health(10.0), Armor(2))).unwrap();
+    world
+      .create_entity()
+      .with_components((Health(20.0), Armor(4), Name("b".to_string())))
+      .unwrap();
+
+    let (health, armor, name) = world.get_components::<(&Health, &mut Armor, Option<&Name>)>(0).unwrap();
+    armor.0 += 1;
+    assert_eq!(health.0, 10.0);
+    assert!(name.is_none());
+    assert_eq!(world.get_component::<Armor>(0).unwrap().0, 3);
+
+    let (health, name) = world.get_components::<(&mut Health, Option<&Name>)>(1).unwrap();
+    health.0 = 0.0;
+    assert_eq!(name.unwrap().0, "b");
+    assert_eq!(world.get_component::<Health>(1).unwrap().0, 0.0);
+  }
+
+  #[test]
+  fn get_components_rejects_invalid_fetches() {
+    let mut world = World::new();
+    world.create_entity().with_component(Health(10.0)).unwrap();
+    world.create_entity().with_components((Health(20.0), Armor(4))).unwrap();
+    world.delete_entity(1).unwrap();
+
+    let error = world.get_components::<(&mut Health, &Health)>(0).err().unwrap();
+    assert!(error.to_string().starts_with("Conflicting borrows of"));
+    assert!(world.get_components::<(&mut Health, &mut Health)>(0).is_err());
+    assert!(world.get_components::<(&Health, &Health)>(0).is_ok());
+
+    // Missing, unregistered and dead entity's components
+    assert!(matches!(
+      world.get_components::<(&Health, &Armor)>(0),
+      Err(NinaError::Ecs(EcsErrors::ComponentDataDoesNotExist { entity:0, ty })) if ty == std::any::type_name::<Armor>()
+    ));
+    assert!(world.get_components::<(&Health, &Name)>(0).is_err());
+    assert!(world.get_components::<(&Health, Option<&Name>)>(0).is_ok());
+    assert!(world.get_components::<(&Health,)>(1).is_err());
+  }
+
+  #[test]
+  fn merge_moves_entities_into_the_world() {
+    let drops = Arc::new(AtomicU32::new(0));
+
+    let mut world = World::new();
+    world.create_entity().with_component(Health(1.0)).unwrap();
+
+    let mut chunk = World::new();
+    chunk
+      .create_entity()
+      .with_components((Name("a".to_string()), Counted(drops.clone())))
+      .unwrap();
+    chunk.create_entity().with_component(Health(2.0)).unwrap();
+    chunk.create_entity().with_components((Name("c".to_string()), Health(3.0))).unwrap();
+    chunk.delete_entity(1).unwrap();
+
+    // The deleted entity leaves a hole which the pairs skip
+    let new_entities = world.merge(chunk);
+    assert_eq!(new_entities, vec![(0, 1), (2, 2)]);
+    assert_eq!(drops.load(Ordering::Relaxed), 0);
+
+    assert_eq!(world.get_component::<Name>(1).unwrap().0, "a");
+    assert!(world.get_component::<Counted>(1).is_ok());
+    assert!(world.get_component::<Health>(1).is_err());
+    assert_eq!(world.get_component::<Name>(2).unwrap().0, "c");
+    assert_eq!(world.get_component::<Health>(2).unwrap().0, 3.0);
+    assert_eq!(world.get_component::<Health>(0).unwrap().0, 1.0);
+
+    // The moved component is dropped exactly once, by its new owner
+    drop(world);
+    assert_eq!(drops.load(Ordering::Relaxed), 1);
+  }
+
+  #[test]
+  fn transfer_moves_an_entity_between_worlds() {
+    let drops = Arc::new(AtomicU32::new(0));
+    let mut loading = World::new();
+    let mut live = World::new();
+    live.register_components::<(Name, Counted, Health)>();
+    live.spawn((Health(1.0),)).unwrap();
+
+    loading.spawn((Health(2.0),)).unwrap();
+    let entity = loading.spawn((Name("loaded".to_string()), Counted(drops.clone()))).unwrap();
+
+    let moved = loading.transfer(entity, &mut live).unwrap();
+    assert_eq!(moved, 1);
+    assert_eq!(live.get_component::<Name>(moved).unwrap().0, "loaded");
+    assert!(live.has_components::<(Counted,)>(moved).unwrap());
+    assert!(!live.has_components::<(Health,)>(moved).unwrap());
+
+    assert!(loading.entity(entity).is_err());
+    assert!(loading.transfer(entity, &mut live).is_err());
+    assert_eq!(loading.removed::<Name>().collect::<Vec<_>>(), vec![entity]);
+
+    // Only the destination owns the moved components
+    drop(loading);
+    assert_eq!(drops.load(Ordering::Relaxed), 0);
+    drop(live);
+    assert_eq!(drops.load(Ordering::Relaxed), 1);
+  }
+
+  #[test]
+  fn transfer_names_the_missing_registration() {
+    let mut loading = World::new();
+    let mut live = World::new();
+    live.register_component::<Health>();
+    let entity = loading.spawn((Health(1.0), Name("a".to_string()))).unwrap();
+
+    let name = std::any::type_name::<Name>();
+    assert!(matches!(
+      loading.transfer(entity, &mut live),
+      Err(NinaError::Ecs(EcsErrors::ComponentNotRegistered { component })) if component == name
+    ));
+
+    // Neither world changed
+    assert_eq!(loading.get_component::<Name>(entity).unwrap().0, "a");
+    assert_eq!(loading.get_component::<Health>(entity).unwrap().0, 1.0);
+    assert_eq!(live.iter_entities().count(), 0);
+  }
+
+  fn some_system(world:&World) {
+    let mut query = world.query();
+    let entities = query.with_component::<Health>().unwrap().without_component::<Armor>().unwrap().run();
+
+    // Check resources can be fetched and mutated
+    let mut resource = world.get_resource_mut::<Resource>();
+    resource.0 = 1002;
+
+    // Check querying works
+    for entity in entities {
+      let health = entity.get_component::<Health>().unwrap();
+      assert_eq!(health.0, 540.2)
+    }
+
+    let mut query = world.query();
+    let entities = query.with_component::<Health>().unwrap().run();
+
+    for entity in entities {
       let health = entity.get_component::<Health>().unwrap();
       dbg!(health);
       if let Ok(armor) = entity.get_component_mut::<Armor>() {
@@ -266,8 +2267,232 @@ mod tests {
     }
   }
 
+  #[test]
+  fn removals_are_tracked_until_cleared() {
+    let mut world = World::new();
+    let first = world.spawn((Health(1.0), Armor(1))).unwrap();
+    let second = world.spawn((Health(2.0),)).unwrap();
+    let third = world.spawn((Armor(3),)).unwrap();
+
+    world.commands().remove_component::<Health>(second);
+    world.commands().remove_component::<Health>(third);
+    world.flush();
+    world.delete_entity(first).unwrap();
+
+    assert_eq!(world.removed::<Health>().collect::<Vec<_>>(), vec![second, first]);
+    assert_eq!(world.removed::<Armor>().collect::<Vec<_>>(), vec![first]);
+    assert_eq!(world.removed::<Score>().count(), 0);
+
+    world.clear_trackers();
+    assert_eq!(world.removed::<Health>().count(), 0);
+    world.delete_component::<Armor>(third).unwrap();
+    assert_eq!(world.removed::<Armor>().collect::<Vec<_>>(), vec![third]);
+  }
+
+  #[test]
+  fn enumerate_components_dynamically() {
+    let mut world = World::new();
+    world.register_component::<Armor>();
+    let entity = world.spawn((Health(5.0), Armor(2))).unwrap();
+    let armored = world.spawn((Armor(3),)).unwrap();
+
+    let (health, armor) = (TypeInfo::of::<Health>(), TypeInfo::of::<Armor>());
+    assert_eq!(world.components_of(entity), vec![armor, health]);
+    assert_eq!(world.components_of(armored), vec![armor]);
+    assert!(world.components_of(10).is_empty());
+
+    // Types registered later get the next bit and keep the shims added since
+    world.register_component_debug::<Health>();
+    world.add_component(armored, Name("a".to_string())).unwrap();
+    assert_eq!(world.components_of(armored), vec![armor, TypeInfo::of::<Name>()]);
+    assert_eq!(
+      world.registered_components().collect::<Vec<_>>(),
+      vec![armor, health, TypeInfo::of::<Name>()]
+    );
+
+    let ty = world.components_of(entity)[1];
+    let ptr = world.get_component_erased(entity, ty).unwrap();
+    assert_eq!(format!("{:?}", unsafe { ty.debug_value(ptr.as_ptr()) }), "Health(5.0)");
+    assert!(world.get_component_erased(armored, health).is_err());
+  }
+
+  #[test]
+  fn round_trip_components_through_erased_access() {
+    let mut world = World::new();
+    let entity = world.spawn((Bytes(vec![1, 2, 3]), Armor(1))).unwrap();
+    let unarmored = world.spawn((Bytes(Vec::new()),)).unwrap();
+    let ty = TypeInfo::of::<Bytes>();
+
+    let ptr = world.get_component_erased(entity, ty).unwrap();
+    let bytes = unsafe { ptr.cast::<Bytes>().as_ref() };
+    assert_eq!(bytes.0, [1, 2, 3]);
+
+    // The old value is dropped and the new one is owned by the world
+    let mut value = mem::ManuallyDrop::new(Bytes(vec![4; 16]));
+    world.set_component_erased(entity, ty, (&mut *value as *mut Bytes).cast()).unwrap();
+    assert_eq!(world.get_component::<Bytes>(entity).unwrap().0, [4; 16]);
+    assert_eq!(world.get_component::<Armor>(entity).unwrap().0, 1);
+
+    // Writing through the pointer updates the component in place
+    let ptr = world.get_component_erased(entity, ty).unwrap();
+    unsafe { ptr.cast::<Bytes>().as_mut() }.0.push(5);
+    assert_eq!(world.get_component::<Bytes>(entity).unwrap().0.len(), 17);
+
+    // Overwriting requires the component, the value is left to the caller
+    let armor = TypeInfo::of::<Armor>();
+    let mut value = Armor(2);
+    assert!(matches!(
+      world.set_component_erased(unarmored, armor, (&mut value as *mut Armor).cast()),
+      Err(NinaError::Ecs(EcsErrors::ComponentDataDoesNotExist { .. }))
+    ));
+    assert!(world.get_component_erased(unarmored, armor).is_err());
+    assert!(world.get_component_erased(99, ty).is_err());
+  }
+
+  #[cfg(feature = "sync")]
+  #[test]
+  fn world_is_send_and_sync() {
+    // Fails to compile if a field stops being `Send + Sync`
+    fn assert_send_sync<T:Send + Sync>() {}
+    assert_send_sync::<World>();
+    assert_send_sync::<Arc<World>>();
+  }
+
+  #[cfg(feature = "sync")]
+  #[test]
+  fn reserve_entities_from_several_threads() {
+    let mut world = World::new();
+    for _ in 0..4 {
+      world.spawn((Armor(0),)).unwrap();
+    }
+    world.delete_entity(1).unwrap();
+    world.delete_entity(3).unwrap();
+
+    let reserved = std::thread::scope(|scope| {
+      let world = &world;
+      let workers = (0..4)
+        .map(|_| scope.spawn(move || (0..25).map(|_| world.reserve_entity()).collect::<Vec<_>>()))
+        .collect::<Vec<_>>();
+      workers.into_iter().flat_map(|worker| worker.join().unwrap()).collect::<Vec<_>>()
+    });
+
+    let mut unique = reserved.clone();
+    unique.sort();
+    unique.dedup();
+    assert_eq!(unique.len(), 100);
+    // Dead slots are reused before the store grows
+    assert_eq!(unique[..3], [1, 3, 4]);
+    assert_eq!(world.iter_entities().count(), 2);
+
+    world.flush_reservations();
+    assert_eq!(world.iter_entities().count(), 102);
+    for entity in reserved {
+      world.add_component(entity, Armor(entity as u32)).unwrap();
+    }
+    assert_eq!(world.iter_components::<Armor>().count(), 102);
+  }
+
+  #[test]
+  fn reserved_entities_are_created_by_their_first_component() {
+    let mut world = World::new();
+    let entity = world.reserve_entity();
+    assert_eq!(world.reserve_entity(), entity + 1);
+    assert_eq!(world.iter_entities().count(), 0);
+
+    world.add_component(entity, Armor(1)).unwrap();
+    assert_eq!(world.iter_entities().collect::<Vec<_>>(), vec![entity, entity + 1]);
+    assert_eq!(world.get_component::<Armor>(entity).unwrap().0, 1);
+  }
+
+  #[test]
+  fn despawned_components_move_between_worlds() {
+    let mut world = World::new();
+    let item = world.spawn(("sword".to_string(), vec![1_u8, 2, 3])).unwrap();
+    let other = world.spawn(("shield".to_string(),)).unwrap();
+
+    let bundle = world.despawn_take(item).unwrap();
+    assert_eq!(bundle.len(), 2);
+    assert!(world.entity(item).is_err());
+    assert_eq!(world.removed::<String>().collect::<Vec<_>>(), vec![item]);
+    assert!(world.despawn_take(item).is_err());
+
+    // The emptied slot is reused without touching the moved values
+    let reused = world.spawn(("bow".to_string(),)).unwrap();
+    assert_eq!(reused, item);
+
+    let mut inventory = World::new();
+    let picked = inventory.spawn(bundle).unwrap();
+    assert_eq!(inventory.get_component::<String>(picked).unwrap(), "sword");
+    assert_eq!(inventory.get_component::<Vec<u8>>(picked).unwrap(), &vec![1, 2, 3]);
+    assert_eq!(world.get_component::<String>(other).unwrap(), "shield");
+
+    // An uninserted bundle drops its components
+    let drops = Arc::new(AtomicU32::new(0));
+    let counted = world.spawn((Counted(drops.clone()),)).unwrap();
+    drop(world.despawn_take(counted).unwrap());
+    assert_eq!(drops.load(Ordering::Relaxed), 1);
+    drop(world);
+    assert_eq!(drops.load(Ordering::Relaxed), 1);
+  }
+
+  #[test]
+  fn register_a_bundle_of_components() {
+    let mut world = World::new();
+    let entity = world.spawn((Armor(3),)).unwrap();
+
+    world.register_components::<(Health, Armor, Name)>().register_components::<(Name,)>();
+    let tys = [TypeInfo::of::<Armor>(), TypeInfo::of::<Health>(), TypeInfo::of::<Name>()];
+    assert_eq!(world.registered_components().collect::<Vec<_>>(), tys);
+
+    // Registered types can be queried and already stored data is kept
+    assert_eq!(world.query().with_all::<(Health, Name)>().unwrap().count(), 0);
+    assert_eq!(world.get_component::<Armor>(entity).unwrap().0, 3);
+  }
+
+  #[test]
+  fn spawning_up_to_the_capacity_does_not_reallocate() {
+    fn capacity<T:'static>(world:&World) -> usize {
+      world.entities.column(&TypeInfo::of::<T>()).unwrap().data().capacity()
+    }
+
+    let mut world = World::with_capacity(64);
+    world.register_component::<Armor>();
+    world.spawn((Armor(0),)).unwrap();
+    let armor = capacity::<Armor>(&world);
+    assert!(armor >= 64);
+
+    // Columns registered after the hint inherit it
+    world.register_component::<Health>();
+    let health = capacity::<Health>(&world);
+    assert!(health >= 64);
+
+    for x in 1..64 {
+      world.spawn((Armor(x), Health(x as f32))).unwrap();
+    }
+    assert_eq!(capacity::<Armor>(&world), armor);
+    assert_eq!(capacity::<Health>(&world), health);
+
+    world.reserve_entities_capacity(64);
+    assert!(capacity::<Armor>(&world) >= 128);
+  }
+
   #[derive(Debug)]
   struct Health(f32);
+  #[derive(Clone)]
+  struct Name(String);
+
+  struct Counted(Arc<AtomicU32>);
+
+  impl Drop for Counted {
+    fn drop(&mut self) {
+      self.0.fetch_add(1, Ordering::Relaxed);
+    }
+  }
+
   struct Armor(u32);
+
+  struct Bytes(Vec<u8>);
   struct Resource(i32);
+  #[derive(Default)]
+  struct Score(i32);
 }
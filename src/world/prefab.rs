@@ -0,0 +1,161 @@
+use crate::{
+  errors::{ErasedVecErrors, Result},
+  storage::{DynamicBundle, ErasedBox, TypeInfo}
+};
+
+/// A template of components stamped onto new entities by
+/// [`World::instantiate`](super::World::instantiate).
+///
+/// The components are stored type-erased and copied through the clone shims
+/// of their [`TypeInfo`]s, so a prefab can be built from components created
+/// at runtime, such as ones loaded from a scene file, as well as from a
+/// [`Bundle`](crate::storage::Bundle).
+pub struct Prefab {
+  components:Vec<ErasedBox>
+}
+
+// Prefab components are `EcsData`, which is `Send + Sync` with the `sync`
+// feature. Runtime-defined types must uphold the same bounds
+#[cfg(feature = "sync")]
+unsafe impl Send for Prefab {}
+#[cfg(feature = "sync")]
+unsafe impl Sync for Prefab {}
+
+impl Prefab {
+  /// Creates a prefab holding `components`.
+  ///
+  /// Returns an error naming the first component whose [`TypeInfo`] has no
+  /// clone shim.
+  pub fn from_components(components:Vec<ErasedBox>) -> Result<Self> {
+    if let Some(component) = components.iter().find(|component| !component.ty().is_cloneable()) {
+      return Err(ErasedVecErrors::NotCloneable(component.ty().name()).into());
+    }
+    Ok(Prefab { components })
+  }
+
+  /// Returns the [`TypeInfo`] of every component in the prefab.
+  pub fn component_types(&self) -> Vec<TypeInfo> {
+    self.components.iter().map(|component| component.ty()).collect()
+  }
+
+  /// Clones the prefab's components into a [`DynamicBundle`].
+  pub fn to_bundle(&self) -> DynamicBundle {
+    let mut bundle = DynamicBundle::new();
+    for component in &self.components {
+      // Every component was checked for a clone shim on creation
+      bundle.push_boxed(component.try_clone().unwrap());
+    }
+    bundle
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::Prefab;
+  use crate::{
+    errors::{EcsErrors, ErasedVecErrors, NinaError, Result},
+    storage::{ErasedBox, TypeInfo},
+    world::World
+  };
+  use std::{
+    mem,
+    sync::{
+      atomic::{AtomicU32, Ordering},
+      Arc
+    }
+  };
+
+  #[derive(Clone)]
+  struct Name(String);
+  #[derive(Clone)]
+  struct Hitpoints(u32);
+  #[derive(Clone)]
+  struct Hostile;
+  struct Target(u32);
+
+  /// Counts its clones.
+  struct Cloned(Arc<AtomicU32>);
+
+  impl Clone for Cloned {
+    fn clone(&self) -> Self {
+      self.0.fetch_add(1, Ordering::Relaxed);
+      Cloned(self.0.clone())
+    }
+  }
+
+  fn goblin_world() -> World {
+    let mut world = World::new();
+    world
+      .register_component_cloneable::<Name>()
+      .register_component_cloneable::<Hitpoints>()
+      .register_component_cloneable::<Hostile>()
+      .register_component_cloneable::<Cloned>();
+    world
+  }
+
+  #[test]
+  fn instantiate_clones_the_prefab() -> Result<()> {
+    let clones = Arc::new(AtomicU32::new(0));
+    let mut world = goblin_world();
+    world.register_prefab("goblin", (Name("goblin".to_string()), Hitpoints(7), Hostile, Cloned(clones.clone())))?;
+
+    let first = world.instantiate("goblin")?;
+    let second = world.instantiate("goblin")?;
+    assert_ne!(first, second);
+    assert_eq!(clones.load(Ordering::Relaxed), 2);
+
+    world.get_component_mut::<Hitpoints>(first)?.0 = 1;
+    assert_eq!(world.get_component::<Hitpoints>(second)?.0, 7);
+    assert_eq!(world.get_component::<Name>(second)?.0, "goblin");
+    assert!(world.has_components::<(Hostile, Cloned)>(second)?);
+
+    // Registering a prefab under the same name replaces it
+    world.register_prefab("goblin", (Name("chief".to_string()),))?;
+    let chief = world.instantiate("goblin")?;
+    assert_eq!(world.get_component::<Name>(chief)?.0, "chief");
+    assert!(!world.has_components::<(Hitpoints,)>(chief)?);
+    Ok(())
+  }
+
+  #[test]
+  fn prefab_errors() {
+    let mut world = goblin_world();
+    world.register_component::<Target>();
+
+    assert!(matches!(
+      world.instantiate("goblin"),
+      Err(NinaError::Ecs(EcsErrors::PrefabDoesNotExist { name })) if name == "goblin"
+    ));
+
+    // Members must be registered as cloneable, the bundle is dropped
+    let name = std::any::type_name::<Target>();
+    assert!(matches!(
+      world.register_prefab("archer", (Hitpoints(3), Target(1))),
+      Err(NinaError::ErasedVec(ErasedVecErrors::NotCloneable(ty))) if ty == name
+    ));
+    assert!(world.instantiate("archer").is_err());
+    assert_eq!(world.iter_entities().count(), 0);
+  }
+
+  /// Boxes `value` alongside its clone shim.
+  fn cloneable<T:Clone + 'static>(value:T) -> ErasedBox {
+    let mut value = mem::ManuallyDrop::new(value);
+    ErasedBox::from_raw_parts(TypeInfo::of_cloneable::<T>(), (&mut *value as *mut T).cast())
+  }
+
+  #[test]
+  fn prefabs_from_erased_components() -> Result<()> {
+    let mut world = World::new();
+    assert!(Prefab::from_components(vec![cloneable(Name("a".to_string())), ErasedBox::new(Hitpoints(2))]).is_err());
+
+    // The types do not need to be registered up front
+    let prefab = Prefab::from_components(vec![cloneable(Name("loaded".to_string())), cloneable(Hostile)])?;
+    assert_eq!(prefab.component_types(), vec![TypeInfo::of::<Name>(), TypeInfo::of::<Hostile>()]);
+    world.register_prefab_erased("loaded", prefab);
+
+    let entity = world.instantiate("loaded")?;
+    assert_eq!(world.get_component::<Name>(entity)?.0, "loaded");
+    assert!(world.has_components::<(Hostile,)>(entity)?);
+    Ok(())
+  }
+}
@@ -0,0 +1,225 @@
+use crate::{
+  errors::{EcsErrors, Result},
+  storage::{Bitmask, ComponentStorage, EcsData, TypeInfo},
+  world::{
+    access::Access,
+    entities::{ComponentId, Entity},
+    Entities
+  }
+};
+
+/// Types which can be fetched from an entity's components in one call.
+///
+/// Implemented for `&T`, `&mut T`, `Option<&T>`, `Option<&mut T>` and tuples
+/// of up to eight of those.
+///
+/// # Safety
+/// - `access` must record every component `fetch` reads or writes.
+/// - `required` must record every component `fetch` reads without checking the
+///   entity's mask.
+pub unsafe trait Fetch {
+  /// The references handed out for a single entity.
+  type Item<'a>;
+
+  /// The columns resolved up front by [`Fetch::prepare`].
+  type State<'a>;
+
+  /// Records the components the fetch reads and writes.
+  ///
+  /// Returns an error if the fetch borrows a component mutably more than
+  /// once.
+  fn access(access:&mut Access) -> Result<()>;
+
+  /// Resolves the column of every component the fetch reads.
+  ///
+  /// Returns an error if a required component was never registered.
+  fn prepare(entities:&Entities) -> Result<Self::State<'_>>;
+
+  /// Sets the bits of every component an entity must hold to match the
+  /// fetch.
+  fn required(state:&Self::State<'_>, mask:&mut Bitmask);
+
+  /// Fetches the components held by `entity`.
+  ///
+  /// # Safety
+  /// - `entity` must be alive and `mask` must be its [`Bitmask`].
+  /// - `mask` must contain every bit set by [`Fetch::required`].
+  /// - Mutably fetched components must not be aliased for the lifetime of the
+  ///   item.
+  unsafe fn fetch<'a>(state:&Self::State<'a>, entity:Entity, mask:&Bitmask) -> Self::Item<'a>;
+}
+
+/// A component's storage alongside the bit marking it in entity masks.
+pub struct Column<'a> {
+  components:&'a ComponentStorage,
+  mask:&'a Bitmask
+}
+
+impl<'a> Column<'a> {
+  /// Resolves the column storing components of type `ty`.
+  pub fn new(entities:&'a Entities, ty:&TypeInfo) -> Result<Self> {
+    match entities.component_id(ty) {
+      Some(id) => Ok(Self::by_id(entities, id)),
+      None => Err(EcsErrors::ComponentNotRegistered { component:ty.name() }.into())
+    }
+  }
+
+  /// Resolves the column of the component registered as `id`.
+  ///
+  /// # Panics
+  /// - Panics if `id` was not registered with `entities`.
+  pub fn by_id(entities:&'a Entities, id:ComponentId) -> Self {
+    Column {
+      components:&entities.components[id.index()],
+      mask:entities.bitmask_by_id(id)
+    }
+  }
+
+  /// Returns the bit marking the component in entity masks.
+  pub fn mask(&self) -> &'a Bitmask {
+    self.mask
+  }
+
+  /// Returns the component storage.
+  pub fn components(&self) -> &'a ComponentStorage {
+    self.components
+  }
+
+  /// Returns a pointer to the entity's component.
+  ///
+  /// # Safety
+  /// - `entity` must be within the column.
+  pub unsafe fn ptr<T:'static>(&self, entity:Entity) -> *mut T {
+    debug_assert!(self.components.is_filled(entity), "The mask and storage disagree on entity {entity}");
+    self.components.indexed_ptr::<T>(entity)
+  }
+}
+
+unsafe impl<T:EcsData> Fetch for &T {
+  type Item<'a> = &'a T;
+  type State<'a> = Column<'a>;
+
+  fn access(access:&mut Access) -> Result<()> {
+    access.add_read(TypeInfo::of::<T>())
+  }
+
+  fn prepare(entities:&Entities) -> Result<Self::State<'_>> {
+    Column::new(entities, &TypeInfo::of::<T>())
+  }
+
+  fn required(state:&Self::State<'_>, mask:&mut Bitmask) {
+    mask.insert(state.mask());
+  }
+
+  unsafe fn fetch<'a>(state:&Self::State<'a>, entity:Entity, _mask:&Bitmask) -> Self::Item<'a> {
+    &*state.ptr::<T>(entity)
+  }
+}
+
+unsafe impl<T:EcsData> Fetch for &mut T {
+  type Item<'a> = &'a mut T;
+  type State<'a> = Column<'a>;
+
+  fn access(access:&mut Access) -> Result<()> {
+    access.add_write(TypeInfo::of::<T>())
+  }
+
+  fn prepare(entities:&Entities) -> Result<Self::State<'_>> {
+    Column::new(entities, &TypeInfo::of::<T>())
+  }
+
+  fn required(state:&Self::State<'_>, mask:&mut Bitmask) {
+    mask.insert(state.mask());
+  }
+
+  unsafe fn fetch<'a>(state:&Self::State<'a>, entity:Entity, _mask:&Bitmask) -> Self::Item<'a> {
+    state.components().mark_changed(entity);
+    &mut *state.ptr::<T>(entity)
+  }
+}
+
+unsafe impl<T:EcsData> Fetch for Option<&T> {
+  type Item<'a> = Option<&'a T>;
+  type State<'a> = Option<Column<'a>>;
+
+  fn access(access:&mut Access) -> Result<()> {
+    access.add_read(TypeInfo::of::<T>())
+  }
+
+  fn prepare(entities:&Entities) -> Result<Self::State<'_>> {
+    // An unregistered component is never held so it is simply absent
+    Ok(Column::new(entities, &TypeInfo::of::<T>()).ok())
+  }
+
+  fn required(_state:&Self::State<'_>, _mask:&mut Bitmask) {}
+
+  unsafe fn fetch<'a>(state:&Self::State<'a>, entity:Entity, mask:&Bitmask) -> Self::Item<'a> {
+    match state {
+      Some(column) if mask.contains(column.mask()) => Some(&*column.ptr::<T>(entity)),
+      _ => None
+    }
+  }
+}
+
+unsafe impl<T:EcsData> Fetch for Option<&mut T> {
+  type Item<'a> = Option<&'a mut T>;
+  type State<'a> = Option<Column<'a>>;
+
+  fn access(access:&mut Access) -> Result<()> {
+    access.add_write(TypeInfo::of::<T>())
+  }
+
+  fn prepare(entities:&Entities) -> Result<Self::State<'_>> {
+    Ok(Column::new(entities, &TypeInfo::of::<T>()).ok())
+  }
+
+  fn required(_state:&Self::State<'_>, _mask:&mut Bitmask) {}
+
+  unsafe fn fetch<'a>(state:&Self::State<'a>, entity:Entity, mask:&Bitmask) -> Self::Item<'a> {
+    match state {
+      Some(column) if mask.contains(column.mask()) => {
+        column.components().mark_changed(entity);
+        Some(&mut *column.ptr::<T>(entity))
+      }
+      _ => None
+    }
+  }
+}
+
+macro_rules! impl_fetch_tuple {
+  ($($name:ident),*) => {
+    #[allow(non_snake_case)]
+    unsafe impl<$($name:Fetch),*> Fetch for ($($name,)*) {
+      type Item<'a> = ($($name::Item<'a>,)*);
+      type State<'a> = ($($name::State<'a>,)*);
+
+      fn access(access:&mut Access) -> Result<()> {
+        $($name::access(access)?;)*
+        Ok(())
+      }
+
+      fn prepare(entities:&Entities) -> Result<Self::State<'_>> {
+        Ok(($($name::prepare(entities)?,)*))
+      }
+
+      fn required(state:&Self::State<'_>, mask:&mut Bitmask) {
+        let ($($name,)*) = state;
+        $($name::required($name, mask);)*
+      }
+
+      unsafe fn fetch<'a>(state:&Self::State<'a>, entity:Entity, mask:&Bitmask) -> Self::Item<'a> {
+        let ($($name,)*) = state;
+        ($($name::fetch($name, entity, mask),)*)
+      }
+    }
+  };
+}
+
+impl_fetch_tuple!(A);
+impl_fetch_tuple!(A, B);
+impl_fetch_tuple!(A, B, C);
+impl_fetch_tuple!(A, B, C, D);
+impl_fetch_tuple!(A, B, C, D, E);
+impl_fetch_tuple!(A, B, C, D, E, F);
+impl_fetch_tuple!(A, B, C, D, E, F, G);
+impl_fetch_tuple!(A, B, C, D, E, F, G, H);
@@ -1,2 +1,6 @@
+pub mod fetch;
+pub mod prepared_query;
+#[allow(clippy::module_inception)]
 pub mod query;
 pub mod query_entity;
+pub mod tuple_query;
@@ -0,0 +1,88 @@
+use super::query_entity::QueryEntity;
+use crate::{
+  errors::Result,
+  storage::{Bitmask, Bundle},
+  world::{Entities, World}
+};
+
+/// A query whose component masks were resolved up front.
+///
+/// Running a [`PreparedQuery`] performs no type lookups so it can be stored,
+/// for example in a system, and run every frame. The masks are only valid
+/// for the [`World`] the query was prepared from.
+#[derive(Debug, Clone, Default)]
+pub struct PreparedQuery {
+  map:Bitmask,
+  exclude_map:Bitmask
+}
+
+impl PreparedQuery {
+  /// Create a new [`PreparedQuery`] from already resolved masks.
+  pub fn new(map:Bitmask, exclude_map:Bitmask) -> Self {
+    Self { map, exclude_map }
+  }
+
+  /// Create a [`PreparedQuery`] matching entities which hold every component
+  /// in the [`Bundle`] `B`.
+  ///
+  /// Returns an error if a component in `B` was never registered.
+  pub fn with<B:Bundle>(entities:&Entities) -> Result<Self> {
+    Ok(Self::new(entities.bundle_mask::<B>()?, Bitmask::new()))
+  }
+
+  /// Returns the mask of the components a matching entity must hold.
+  pub fn mask(&self) -> &Bitmask {
+    &self.map
+  }
+
+  /// Returns an iterator over every live entity in `world` which matches the
+  /// query.
+  pub fn iter<'q, 'w:'q>(&'q self, world:&'w World) -> impl Iterator<Item = QueryEntity<'w>> + 'q {
+    let entities = &world.entities;
+    entities.map.iter().enumerate().filter_map(move |(entity, entity_map)| {
+      if entities.is_alive(entity) && entity_map.contains(&self.map) && !entity_map.intersects(&self.exclude_map) {
+        Some(QueryEntity::new(entity, entities))
+      } else {
+        None
+      }
+    })
+  }
+
+  /// Returns a [`Vec`] of [`QueryEntity`] containing every live entity in
+  /// `world` which matches the query.
+  pub fn run<'w>(&self, world:&'w World) -> Vec<QueryEntity<'w>> {
+    self.iter(world).collect()
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::errors::Result;
+  use crate::world::World;
+
+  #[test]
+  fn run_prepared_query_across_frames() -> Result<()> {
+    let mut world = World::new();
+    world.create_entity().with_components((Health(1), Damage(1)))?;
+    world.create_entity().with_component(Health(2))?;
+
+    let prepared = world.prepare_query::<(Health, Damage)>()?;
+    let excluding = world.query().with_component::<Health>()?.without_component::<Damage>()?.prepare();
+    assert_eq!(prepared.run(&world).len(), 1);
+    assert_eq!(excluding.run(&world)[0].id, 1);
+
+    // The stored query sees the world's current state
+    world.add_component(1, Damage(2))?;
+    world.create_entity().with_components((Health(3), Damage(3)))?;
+    let cloned = prepared.clone();
+    let ids = cloned.iter(&world).map(|entity| entity.id).collect::<Vec<_>>();
+    assert_eq!(ids, vec![0, 1, 2]);
+    assert!(excluding.run(&world).is_empty());
+
+    assert!(world.prepare_query::<(Health, String)>().is_err());
+    Ok(())
+  }
+
+  struct Health(i32);
+  struct Damage(i32);
+}
@@ -1,14 +1,38 @@
-use super::query_entity::QueryEntity;
+use super::{
+  prepared_query::PreparedQuery,
+  query_entity::{Columns, QueryEntity, CACHED_COLUMNS}
+};
 use crate::{
-  errors::EcsErrors,
-  storage::{EcsData, TypeInfo},
-  world::Entities
+  errors::{EcsErrors, Result},
+  storage::{Bitmask, Bundle, ComponentStorage, EcsData, Tick, TypeInfo},
+  world::{
+    entities::{ComponentId, Entity},
+    Entities
+  }
 };
-use eyre::Result;
+use std::{fmt, ptr};
+
+/// A predicate an entity's component must satisfy.
+///
+/// The predicate is `'static` so dropping a [`Query`] never touches borrowed
+/// data, which would keep the world borrowed until the query goes out of
+/// scope.
+type Predicate = Box<dyn Fn(&ComponentStorage, Entity) -> bool>;
 
 pub struct Query<'a> {
-  map:u128,
-  exclude_map:u128,
+  map:Bitmask,
+  exclude_map:Bitmask,
+  /// Columns whose slots must have been added since `since`.
+  added:Vec<&'a ComponentStorage>,
+  /// Columns whose slots must have changed since `since`.
+  changed:Vec<&'a ComponentStorage>,
+  since:Tick,
+  /// Predicates on the queried components, keyed by the type they read,
+  /// alongside the column of that type.
+  filters:Vec<(TypeInfo, &'a ComponentStorage, Predicate)>,
+  /// Columns of the first required component types, handed to each
+  /// [`QueryEntity`] so their components are fetched without a lookup.
+  columns:Columns<'a>,
   entities:&'a Entities
 }
 
@@ -16,50 +40,342 @@ impl<'a> Query<'a> {
   /// Create a new [`Query`].
   pub fn new(entities:&'a Entities) -> Self {
     Self {
-      map:0,
-      exclude_map:0,
+      map:Bitmask::new(),
+      exclude_map:Bitmask::new(),
+      added:Vec::new(),
+      changed:Vec::new(),
+      since:entities.tick(),
+      filters:Vec::new(),
+      columns:[None; CACHED_COLUMNS],
       entities
     }
   }
 
   /// Register a component the queried entities must hold.
   pub fn with_component<T:EcsData>(&mut self) -> Result<&mut Self> {
-    let ty = TypeInfo::of::<T>();
+    self.with_type(TypeInfo::of::<T>())
+  }
+
+  /// Register a component the queried entities must **not** hold.
+  pub fn without_component<T:EcsData>(&mut self) -> Result<&mut Self> {
+    self.without_type(TypeInfo::of::<T>())
+  }
+
+  /// Register a type-erased component the queried entities must hold.
+  pub fn with_type(&mut self, ty:TypeInfo) -> Result<&mut Self> {
     if let Some(bit_mask) = self.entities.get_bitmask(&ty) {
-      self.map |= bit_mask;
+      self.map.insert(bit_mask);
+      self.cache(self.entities.column(&ty).unwrap());
     } else {
-      return Err(EcsErrors::ComponentNotRegistered.into());
+      return Err(EcsErrors::ComponentNotRegistered { component:ty.name() }.into());
     }
     Ok(self)
   }
 
-  /// Register a component the queried entities must **not** hold.
-  pub fn without_component<T:EcsData>(&mut self) -> Result<&mut Self> {
-    let ty = TypeInfo::of::<T>();
+  /// Register the component registered as `id` as one the queried entities
+  /// must hold.
+  ///
+  /// # Panics
+  /// - Panics if `id` was not registered with the queried entities.
+  pub fn with_id(&mut self, id:ComponentId) -> &mut Self {
+    self.map.insert(self.entities.bitmask_by_id(id));
+    self.cache(&self.entities.components[id.index()]);
+    self
+  }
+
+  /// Register the component registered as `id` as one the queried entities
+  /// must **not** hold.
+  ///
+  /// # Panics
+  /// - Panics if `id` was not registered with the queried entities.
+  pub fn without_id(&mut self, id:ComponentId) -> &mut Self {
+    self.exclude_map.insert(self.entities.bitmask_by_id(id));
+    self
+  }
+
+  /// Register a type-erased component the queried entities must **not**
+  /// hold.
+  pub fn without_type(&mut self, ty:TypeInfo) -> Result<&mut Self> {
     if let Some(bit_mask) = self.entities.get_bitmask(&ty) {
-      self.exclude_map |= bit_mask;
+      self.exclude_map.insert(bit_mask);
     } else {
-      return Err(EcsErrors::ComponentNotRegistered.into());
+      return Err(EcsErrors::ComponentNotRegistered { component:ty.name() }.into());
+    }
+    Ok(self)
+  }
+
+  /// Register every component in the [`Bundle`] `B` as one the queried
+  /// entities must hold.
+  pub fn with_all<B:Bundle>(&mut self) -> Result<&mut Self> {
+    for ty in B::types() {
+      self.with_type(ty)?;
+    }
+    Ok(self)
+  }
+
+  /// Register every component in the [`Bundle`] `B` as one the queried
+  /// entities must **not** hold.
+  pub fn without_all<B:Bundle>(&mut self) -> Result<&mut Self> {
+    for ty in B::types() {
+      self.without_type(ty)?;
     }
     Ok(self)
   }
 
-  /// Consumes the [`Query`]. Returns a [`Vec`] of [`QueryEntity`] containing
-  /// all entities who hold the queried components.
-  pub fn run(&self) -> Vec<QueryEntity> {
+  /// Register a component the queried entities must hold which was added
+  /// at or after the query's tick.
+  pub fn added<T:EcsData>(&mut self) -> Result<&mut Self> {
+    self.with_component::<T>()?;
+    self.added.push(self.entities.column(&TypeInfo::of::<T>()).unwrap());
+    Ok(self)
+  }
+
+  /// Register a component the queried entities must hold which was changed
+  /// at or after the query's tick.
+  ///
+  /// Adding a component counts as changing it.
+  pub fn changed<T:EcsData>(&mut self) -> Result<&mut Self> {
+    self.with_component::<T>()?;
+    self.changed.push(self.entities.column(&TypeInfo::of::<T>()).unwrap());
+    Ok(self)
+  }
+
+  /// Register a predicate the queried entities' component of type `T` must
+  /// satisfy.
+  ///
+  /// The entities must also hold a `T`, as if it was registered with
+  /// [`with_component`](Query::with_component). Every filter must pass for
+  /// an entity to match. The predicate cannot borrow from its environment,
+  /// move the values it compares against into it.
+  pub fn filter<T:EcsData>(&mut self, predicate:impl Fn(&T) -> bool + 'static) -> Result<&mut Self> {
+    let ty = TypeInfo::of::<T>();
+    self.with_type(ty)?;
+
+    let column = self.entities.column(&ty).unwrap();
+    let predicate = move |column:&ComponentStorage, entity| column.try_get::<T>(entity).is_some_and(&predicate);
+    self.filters.push((ty, column, Box::new(predicate)));
+    Ok(self)
+  }
+
+  /// Set the tick the [`added`](Query::added) and
+  /// [`changed`](Query::changed) filters compare against.
+  ///
+  /// Defaults to the world's current tick.
+  pub fn since(&mut self, tick:Tick) -> &mut Self {
+    self.since = tick;
     self
-      .entities
-      .map
-      .iter()
-      .enumerate()
-      .filter_map(|(index, entity_map)| {
-        if (entity_map & (self.map | self.exclude_map)) == self.map {
-          Some(QueryEntity::new(index, self.entities))
-        } else {
-          None
-        }
-      })
-      .collect()
+  }
+
+  /// Returns an iterator over every live entity which holds the queried
+  /// components.
+  ///
+  /// The iterator borrows the entity store so the entity masks cannot change
+  /// while it is in use.
+  pub fn iter(&self) -> QueryIter<'_, 'a> {
+    QueryIter { query:self, index:0 }
+  }
+
+  /// Stores the query's component masks in a [`PreparedQuery`] which can be
+  /// run again without looking the components up.
+  ///
+  /// The [`added`](Query::added) and [`changed`](Query::changed) filters and
+  /// the predicates registered with [`filter`](Query::filter) are not carried
+  /// over.
+  pub fn prepare(&self) -> PreparedQuery {
+    PreparedQuery::new(self.map.clone(), self.exclude_map.clone())
+  }
+
+  /// Caches the column of a required component type if there is room left.
+  fn cache(&mut self, column:&'a ComponentStorage) {
+    let free = self.columns.iter_mut().find(|cached| cached.is_none_or(|cached| ptr::eq(cached, column)));
+    if let Some(cached) = free {
+      *cached = Some(column);
+    }
+  }
+
+  /// Returns `true` if the entity matches the query.
+  fn matches(&self, entity:Entity, entity_map:&Bitmask) -> bool {
+    self.entities.is_alive(entity)
+      && entity_map.contains(&self.map)
+      && !entity_map.intersects(&self.exclude_map)
+      && self.added.iter().all(|components| components.ticks(entity).added() >= self.since)
+      && self.changed.iter().all(|components| components.ticks(entity).changed() >= self.since)
+      && self.filters.iter().all(|(_, column, predicate)| predicate(column, entity))
+  }
+
+  /// Returns a [`Vec`] of [`QueryEntity`] containing all entities who hold
+  /// the queried components.
+  pub fn run(&self) -> Vec<QueryEntity<'a>> {
+    self.iter().collect()
+  }
+
+  /// Clears `out` and fills it with every entity which holds the queried
+  /// components.
+  ///
+  /// Reusing the buffer across runs avoids allocating a new [`Vec`] each
+  /// time.
+  pub fn run_into(&self, out:&mut Vec<QueryEntity<'a>>) {
+    out.clear();
+    out.extend(self.iter());
+  }
+
+  /// Returns a [`QueryEntity`] for `entity` if it is alive and matches the
+  /// query.
+  ///
+  /// Only the entity's own bitmask is checked.
+  pub fn get(&self, entity:Entity) -> Option<QueryEntity<'a>> {
+    let entity_map = self.entities.map.get(entity)?;
+    if self.matches(entity, entity_map) {
+      Some(QueryEntity::with_columns(entity, self.entities, self.columns))
+    } else {
+      None
+    }
+  }
+
+  /// Returns the number of entities which hold the queried components
+  /// without allocating.
+  pub fn count(&self) -> usize {
+    self.iter().count()
+  }
+
+  /// Combines the query with `other` into a [`CombinedQuery`] matching the
+  /// entities which match either of them.
+  ///
+  /// # Panics
+  /// - Panics if `other` queries a different entity store.
+  pub fn or(self, other:Query<'a>) -> CombinedQuery<'a> {
+    CombinedQuery { queries:vec![self] }.or(other)
+  }
+}
+
+/// The union of several [`Query`]s, created by [`Query::or`].
+///
+/// Each entity is checked against every query in turn and yielded once if
+/// any of them matches, so entities matching several queries are not
+/// duplicated.
+#[derive(Debug)]
+pub struct CombinedQuery<'a> {
+  queries:Vec<Query<'a>>
+}
+
+impl<'a> CombinedQuery<'a> {
+  /// Adds `other` to the queries an entity can match.
+  ///
+  /// # Panics
+  /// - Panics if `other` queries a different entity store.
+  pub fn or(mut self, other:Query<'a>) -> Self {
+    assert!(
+      ptr::eq(self.queries[0].entities, other.entities),
+      "Combined queries must query the same entity store"
+    );
+    self.queries.push(other);
+    self
+  }
+
+  /// Returns an iterator over every live entity which matches any of the
+  /// queries, in ascending order.
+  pub fn iter(&self) -> CombinedQueryIter<'_, 'a> {
+    CombinedQueryIter { query:self, index:0 }
+  }
+
+  /// Returns a [`Vec`] of [`QueryEntity`] containing every entity which
+  /// matches any of the queries.
+  pub fn run(&self) -> Vec<QueryEntity<'a>> {
+    self.iter().collect()
+  }
+
+  /// Returns a [`QueryEntity`] for `entity` if it is alive and matches any
+  /// of the queries.
+  pub fn get(&self, entity:Entity) -> Option<QueryEntity<'a>> {
+    self.queries.iter().find_map(|query| query.get(entity))
+  }
+
+  /// Returns the number of entities which match any of the queries without
+  /// allocating.
+  pub fn count(&self) -> usize {
+    self.iter().count()
+  }
+}
+
+/// Lazy iterator over the entities matched by a [`CombinedQuery`].
+pub struct CombinedQueryIter<'q, 'a> {
+  query:&'q CombinedQuery<'a>,
+  index:usize
+}
+
+impl<'q, 'a> Iterator for CombinedQueryIter<'q, 'a> {
+  type Item = QueryEntity<'a>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let entities = self.query.queries[0].entities;
+    while let Some(entity_map) = entities.map.get(self.index) {
+      let entity = self.index;
+      self.index += 1;
+
+      // The first matching query hands over its cached columns
+      if let Some(query) = self.query.queries.iter().find(|query| query.matches(entity, entity_map)) {
+        return Some(QueryEntity::with_columns(entity, entities, query.columns));
+      }
+    }
+    None
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    (0, Some(self.query.queries[0].entities.map.len().saturating_sub(self.index)))
+  }
+}
+
+/// Lazy iterator over the entities matched by a [`Query`].
+pub struct QueryIter<'q, 'a> {
+  query:&'q Query<'a>,
+  index:usize
+}
+
+impl<'q, 'a> Iterator for QueryIter<'q, 'a> {
+  type Item = QueryEntity<'a>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let entities = self.query.entities;
+    while let Some(entity_map) = entities.map.get(self.index) {
+      let entity = self.index;
+      self.index += 1;
+
+      if self.query.matches(entity, entity_map) {
+        return Some(QueryEntity::with_columns(entity, entities, self.query.columns));
+      }
+    }
+    None
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    (0, Some(self.query.entities.map.len().saturating_sub(self.index)))
+  }
+}
+
+impl fmt::Debug for Query<'_> {
+  /// Lists the required and excluded components by type name. The alternate
+  /// form also lists the change filters and predicates.
+  fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result {
+    let names = |mask:&Bitmask| {
+      mask
+        .iter()
+        .filter_map(|bit| self.entities.component_type(bit))
+        .map(|ty| ty.type_name())
+        .collect::<Vec<_>>()
+    };
+    let column_names = |columns:&[&ComponentStorage]| columns.iter().map(|column| column.ty().type_name()).collect::<Vec<_>>();
+
+    let alternate = f.alternate();
+    let mut debug = f.debug_struct("Query");
+    debug.field("with", &names(&self.map)).field("without", &names(&self.exclude_map));
+    if alternate {
+      debug
+        .field("added", &column_names(&self.added))
+        .field("changed", &column_names(&self.changed))
+        .field("since", &self.since)
+        .field("filters", &self.filters.iter().map(|(ty, ..)| ty.type_name()).collect::<Vec<_>>());
+    }
+    debug.finish()
   }
 }
 
@@ -80,7 +396,201 @@ mod test {
 
     query.with_component::<u32>()?.with_component::<f32>()?.without_component::<usize>()?;
 
-    assert_eq!(query.map, 3);
+    assert_eq!(query.map, Bitmask::from_iter([0, 1]));
+    Ok(())
+  }
+
+  #[test]
+  fn iterate_query_lazily() -> Result<()> {
+    let mut world = World::new();
+    world.create_entity().with_component(Health(1))?;
+    world.create_entity().with_component(Damage(2))?;
+    world.create_entity().with_component(Health(3))?;
+    world.create_entity().with_component(Health(4))?;
+    world.delete_entity(3)?;
+
+    let mut query = world.query();
+    query.with_component::<Health>()?;
+    assert_eq!(query.iter().count(), 2);
+
+    let first = query.iter().take(1).map(|entity| entity.id).collect::<Vec<_>>();
+    assert_eq!(first, vec![0]);
+
+    let mut query = world.query();
+    query.with_component::<Health>()?.with_component::<Damage>()?;
+    assert!(query.iter().next().is_none());
+    assert!(query.run().is_empty());
+    Ok(())
+  }
+
+  #[test]
+  fn exclusion_only_query_skips_deleted_entities() -> Result<()> {
+    let mut world = World::new();
+    world.register_component::<Disabled>();
+    world.create_entity().with_component(Health(1))?;
+    world.create_entity().with_component(Health(2))?;
+    world.delete_entity(0)?;
+
+    let mut query = world.query();
+    let entities = query.without_component::<Disabled>()?.run();
+    assert_eq!(entities.len(), 1);
+    assert_eq!(entities[0].id, 1);
+    assert_eq!(entities[0].get_component::<Health>()?.0, 2);
+    Ok(())
+  }
+
+  #[test]
+  fn query_changed_and_added_components() -> Result<()> {
+    let mut world = World::new();
+    for x in 0..3 {
+      world.create_entity().with_component(Transform(x))?;
+    }
+
+    world.increment_tick();
+    world.get_component_mut::<Transform>(1)?.0 += 10;
+
+    let mut query = world.query();
+    let changed = query.changed::<Transform>()?.iter().map(|entity| entity.id).collect::<Vec<_>>();
+    assert_eq!(changed, vec![1]);
+
+    let mut query = world.query();
+    assert_eq!(query.added::<Transform>()?.iter().count(), 0);
+    assert_eq!(query.since(0).iter().count(), 3);
+
+    // Mutable access through a query entity is also tracked
+    world.increment_tick();
+    world.create_entity().with_component(Transform(3))?;
+    let mut query = world.query();
+    query.with_component::<Transform>()?.run()[0].get_component_mut::<Transform>()?.0 += 1;
+
+    let mut query = world.query();
+    let changed = query.changed::<Transform>()?.iter().map(|entity| entity.id).collect::<Vec<_>>();
+    assert_eq!(changed, vec![0, 3]);
+
+    let mut query = world.query();
+    let added = query.added::<Transform>()?.iter().map(|entity| entity.id).collect::<Vec<_>>();
+    assert_eq!(added, vec![3]);
+    Ok(())
+  }
+
+  #[test]
+  fn added_markers_are_tracked_per_entity() -> Result<()> {
+    let mut world = World::new();
+    world.create_entity().with_components((Transform(0), Disabled))?;
+    for x in 1..3 {
+      world.create_entity().with_component(Transform(x))?;
+    }
+
+    world.increment_tick();
+    world.add_component(2, Disabled)?;
+
+    let mut query = world.query();
+    let added = query.added::<Disabled>()?.iter().map(|entity| entity.id).collect::<Vec<_>>();
+    assert_eq!(added, vec![2]);
+    Ok(())
+  }
+
+  #[test]
+  fn dynamic_query_matches_typed_query() -> Result<()> {
+    let mut world = World::new();
+    world.create_entity().with_components((Health(1), Damage(1)))?;
+    world.create_entity().with_component(Health(2))?;
+    world.create_entity().with_component(Damage(3))?;
+
+    let mut typed = world.query();
+    typed.with_component::<Health>()?.without_component::<Damage>()?;
+
+    let mut dynamic = world.query();
+    dynamic.with_type(TypeInfo::of::<Health>())?.without_type(TypeInfo::of::<Damage>())?;
+
+    assert_eq!(typed.map, dynamic.map);
+    assert_eq!(typed.exclude_map, dynamic.exclude_map);
+    let typed_ids = typed.iter().map(|entity| entity.id).collect::<Vec<_>>();
+    let dynamic_ids = dynamic.iter().map(|entity| entity.id).collect::<Vec<_>>();
+    assert_eq!(typed_ids, dynamic_ids);
+
+    // Write through the erased pointer and read it back typed
+    let entity = &dynamic.run()[0];
+    let (ptr, ty) = entity.get_component_ptr(TypeInfo::of::<Health>())?;
+    assert_eq!(ty, TypeInfo::of::<Health>());
+    unsafe { (*ptr.cast::<Health>()).0 = 20 };
+    assert_eq!(entity.get_component::<Health>()?.0, 20);
+    assert!(entity.get_component_ptr(TypeInfo::of::<Damage>()).is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn run_into_reuses_the_buffer() -> Result<()> {
+    let mut world = World::new();
+    for x in 0..8 {
+      world.create_entity().with_component(Health(x))?;
+    }
+    world.create_entity().with_component(Damage(1))?;
+
+    let mut query = world.query();
+    query.with_component::<Health>()?;
+    assert_eq!(query.count(), 8);
+
+    let mut buffer = Vec::new();
+    query.run_into(&mut buffer);
+    assert_eq!(buffer.len(), 8);
+    let capacity = buffer.capacity();
+    let ptr = buffer.as_ptr();
+
+    query.run_into(&mut buffer);
+    assert_eq!(buffer.len(), 8);
+    assert_eq!(buffer.capacity(), capacity);
+    assert_eq!(buffer.as_ptr(), ptr);
+    assert_eq!(buffer[7].get_component::<Health>()?.0, 7);
+    Ok(())
+  }
+
+  #[test]
+  fn get_a_single_entity_from_a_query() -> Result<()> {
+    let mut world = World::new();
+    world.register_component::<Disabled>();
+    world.create_entity().with_component(Health(1))?;
+    world.create_entity().with_components((Health(2), Disabled))?;
+    world.create_entity().with_component(Health(3))?;
+    world.delete_entity(2)?;
+
+    let mut query = world.query();
+    query.with_component::<Health>()?.without_component::<Disabled>()?;
+
+    // Matching entity
+    let entity = query.get(0).unwrap();
+    assert_eq!(entity.get_component::<Health>()?.0, 1);
+
+    // Non-matching entity
+    assert!(query.get(1).is_none());
+
+    // Dead and never created entities
+    assert!(query.get(2).is_none());
+    assert!(query.get(100).is_none());
+    Ok(())
+  }
+
+  #[test]
+  fn bundle_filters_match_the_manual_chain() -> Result<()> {
+    let mut world = World::new();
+    world.register_component::<Health>().register_component::<Damage>();
+    world.register_component::<Disabled>().register_component::<u32>();
+
+    let mut manual = world.query();
+    manual
+      .with_component::<Health>()?
+      .with_component::<Damage>()?
+      .without_component::<Disabled>()?
+      .without_component::<u32>()?;
+
+    let mut bundled = world.query();
+    bundled.with_all::<(Health, Damage)>()?.without_all::<(Disabled, u32)>()?;
+
+    assert_eq!(manual.map, bundled.map);
+    assert_eq!(manual.exclude_map, bundled.exclude_map);
+
+    let error = world.query().with_all::<(Health, String)>().err().unwrap();
+    assert!(error.to_string().contains("String"));
     Ok(())
   }
 
@@ -140,6 +650,24 @@ mod test {
     Ok(())
   }
 
+  #[test]
+  fn query_after_registering_a_component_twice() -> Result<()> {
+    let mut world = World::new();
+    world.register_component::<Health>().register_component::<Damage>();
+
+    world.create_entity().with_component(Health(100))?;
+    world.create_entity().with_component(Health(30))?.with_component(Damage(5))?;
+
+    world.register_component::<Health>();
+
+    let mut query = world.query();
+    let entities = query.with_component::<Health>()?.run();
+    assert_eq!(entities.len(), 2);
+    assert_eq!(entities[0].get_component::<Health>()?.0, 100);
+    assert_eq!(entities[1].get_component::<Health>()?.0, 30);
+    Ok(())
+  }
+
   #[test]
   fn query_for_entity_after_component_delete() -> Result<()> {
     let mut world = World::new();
@@ -180,6 +708,202 @@ mod test {
 
     Ok(())
   }
+  /// Registers `Marker<A, B>` for every `A` paired with every `B`.
+  macro_rules! register_markers {
+    ($world:ident; $($a:literal)*; $b:tt) => {
+      $(register_markers!(@row $world, $a, $b);)*
+    };
+    (@row $world:ident, $a:literal, [$($b:literal)*]) => {
+      $($world.register_component::<Marker<$a, $b>>();)*
+    };
+  }
+
+  #[test]
+  fn query_for_components_past_the_128th() -> Result<()> {
+    let mut world = World::new();
+    register_markers!(world; 0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19; [0 1 2 3 4 5 6 7 8 9]);
+
+    // `Marker<A, B>` is assigned bit `A * 10 + B`
+    let mask_127 = world.entities.get_bitmask(&TypeInfo::of::<Marker<12, 7>>()).unwrap();
+    let mask_128 = world.entities.get_bitmask(&TypeInfo::of::<Marker<12, 8>>()).unwrap();
+    assert_eq!(*mask_127, Bitmask::from_bit(127));
+    assert_eq!(*mask_128, Bitmask::from_bit(128));
+
+    world.create_entity().with_component(Marker::<12, 7>)?;
+    world.create_entity().with_component(Marker::<12, 8>)?;
+    world.create_entity().with_component(Marker::<12, 8>)?.with_component(Marker::<19, 9>)?;
+
+    let mut query = world.query();
+    let entities = query.with_component::<Marker<12, 7>>()?.run();
+    assert_eq!(entities.iter().map(|entity| entity.id).collect::<Vec<_>>(), vec![0]);
+
+    let mut query = world.query();
+    let entities = query.with_component::<Marker<12, 8>>()?.without_component::<Marker<12, 7>>()?.run();
+    assert_eq!(entities.iter().map(|entity| entity.id).collect::<Vec<_>>(), vec![1, 2]);
+
+    let mut query = world.query();
+    let entities = query.with_component::<Marker<19, 9>>()?.run();
+    assert_eq!(entities.iter().map(|entity| entity.id).collect::<Vec<_>>(), vec![2]);
+
+    let mut query = world.query();
+    let entities = query.with_component::<Marker<12, 8>>()?.without_component::<Marker<19, 9>>()?.run();
+    assert_eq!(entities.iter().map(|entity| entity.id).collect::<Vec<_>>(), vec![1]);
+    Ok(())
+  }
+
+  #[test]
+  fn cached_and_looked_up_components_agree() -> Result<()> {
+    let mut world = World::new();
+    world.create_entity().with_components((Health(1), Damage(2), Transform(3)))?;
+    world.create_entity().with_components((Health(4), Transform(5)))?;
+    world.create_entity().with_component(Damage(6))?;
+
+    world.increment_tick();
+
+    // `Health` is cached, `Damage` and `Transform` are looked up
+    let mut query = world.query();
+    let entities = query.with_component::<Health>()?.run();
+    assert_eq!(entities.len(), 2);
+    assert_eq!(entities[0].get_component::<Damage>()?.0, 2);
+    assert!(entities[1].get_component::<Damage>().is_err());
+
+    for entity in &entities {
+      entity.get_component_mut::<Health>()?.0 += 10;
+      entity.get_component_mut::<Transform>()?.0 += 10;
+    }
+    assert_eq!(world.get_component::<Health>(1)?.0, 14);
+    assert_eq!(world.get_component::<Transform>(0)?.0, 13);
+
+    // Writes through a cached column are tracked
+    let mut query = world.query();
+    assert_eq!(query.changed::<Health>()?.count(), 2);
+
+    // Only the first required types are cached, the rest are looked up
+    world.spawn((Marker::<0, 0>, Marker::<0, 1>, Marker::<0, 2>, Marker::<0, 3>, Health(7)))?;
+    let mut query = world.query();
+    query.with_all::<(Marker<0, 0>, Marker<0, 1>, Marker<0, 2>, Marker<0, 3>, Health)>()?;
+    assert_eq!(query.run()[0].get_component::<Health>()?.0, 7);
+    Ok(())
+  }
+
+  #[test]
+  fn repeated_access_through_cached_columns() -> Result<()> {
+    let mut world = World::new();
+    for x in 0..1000 {
+      world.create_entity().with_components((Health(x), Damage(1)))?;
+    }
+
+    let mut query = world.query();
+    let entities = query.with_component::<Health>()?.with_component::<Damage>()?.run();
+    let mut total = 0;
+    for _ in 0..100 {
+      for entity in &entities {
+        total += entity.get_component::<Health>()?.0 + entity.get_component::<Damage>()?.0 as i32;
+      }
+    }
+    assert_eq!(total, 100 * (999 * 1000 / 2 + 1000));
+    Ok(())
+  }
+
+  #[test]
+  fn filters_combine_with_other_constraints() -> Result<()> {
+    let mut world = World::new();
+    for x in 0..10 {
+      world.create_entity().with_components((Health(x), Damage(x as u32 % 3)))?;
+    }
+    world.add_component(2, Disabled)?;
+    world.create_entity().with_component(Health(5))?;
+
+    // Filtering implicitly requires the component
+    let mut query = world.query();
+    query.without_component::<Disabled>()?.filter::<Health>(|health| health.0 < 5)?;
+    let ids = query.iter().map(|entity| entity.id).collect::<Vec<_>>();
+    assert_eq!(ids, vec![0, 1, 3, 4]);
+
+    // Filters on different types and on the same type are all applied
+    query.filter::<Damage>(|damage| damage.0 == 1)?.filter::<Health>(|health| health.0 > 1)?;
+    let ids = query.run().iter().map(|entity| entity.id).collect::<Vec<_>>();
+    assert_eq!(ids, vec![4]);
+    assert!(query.get(1).is_none());
+    assert_eq!(query.get(4).unwrap().get_component::<Health>()?.0, 4);
+
+    let mut query = world.query();
+    query.filter::<Health>(|health| health.0 == 5)?;
+    assert_eq!(query.count(), 2);
+    Ok(())
+  }
+
+  #[test]
+  fn combined_queries_yield_overlapping_entities_once() -> Result<()> {
+    let mut world = World::new();
+    world.create_entity().with_component(Health(1))?;
+    world.create_entity().with_components((Health(2), Damage(2)))?;
+    world.create_entity().with_component(Damage(3))?;
+    world.create_entity().with_components((Health(4), Disabled))?;
+    world.create_entity().with_component(Transform(5))?;
+    world.create_entity().with_components((Health(6), Damage(6)))?;
+    world.delete_entity(5)?;
+
+    let mut healthy = world.query();
+    healthy.with_component::<Health>()?.without_component::<Disabled>()?;
+    let mut damaged = world.query();
+    damaged.with_component::<Damage>()?;
+
+    // Entity 1 matches both queries and is yielded once
+    let combined = healthy.or(damaged);
+    let ids = combined.iter().map(|entity| entity.id).collect::<Vec<_>>();
+    assert_eq!(ids, vec![0, 1, 2]);
+    assert_eq!(combined.count(), 3);
+    assert!(combined.get(1).is_some());
+    assert!(combined.get(3).is_none());
+
+    // Components are fetched whichever query matched
+    let entities = combined.run();
+    assert_eq!(entities[1].get_component::<Damage>()?.0, 2);
+    assert_eq!(entities[2].get_component::<Damage>()?.0, 3);
+
+    let mut transforms = world.query();
+    transforms.with_component::<Transform>()?;
+    let ids = combined.or(transforms).iter().map(|entity| entity.id).collect::<Vec<_>>();
+    assert_eq!(ids, vec![0, 1, 2, 4]);
+    Ok(())
+  }
+
+  #[test]
+  #[should_panic(expected = "same entity store")]
+  fn combined_queries_share_an_entity_store() {
+    let world = World::new();
+    let other = World::new();
+    let _ = world.query().or(other.query());
+  }
+
+  #[test]
+  fn debug_resolves_masks_to_type_names() -> Result<()> {
+    let mut world = World::new();
+    world.create_entity().with_components((Health(1), Damage(2)))?;
+    world.register_component::<Disabled>();
+
+    let mut query = world.query();
+    query.with_component::<Health>()?.without_component::<Disabled>()?;
+    query.filter::<Damage>(|damage| damage.0 > 1)?;
+
+    let health = std::any::type_name::<Health>();
+    let damage = std::any::type_name::<Damage>();
+    let disabled = std::any::type_name::<Disabled>();
+    assert_eq!(
+      format!("{query:?}"),
+      format!("Query {{ with: [\"{health}\", \"{damage}\"], without: [\"{disabled}\"] }}")
+    );
+
+    let pretty = format!("{query:#?}");
+    assert!(pretty.contains("filters: [\n") && pretty.contains(&format!("\"{damage}\"")));
+    assert!(pretty.contains("added: []"));
+    Ok(())
+  }
+
+  struct Marker<const A: usize, const B: usize>;
+  struct Disabled;
+  struct Transform(i32);
   struct Health(pub i32);
   struct Damage(pub u32);
 }
@@ -1,43 +1,51 @@
 use crate::{
-  errors::EcsErrors,
-  storage::{EcsData, TypeInfo},
+  errors::{EcsErrors, NinaError, Result},
+  storage::{ComponentStorage, EcsData, TypeInfo},
   world::Entities
 };
-use eyre::Result;
+use std::{any::TypeId, fmt};
+
+/// The number of queried component columns a [`QueryEntity`] caches.
+///
+/// Components of types past the first `CACHED_COLUMNS` queried types are
+/// looked up in the entity store.
+pub const CACHED_COLUMNS:usize = 4;
+
+/// Columns of the component types a query requires, resolved once by the
+/// query.
+pub(crate) type Columns<'a> = [Option<&'a ComponentStorage>; CACHED_COLUMNS];
 
 /// Structure which references an entity located by a
 /// [`Query`](super::query::Query).
 pub struct QueryEntity<'a> {
   pub id:usize,
-  entities:&'a Entities
+  entities:&'a Entities,
+  /// Columns of the queried components, which the entity is known to hold.
+  columns:Columns<'a>
 }
 
 impl<'a> QueryEntity<'a> {
   pub fn new(id:usize, entities:&'a Entities) -> Self {
-    Self { id, entities }
+    Self::with_columns(id, entities, [None; CACHED_COLUMNS])
+  }
+
+  /// Create a [`QueryEntity`] for an entity which holds a component in each
+  /// of `columns`.
+  pub(crate) fn with_columns(id:usize, entities:&'a Entities, columns:Columns<'a>) -> Self {
+    Self { id, entities, columns }
   }
 
   /// Fetches a component of type `T` from a queried entity.
   ///
+  /// Components the query required are read straight from their cached
+  /// column, other components are looked up in the entity store.
+  ///
   /// # Panics
   /// - Panics if the entity does not have the component.
   pub fn get_component<T:EcsData>(&self) -> Result<&T> {
-    let ty = TypeInfo::of::<T>();
-    let entities = self.entities;
-
-    if entities.has_component::<T>(self.id)? {
-      let components = entities.components.get(&ty).unwrap();
-      // This is essentially the same as `ErasedVec`'s get method but skips the checks
-      // because they are redundant
-      return Ok(unsafe { &*components.indexed_ptr::<T>(self.id) });
-    } else {
-      return Err(
-        EcsErrors::ComponentDataDoesNotExist {
-          entity:self.id,
-          ty:ty.name()
-        }
-        .into()
-      );
+    match self.column::<T>() {
+      Some(column) => column.try_get::<T>(self.id).ok_or_else(|| self.inconsistent(column)),
+      None => self.entities.get_component::<T>(self.id)
     }
   }
 
@@ -46,22 +54,67 @@ impl<'a> QueryEntity<'a> {
   /// # Panics
   /// - Panics if the entity does not have the component.
   pub fn get_component_mut<T:EcsData>(&self) -> Result<&mut T> {
-    let ty = TypeInfo::of::<T>();
-    let entities = self.entities;
+    match self.column::<T>() {
+      Some(column) => column.try_get_mut::<T>(self.id).ok_or_else(|| self.inconsistent(column)),
+      None => self.entities.get_component_mut::<T>(self.id)
+    }
+  }
 
-    if entities.has_component::<T>(self.id)? {
-      let components = entities.components.get(&ty).unwrap();
-      // This is essentially the same as `ErasedVec`'s get method but skips the checks
-      // because they are redundant
-      return Ok(unsafe { &mut *components.indexed_ptr::<T>(self.id) });
-    } else {
-      return Err(
-        EcsErrors::ComponentDataDoesNotExist {
-          entity:self.id,
-          ty:ty.name()
-        }
-        .into()
-      );
+  /// Returns the cached column of `T` if the query required it.
+  fn column<T:EcsData>(&self) -> Option<&'a ComponentStorage> {
+    let id = TypeId::of::<T>();
+    self.columns.iter().flatten().copied().find(|column| column.ty().id() == Some(id))
+  }
+
+  /// The error returned when a cached column's slot is empty even though the
+  /// entity's bitmask says it holds the component.
+  fn inconsistent(&self, column:&ComponentStorage) -> NinaError {
+    EcsErrors::InconsistentStorage {
+      entity:self.id,
+      ty:column.ty().name()
     }
+    .into()
+  }
+
+  /// Fetches a pointer to a type-erased component from a queried entity
+  /// alongside the [`TypeInfo`] registered for it.
+  ///
+  /// The component is marked as changed since it may be written through the
+  /// pointer.
+  pub fn get_component_ptr(&self, ty:TypeInfo) -> Result<(*mut u8, TypeInfo)> {
+    self.entities.get_component_ptr(self.id, &ty)
+  }
+}
+
+impl fmt::Debug for QueryEntity<'_> {
+  /// Lists the entity's components by type name. Components registered
+  /// without a debug shim render as `TypeName(opaque)`.
+  fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result {
+    let mut components = self
+      .entities
+      .components
+      .iter()
+      .filter(|components| self.entities.has_component_erased(self.id, &components.ty()).unwrap_or(false))
+      .map(|components| {
+        (components.ty().name(), unsafe {
+          components.ty().debug_value(components.indexed_ptr::<u8>(self.id))
+        })
+      })
+      .collect::<Vec<_>>();
+    components.sort_by(|(name_1, _), (name_2, _)| name_1.cmp(name_2));
+
+    f.debug_struct("QueryEntity")
+      .field("id", &self.id)
+      .field("components", &DebugMap(components))
+      .finish()
+  }
+}
+
+/// Formats a list of pairs as a map.
+pub(crate) struct DebugMap<K, V>(pub Vec<(K, V)>);
+
+impl<K:fmt::Debug, V:fmt::Debug> fmt::Debug for DebugMap<K, V> {
+  fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result {
+    f.debug_map().entries(self.0.iter().map(|(key, value)| (key, value))).finish()
   }
 }
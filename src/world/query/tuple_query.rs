@@ -0,0 +1,213 @@
+use super::fetch::Fetch;
+use crate::{
+  errors::Result,
+  storage::{Bitmask, EcsData, TypeInfo},
+  world::{access::Access, entities::Entity, Entities}
+};
+
+/// Query which yields a tuple of components for every matching entity.
+///
+/// The column of each queried component is resolved once when the query is
+/// created so iterating only walks the entity masks.
+pub struct TupleQuery<'a, Q:Fetch> {
+  state:Q::State<'a>,
+  map:Bitmask,
+  exclude_map:Bitmask,
+  entities:&'a Entities
+}
+
+impl<'a, Q:Fetch> TupleQuery<'a, Q> {
+  /// Create a new [`TupleQuery`].
+  ///
+  /// Returns an error if a required component was never registered or if `Q`
+  /// borrows a component mutably alongside any other borrow of the same type.
+  pub fn new(entities:&'a Entities) -> Result<Self> {
+    Q::access(&mut Access::new())?;
+    let state = Q::prepare(entities)?;
+    let mut map = Bitmask::new();
+    Q::required(&state, &mut map);
+
+    Ok(Self {
+      state,
+      map,
+      exclude_map:Bitmask::new(),
+      entities
+    })
+  }
+
+  /// Register a component the queried entities must **not** hold.
+  ///
+  /// Excluding a component which was never registered has no effect.
+  pub fn without<T:EcsData>(mut self) -> Self {
+    if let Some(bit_mask) = self.entities.get_bitmask(&TypeInfo::of::<T>()) {
+      self.exclude_map.insert(bit_mask);
+    }
+    self
+  }
+
+  /// Consumes the [`TupleQuery`] and calls `f` with every live entity which
+  /// matches the query, splitting the entities across rayon's thread pool.
+  ///
+  /// Each entity is visited exactly once so mutable components are never
+  /// aliased. The fetched components must be safe to send across threads.
+  #[cfg(feature = "parallel")]
+  pub fn par_for_each<F>(self, f:F)
+  where
+    F: Fn(Entity, Q::Item<'a>) + Send + Sync,
+    Q::Item<'a>: Send {
+    use rayon::prelude::*;
+
+    /// Shares the query's resolved columns between threads.
+    struct Shared<'s, 'a, Q:Fetch>(&'s TupleQuery<'a, Q>);
+
+    impl<'s, 'a, Q:Fetch> Shared<'s, 'a, Q> {
+      fn query(&self) -> &'s TupleQuery<'a, Q> {
+        self.0
+      }
+    }
+
+    // The columns are not resized during the query, the slot ticks are atomic
+    // and `Q::Item: Send` ensures the fetched components may cross threads.
+    unsafe impl<'s, 'a, Q:Fetch> Sync for Shared<'s, 'a, Q> {}
+
+    let shared = Shared(&self);
+    (0..self.entities.map.len()).into_par_iter().for_each(|entity| {
+      let query = shared.query();
+      let entity_map = &query.entities.map[entity];
+      if query.matches(entity, entity_map) {
+        f(entity, unsafe { Q::fetch(&query.state, entity, entity_map) });
+      }
+    });
+  }
+
+  /// Returns `true` if the entity matches the query.
+  fn matches(&self, entity:Entity, entity_map:&Bitmask) -> bool {
+    self.entities.is_alive(entity) && entity_map.contains(&self.map) && !entity_map.intersects(&self.exclude_map)
+  }
+
+  /// Consumes the [`TupleQuery`]. Returns an iterator over every live entity
+  /// which matches the query alongside its components.
+  pub fn iter(self) -> TupleQueryIter<'a, Q> {
+    TupleQueryIter { query:self, index:0 }
+  }
+}
+
+impl<'a, Q:Fetch> IntoIterator for TupleQuery<'a, Q> {
+  type Item = (Entity, Q::Item<'a>);
+  type IntoIter = TupleQueryIter<'a, Q>;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.iter()
+  }
+}
+
+/// Iterator over the entities matched by a [`TupleQuery`].
+pub struct TupleQueryIter<'a, Q:Fetch> {
+  query:TupleQuery<'a, Q>,
+  index:usize
+}
+
+impl<'a, Q:Fetch> Iterator for TupleQueryIter<'a, Q> {
+  type Item = (Entity, Q::Item<'a>);
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let query = &self.query;
+    let entities:&'a Entities = query.entities;
+
+    while let Some(entity_map) = entities.map.get(self.index) {
+      let entity = self.index;
+      self.index += 1;
+
+      if query.matches(entity, entity_map) {
+        // Each entity is visited once so mutable fetches never alias
+        return Some((entity, unsafe { Q::fetch(&query.state, entity, entity_map) }));
+      }
+    }
+    None
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::errors::Result;
+  use crate::world::World;
+
+  #[test]
+  fn mutate_components_through_a_tuple_query() -> Result<()> {
+    let mut world = World::new();
+    world.create_entity().with_components((Position(0), Velocity(1)))?;
+    world.create_entity().with_component(Position(10))?;
+    world.create_entity().with_components((Position(20), Velocity(2)))?;
+
+    for (_, (position, velocity)) in world.query_tuple::<(&mut Position, &Velocity)>()? {
+      position.0 += velocity.0;
+    }
+
+    let positions = world
+      .query_tuple::<(&Position,)>()?
+      .into_iter()
+      .map(|(entity, (position,))| (entity, position.0))
+      .collect::<Vec<_>>();
+    assert_eq!(positions, vec![(0, 1), (1, 10), (2, 22)]);
+    Ok(())
+  }
+
+  #[test]
+  fn tuple_query_exclusions_and_optional_components() -> Result<()> {
+    let mut world = World::new();
+    world.create_entity().with_components((Position(0), Velocity(1)))?;
+    world.create_entity().with_component(Position(10))?;
+    world.create_entity().with_components((Position(20), Frozen))?;
+    world.create_entity().with_component(Position(30))?;
+    world.delete_entity(3)?;
+
+    let moving = world
+      .query_tuple::<(&Position, Option<&Velocity>)>()?
+      .without::<Frozen>()
+      .iter()
+      .map(|(entity, (_, velocity))| (entity, velocity.map(|velocity| velocity.0)))
+      .collect::<Vec<_>>();
+    assert_eq!(moving, vec![(0, Some(1)), (1, None)]);
+
+    assert!(world.query_tuple::<(&Position, &mut Position)>().is_err());
+    assert!(world.query_tuple::<(&Position, &String)>().is_err());
+    Ok(())
+  }
+
+  #[cfg(feature = "parallel")]
+  #[test]
+  fn parallel_iteration_matches_serial_iteration() -> Result<()> {
+    use std::sync::atomic::{AtomicI64, Ordering};
+
+    let mut world = World::new();
+    for x in 0..1000 {
+      world.create_entity().with_components((Position(x), Velocity(x % 7)))?;
+    }
+    world.create_entity().with_component(Position(5000))?;
+
+    world
+      .query_tuple::<(&mut Position, &Velocity)>()?
+      .par_for_each(|_, (position, velocity)| {
+        position.0 += velocity.0;
+      });
+
+    let parallel = AtomicI64::new(0);
+    world.query_tuple::<(&Position,)>()?.par_for_each(|_, (position,)| {
+      parallel.fetch_add(position.0 as i64, Ordering::Relaxed);
+    });
+
+    let serial = world
+      .query_tuple::<(&Position,)>()?
+      .into_iter()
+      .map(|(_, (position,))| position.0 as i64)
+      .sum::<i64>();
+    let expected = (0..1000).map(|x| x + x % 7).sum::<i64>() + 5000;
+    assert_eq!(parallel.into_inner(), expected);
+    assert_eq!(serial, expected);
+    Ok(())
+  }
+
+  struct Position(i32);
+  struct Velocity(i32);
+  struct Frozen;
+}
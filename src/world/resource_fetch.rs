@@ -0,0 +1,111 @@
+use super::{
+  access::Access,
+  resources::{Res, ResMut, Resources}
+};
+use crate::{
+  errors::Result,
+  storage::{EcsData, TypeInfo}
+};
+
+/// Types which can be borrowed from the [`Resources`] in one call.
+///
+/// Implemented for `&T`, `&mut T`, `Option<&T>`, `Option<&mut T>` and tuples
+/// of up to eight of those. References are handed out as [`Res`] and
+/// [`ResMut`] guards.
+pub trait ResourceFetch {
+  /// The guards handed out for the fetch.
+  type Item<'a>;
+
+  /// Records the resources the fetch reads and writes.
+  ///
+  /// Returns an error if the fetch borrows a resource mutably alongside any
+  /// other borrow of the same type.
+  fn access(access:&mut Access) -> Result<()>;
+
+  /// Borrows the resources.
+  ///
+  /// Returns an error if a required resource has not been added or if a
+  /// resource is already borrowed in a conflicting way.
+  fn fetch(resources:&Resources) -> Result<Self::Item<'_>>;
+}
+
+impl<T:EcsData> ResourceFetch for &T {
+  type Item<'a> = Res<'a, T>;
+
+  fn access(access:&mut Access) -> Result<()> {
+    access.add_read(TypeInfo::of::<T>())
+  }
+
+  fn fetch(resources:&Resources) -> Result<Self::Item<'_>> {
+    resources.try_borrow::<T>()
+  }
+}
+
+impl<T:EcsData> ResourceFetch for &mut T {
+  type Item<'a> = ResMut<'a, T>;
+
+  fn access(access:&mut Access) -> Result<()> {
+    access.add_write(TypeInfo::of::<T>())
+  }
+
+  fn fetch(resources:&Resources) -> Result<Self::Item<'_>> {
+    resources.try_borrow_mut::<T>()
+  }
+}
+
+impl<T:EcsData> ResourceFetch for Option<&T> {
+  type Item<'a> = Option<Res<'a, T>>;
+
+  fn access(access:&mut Access) -> Result<()> {
+    access.add_read(TypeInfo::of::<T>())
+  }
+
+  fn fetch(resources:&Resources) -> Result<Self::Item<'_>> {
+    if !resources.contains::<T>() {
+      return Ok(None);
+    }
+    Ok(Some(resources.try_borrow::<T>()?))
+  }
+}
+
+impl<T:EcsData> ResourceFetch for Option<&mut T> {
+  type Item<'a> = Option<ResMut<'a, T>>;
+
+  fn access(access:&mut Access) -> Result<()> {
+    access.add_write(TypeInfo::of::<T>())
+  }
+
+  fn fetch(resources:&Resources) -> Result<Self::Item<'_>> {
+    if !resources.contains::<T>() {
+      return Ok(None);
+    }
+    Ok(Some(resources.try_borrow_mut::<T>()?))
+  }
+}
+
+macro_rules! impl_resource_fetch_tuple {
+  ($($name:ident),*) => {
+    impl<$($name:ResourceFetch),*> ResourceFetch for ($($name,)*) {
+      type Item<'a> = ($($name::Item<'a>,)*);
+
+      fn access(access:&mut Access) -> Result<()> {
+        $($name::access(access)?;)*
+        Ok(())
+      }
+
+      fn fetch(resources:&Resources) -> Result<Self::Item<'_>> {
+        // Guards fetched before a failure are dropped, releasing their borrows
+        Ok(($($name::fetch(resources)?,)*))
+      }
+    }
+  };
+}
+
+impl_resource_fetch_tuple!(A);
+impl_resource_fetch_tuple!(A, B);
+impl_resource_fetch_tuple!(A, B, C);
+impl_resource_fetch_tuple!(A, B, C, D);
+impl_resource_fetch_tuple!(A, B, C, D, E);
+impl_resource_fetch_tuple!(A, B, C, D, E, F);
+impl_resource_fetch_tuple!(A, B, C, D, E, F, G);
+impl_resource_fetch_tuple!(A, B, C, D, E, F, G, H);
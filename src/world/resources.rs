@@ -1,48 +1,306 @@
 use crate::{
-  errors::EcsErrors,
+  errors::{EcsErrors, Result},
   storage::{EcsData, ErasedBox, TypeInfo, TypeMap}
 };
+use std::{
+  mem::ManuallyDrop,
+  ops::{Deref, DerefMut},
+  sync::atomic::{AtomicUsize, Ordering}
+};
 
 ///Struct containing resources. Singleton values with only one instance in the
 /// game world.
 #[derive(Default)]
 pub struct Resources {
-  data:TypeMap<ErasedBox>
+  data:TypeMap<ResourceCell>
 }
 
+// Resources are added as `EcsData`, which is `Send + Sync` with the `sync`
+// feature, and the borrow flags are atomic
+#[cfg(feature = "sync")]
+unsafe impl Send for Resources {}
+#[cfg(feature = "sync")]
+unsafe impl Sync for Resources {}
+
 impl Resources {
   pub fn add_resource<T:EcsData>(&mut self, data:T) {
     let ty = TypeInfo::of::<T>();
-    self.data.insert(ty, ErasedBox::new::<T>(data));
+    self.data.insert(ty, ResourceCell::new(ErasedBox::new::<T>(data)));
+  }
+
+  /// Add a resource, returning the resource of the same type it replaced.
+  ///
+  /// An existing resource is swapped in place so it keeps the shims it was
+  /// added with.
+  pub fn insert<T:EcsData>(&mut self, data:T) -> Option<T> {
+    let ty = TypeInfo::of::<T>();
+    match self.data.get_mut(&ty) {
+      Some(cell) => Some(cell.data.replace(data)),
+      None => {
+        self.data.insert(ty, ResourceCell::new(ErasedBox::new::<T>(data)));
+        None
+      }
+    }
+  }
+
+  /// Add a resource which is included in snapshots.
+  pub fn add_resource_cloneable<T:EcsData + Clone>(&mut self, data:T) {
+    let ty = TypeInfo::of_cloneable::<T>();
+    let mut data = ManuallyDrop::new(data);
+    let data = ErasedBox::from_raw_parts(ty, (&mut *data as *mut T).cast::<u8>());
+    self.data.insert(ty, ResourceCell::new(data));
+  }
+
+  /// Borrows the resource of type `T`.
+  ///
+  /// # Panics
+  /// - Panics if the resource has not been added.
+  /// - Panics if the resource is mutably borrowed.
+  pub fn get<T:EcsData>(&self) -> Res<'_, T> {
+    self.try_borrow::<T>().unwrap_or_else(|error| panic!("{error}"))
+  }
+
+  /// Mutably borrows the resource of type `T`.
+  ///
+  /// # Panics
+  /// - Panics if the resource has not been added.
+  /// - Panics if the resource is already borrowed.
+  pub fn get_mut<T:EcsData>(&self) -> ResMut<'_, T> {
+    self.try_borrow_mut::<T>().unwrap_or_else(|error| panic!("{error}"))
+  }
+
+  /// Borrows the resource of type `T` or returns `None` if it was never
+  /// added.
+  ///
+  /// # Panics
+  /// - Panics if the resource is mutably borrowed.
+  pub fn try_get<T:EcsData>(&self) -> Option<Res<'_, T>> {
+    if self.contains::<T>() {
+      Some(self.get::<T>())
+    } else {
+      None
+    }
+  }
+
+  /// Mutably borrows the resource of type `T` or returns `None` if it was
+  /// never added.
+  ///
+  /// # Panics
+  /// - Panics if the resource is already borrowed.
+  pub fn try_get_mut<T:EcsData>(&self) -> Option<ResMut<'_, T>> {
+    if self.contains::<T>() {
+      Some(self.get_mut::<T>())
+    } else {
+      None
+    }
   }
 
-  pub fn get<T:EcsData>(&self) -> &T {
+  /// Borrows the resource of type `T`.
+  ///
+  /// Returns an error if the resource has not been added or is mutably
+  /// borrowed.
+  pub fn try_borrow<T:EcsData>(&self) -> Result<Res<'_, T>> {
     let ty:TypeInfo = TypeInfo::of::<T>();
-    let data = self
-      .data
-      .get(&ty)
-      .ok_or(EcsErrors::ResourceDataDoesNotExist {
-        component:ty.name().to_string()
-      })
-      .unwrap();
-    data.get::<T>()
+    let cell = self.cell(&ty)?;
+    if !cell.borrow() {
+      return Err(EcsErrors::ConflictingAccess { ty:ty.name() }.into());
+    }
+
+    Ok(Res {
+      value:cell.data.get::<T>(),
+      borrow:&cell.borrow
+    })
+  }
+
+  /// Mutably borrows the resource of type `T`.
+  ///
+  /// Returns an error if the resource has not been added or is already
+  /// borrowed.
+  pub fn try_borrow_mut<T:EcsData>(&self) -> Result<ResMut<'_, T>> {
+    let ty:TypeInfo = TypeInfo::of::<T>();
+    let cell = self.cell(&ty)?;
+    if !cell.borrow_mut() {
+      return Err(EcsErrors::ConflictingAccess { ty:ty.name() }.into());
+    }
+
+    Ok(ResMut {
+      value:cell.data.get_mut::<T>(),
+      borrow:&cell.borrow
+    })
   }
 
-  pub fn get_mut<T:EcsData>(&self) -> &mut T {
+  /// Returns the resource of type `T`, first adding the value returned by
+  /// `f` if the resource has not been added.
+  pub fn get_or_insert_with<T:EcsData>(&mut self, f:impl FnOnce() -> T) -> &mut T {
+    if !self.contains::<T>() {
+      self.add_resource(f());
+    }
+    // No guards can be alive while `self` is mutably borrowed
+    self.data[&TypeInfo::of::<T>()].data.get_mut::<T>()
+  }
+
+  /// Returns `true` if a resource of type `T` has been added.
+  pub fn contains<T:EcsData>(&self) -> bool {
+    self.data.contains_key(&TypeInfo::of::<T>())
+  }
+
+  /// Removes the resource of type `T` and returns it, or `None` if it was
+  /// never added.
+  pub fn remove<T:EcsData>(&mut self) -> Option<T> {
     let ty:TypeInfo = TypeInfo::of::<T>();
+    self.data.remove(&ty).map(|cell| cell.data.into_inner::<T>())
+  }
+
+  /// Removes the type-erased resource of type `ty` alongside the
+  /// [`TypeInfo`] it was added with.
+  pub(crate) fn remove_erased(&mut self, ty:&TypeInfo) -> Option<(TypeInfo, ErasedBox)> {
+    self.data.remove_entry(ty).map(|(ty, cell)| (ty, cell.data))
+  }
+
+  /// Adds a type-erased resource, replacing any resource of the same type.
+  pub(crate) fn insert_erased(&mut self, ty:TypeInfo, data:ErasedBox) {
+    self.data.insert(ty, ResourceCell::new(data));
+  }
+
+  /// Returns `true` if a resource of type `ty` has been added.
+  pub fn contains_erased(&self, ty:&TypeInfo) -> bool {
+    self.data.contains_key(ty)
+  }
+
+  /// Iterates over the [`TypeInfo`] of every resource, in no particular
+  /// order.
+  pub fn types(&self) -> impl Iterator<Item = TypeInfo> + '_ {
+    self.data.keys().copied()
+  }
+
+  /// Deep copies every cloneable resource.
+  ///
+  /// # Panics
+  /// - Panics if a cloneable resource is mutably borrowed.
+  pub fn snapshot(&self) -> Resources {
     let data = self
       .data
-      .get(&ty)
-      .ok_or(EcsErrors::ResourceDataDoesNotExist {
-        component:ty.name().to_string()
+      .iter()
+      .filter(|(ty, _)| ty.is_cloneable())
+      .map(|(ty, cell)| {
+        assert!(cell.borrow(), "{}", EcsErrors::ConflictingAccess { ty:ty.name() });
+        let data = cell.data.try_clone().unwrap();
+        cell.release();
+        (*ty, ResourceCell::new(data))
       })
-      .unwrap();
-    data.get_mut::<T>()
+      .collect();
+    Resources { data }
   }
 
-  pub fn remove<T:EcsData>(&mut self) {
-    let ty:TypeInfo = TypeInfo::of::<T>();
-    self.data.remove(&ty);
+  /// Replaces the resources held in `snapshot` with copies of the snapshot's
+  /// values. Resources missing from the snapshot are left untouched.
+  pub fn restore(&mut self, snapshot:&Resources) {
+    for (ty, cell) in &snapshot.data {
+      self.data.insert(*ty, ResourceCell::new(cell.data.try_clone().unwrap()));
+    }
+  }
+
+  /// Returns the [`ResourceCell`] holding resources of type `ty`.
+  fn cell(&self, ty:&TypeInfo) -> Result<&ResourceCell> {
+    match self.data.get(ty) {
+      Some(cell) => Ok(cell),
+      None => Err(EcsErrors::ResourceDataDoesNotExist { component:ty.name() }.into())
+    }
+  }
+}
+
+/// Marks a resource as exclusively borrowed.
+const UNIQUE:usize = usize::MAX;
+
+/// A resource alongside a flag tracking how it is borrowed.
+///
+/// The flag counts the live [`Res`] guards or holds [`UNIQUE`] while a
+/// [`ResMut`] guard is alive.
+struct ResourceCell {
+  data:ErasedBox,
+  borrow:AtomicUsize
+}
+
+impl ResourceCell {
+  fn new(data:ErasedBox) -> Self {
+    ResourceCell {
+      data,
+      borrow:AtomicUsize::new(0)
+    }
+  }
+
+  /// Registers a shared borrow. Returns `false` if the resource is mutably
+  /// borrowed.
+  fn borrow(&self) -> bool {
+    self
+      .borrow
+      .fetch_update(Ordering::Acquire, Ordering::Relaxed, |count| {
+        if count >= UNIQUE - 1 {
+          None
+        } else {
+          Some(count + 1)
+        }
+      })
+      .is_ok()
+  }
+
+  /// Registers a mutable borrow. Returns `false` if the resource is already
+  /// borrowed.
+  fn borrow_mut(&self) -> bool {
+    self.borrow.compare_exchange(0, UNIQUE, Ordering::Acquire, Ordering::Relaxed).is_ok()
+  }
+
+  /// Releases a shared borrow.
+  fn release(&self) {
+    self.borrow.fetch_sub(1, Ordering::Release);
+  }
+}
+
+/// Guard granting shared access to a resource. The borrow is released when
+/// the guard is dropped.
+pub struct Res<'a, T> {
+  value:&'a T,
+  borrow:&'a AtomicUsize
+}
+
+impl<'a, T> Deref for Res<'a, T> {
+  type Target = T;
+
+  fn deref(&self) -> &T {
+    self.value
+  }
+}
+
+impl<'a, T> Drop for Res<'a, T> {
+  fn drop(&mut self) {
+    self.borrow.fetch_sub(1, Ordering::Release);
+  }
+}
+
+/// Guard granting exclusive access to a resource. The borrow is released
+/// when the guard is dropped.
+pub struct ResMut<'a, T> {
+  value:&'a mut T,
+  borrow:&'a AtomicUsize
+}
+
+impl<'a, T> Deref for ResMut<'a, T> {
+  type Target = T;
+
+  fn deref(&self) -> &T {
+    self.value
+  }
+}
+
+impl<'a, T> DerefMut for ResMut<'a, T> {
+  fn deref_mut(&mut self) -> &mut T {
+    self.value
+  }
+}
+
+impl<'a, T> Drop for ResMut<'a, T> {
+  fn drop(&mut self) {
+    self.borrow.store(0, Ordering::Release);
   }
 }
 
@@ -55,7 +313,7 @@ mod tests {
     let resources:Resources = init_resource();
     let binding = resources.data;
     let stored_resource = binding.get(&TypeInfo::of::<WorldWidth>()).unwrap();
-    let extracted_world_width = stored_resource.get::<WorldWidth>();
+    let extracted_world_width = stored_resource.data.get::<WorldWidth>();
     assert_eq!(extracted_world_width.0, 100.0)
   }
 
@@ -71,18 +329,86 @@ mod tests {
   fn mut_get_resource() {
     let resources = init_resource();
     {
-      let world_width = resources.get_mut::<WorldWidth>();
+      let mut world_width = resources.get_mut::<WorldWidth>();
       world_width.0 += 1.0
     }
     let world_width = resources.get_mut::<WorldWidth>();
     assert_eq!(world_width.0, 101.0)
   }
 
+  #[test]
+  fn conflicting_borrows_fail() {
+    let resources = init_resource();
+    {
+      let _first = resources.get_mut::<WorldWidth>();
+      let error = resources.try_borrow_mut::<WorldWidth>().err().unwrap();
+      assert!(error.to_string().contains("WorldWidth"));
+      assert!(resources.try_borrow::<WorldWidth>().is_err());
+    }
+
+    {
+      let first = resources.get::<WorldWidth>();
+      let second = resources.get::<WorldWidth>();
+      assert_eq!(first.0, second.0);
+      assert!(resources.try_borrow_mut::<WorldWidth>().is_err());
+    }
+
+    // Every guard has been dropped
+    assert!(resources.try_borrow_mut::<WorldWidth>().is_ok());
+    assert!(resources.try_borrow::<u32>().is_err());
+  }
+
+  #[test]
+  #[should_panic(expected = "WorldWidth")]
+  fn second_simultaneous_res_mut_panics() {
+    let resources = init_resource();
+    let _first = resources.get_mut::<WorldWidth>();
+    let _second = resources.get_mut::<WorldWidth>();
+  }
+
+  #[test]
+  fn try_get_missing_resource() {
+    let resources = init_resource();
+    assert!(resources.contains::<WorldWidth>());
+    assert!(!resources.contains::<u32>());
+
+    assert_eq!(resources.try_get::<WorldWidth>().unwrap().0, 100.0);
+    resources.try_get_mut::<WorldWidth>().unwrap().0 = 50.0;
+    assert_eq!(resources.get::<WorldWidth>().0, 50.0);
+
+    assert!(resources.try_get::<u32>().is_none());
+    assert!(resources.try_get_mut::<u32>().is_none());
+  }
+
+  #[test]
+  fn get_or_insert_with_keeps_existing_resources() {
+    let mut resources = init_resource();
+    resources.get_or_insert_with(|| WorldWidth(5.0)).0 += 1.0;
+    assert_eq!(resources.get::<WorldWidth>().0, 101.0);
+
+    *resources.get_or_insert_with(|| 7_u32) += 1;
+    assert_eq!(*resources.get::<u32>(), 8);
+  }
+
+  #[test]
+  fn insert_returns_the_replaced_resource() {
+    let mut resources = Resources::default();
+    assert_eq!(resources.insert(WorldWidth(1.0)).map(|width| width.0), None);
+    assert_eq!(resources.insert(WorldWidth(2.0)).map(|width| width.0), Some(1.0));
+    assert_eq!(resources.get::<WorldWidth>().0, 2.0);
+
+    // Cloneable resources stay in snapshots after being replaced
+    resources.add_resource_cloneable(String::from("a"));
+    assert_eq!(resources.insert(String::from("b")).unwrap(), "a");
+    assert_eq!(*resources.snapshot().get::<String>(), "b");
+  }
+
   #[test]
   fn remove_resource() {
     let mut resources = init_resource();
 
-    resources.remove::<WorldWidth>();
+    assert_eq!(resources.remove::<WorldWidth>().unwrap().0, 100.0);
+    assert!(resources.remove::<WorldWidth>().is_none());
     let world_width_typeid = TypeInfo::of::<WorldWidth>();
     assert!(!resources.data.contains_key(&world_width_typeid));
   }
@@ -93,7 +419,7 @@ mod tests {
 
     resources.add_resource(world_width);
 
-    return resources;
+    resources
   }
   struct WorldWidth(pub f32);
 }
@@ -0,0 +1,620 @@
+use super::{
+  system::{IntoSystem, System},
+  World
+};
+use crate::errors::{EcsErrors, Result};
+use std::{borrow::Cow, collections::BTreeSet, mem, ops::Range};
+
+/// Names a system, or a group of systems, in ordering constraints.
+///
+/// Every system is labelled with its name. Further labels are added with
+/// [`Schedule::label`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Label(Cow<'static, str>);
+
+impl Label {
+  /// Create a [`Label`] from a string.
+  pub fn new(label:impl Into<Cow<'static, str>>) -> Self {
+    Label(label.into())
+  }
+
+  /// Create a [`Label`] named after the type `T`.
+  pub fn of<T:?Sized>() -> Self {
+    Label::new(std::any::type_name::<T>())
+  }
+}
+
+/// Conversion into a [`Label`].
+///
+/// Implemented for strings, [`Label`]s and systems, which convert into the
+/// label of their name.
+pub trait IntoLabel<Marker> {
+  fn into_label(self) -> Label;
+}
+
+impl IntoLabel<()> for Label {
+  fn into_label(self) -> Label {
+    self
+  }
+}
+
+impl IntoLabel<()> for &'static str {
+  fn into_label(self) -> Label {
+    Label::new(self)
+  }
+}
+
+impl IntoLabel<()> for String {
+  fn into_label(self) -> Label {
+    Label::new(self)
+  }
+}
+
+/// Marks [`IntoLabel`] implementations for systems.
+pub struct SystemLabelMarker;
+
+impl<Marker, S:IntoSystem<Marker>> IntoLabel<(SystemLabelMarker, Marker)> for S {
+  fn into_label(self) -> Label {
+    Label::new(self.into_system().name().to_string())
+  }
+}
+
+/// A system alongside its labels and ordering constraints.
+struct ScheduledSystem {
+  system:Box<dyn System>,
+  labels:Vec<Label>,
+  before:Vec<Label>,
+  after:Vec<Label>
+}
+
+/// The points in a [`Schedule`] run where systems can be added.
+///
+/// Stages run in declaration order and the world's commands are flushed
+/// between them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stage {
+  /// Runs once, before the first [`Stage::Update`].
+  Startup,
+  /// Runs every time the [`Schedule`] runs.
+  Update,
+  /// Runs every time the [`Schedule`] runs, after [`Stage::Update`].
+  PostUpdate
+}
+
+impl Stage {
+  const ALL:[Stage; 3] = [Stage::Startup, Stage::Update, Stage::PostUpdate];
+}
+
+/// The systems of a single [`Stage`].
+#[derive(Default)]
+struct SystemStage {
+  systems:Vec<ScheduledSystem>,
+  /// Whether `systems` is sorted to satisfy the ordering constraints.
+  built:bool
+}
+
+impl SystemStage {
+  /// Sorts the systems so every ordering constraint is satisfied.
+  fn build(&mut self) -> Result<()> {
+    if self.built {
+      return Ok(());
+    }
+
+    let len = self.systems.len();
+    let has_label = |index:usize, label:&Label| self.systems[index].labels.contains(label);
+
+    // `edges[a]` holds every system which must run after `a`
+    let edges = (0..len)
+      .map(|a| {
+        (0..len)
+          .filter(|&b| {
+            a != b
+              && (self.systems[a].before.iter().any(|label| has_label(b, label)) || self.systems[b].after.iter().any(|label| has_label(a, label)))
+          })
+          .collect::<Vec<_>>()
+      })
+      .collect::<Vec<_>>();
+    let mut incoming = vec![0; len];
+    for &b in edges.iter().flatten() {
+      incoming[b] += 1;
+    }
+
+    // Always take the earliest inserted system which is ready
+    let mut ready = (0..len).filter(|index| incoming[*index] == 0).collect::<BTreeSet<_>>();
+    let mut order = Vec::with_capacity(len);
+    while let Some(index) = ready.pop_first() {
+      order.push(index);
+      for &next in &edges[index] {
+        incoming[next] -= 1;
+        if incoming[next] == 0 {
+          ready.insert(next);
+        }
+      }
+    }
+
+    if order.len() < len {
+      let systems = (0..len)
+        .filter(|index| incoming[*index] > 0)
+        .map(|index| self.systems[index].system.name())
+        .collect::<Vec<_>>()
+        .join(", ");
+      return Err(EcsErrors::SystemOrderCycle { systems }.into());
+    }
+
+    let mut systems = mem::take(&mut self.systems).into_iter().map(Some).collect::<Vec<_>>();
+    self.systems = order.into_iter().map(|index| systems[index].take().unwrap()).collect();
+    self.built = true;
+    Ok(())
+  }
+
+  fn run(&mut self, world:&mut World) {
+    for scheduled in &mut self.systems {
+      scheduled.system.run(world);
+      world.flush();
+    }
+  }
+
+  #[cfg(feature = "parallel")]
+  fn run_parallel(&mut self, world:&mut World) {
+    for batch in self.batches() {
+      let systems = &mut self.systems[batch];
+      if let [scheduled] = systems {
+        scheduled.system.run(world);
+      } else {
+        // Systems in a batch never borrow the same type mutably alongside any
+        // other borrow of it
+        let world = &*world;
+        rayon::scope(|scope| {
+          for scheduled in systems.iter_mut() {
+            scope.spawn(move |_| scheduled.system.run(world));
+          }
+        });
+      }
+      world.flush();
+    }
+  }
+
+  /// Splits the systems into consecutive batches of systems which can run at
+  /// the same time.
+  fn batches(&self) -> Vec<Range<usize>> {
+    let mut batches = Vec::new();
+    let mut start = 0;
+
+    for index in 1..self.systems.len() {
+      let joins = self.systems[index].system.access().is_some_and(|access| {
+        self.systems[start..index]
+          .iter()
+          .all(|other| other.system.access().is_some_and(|other| !other.commands && other.is_compatible(access)))
+      });
+
+      if !joins {
+        batches.push(start..index);
+        start = index;
+      }
+    }
+
+    if start < self.systems.len() {
+      batches.push(start..self.systems.len());
+    }
+    batches
+  }
+}
+
+/// Runs [`System`]s stage by stage, in insertion order unless ordering
+/// constraints say otherwise.
+///
+/// ```
+/// # use nina::world::{schedule::{Schedule, Stage}, World};
+/// fn load_assets(_:&World) {}
+/// fn read_input(_:&World) {}
+/// fn apply_velocity(_:&World) {}
+/// fn sync_transforms(_:&World) {}
+///
+/// let mut schedule = Schedule::new();
+/// schedule
+///   .add_startup_system(load_assets)
+///   .add_system(apply_velocity)
+///   .after(read_input)
+///   .add_system(read_input)
+///   .add_system_to_stage(Stage::PostUpdate, sync_transforms);
+/// schedule.run(&mut World::new());
+/// ```
+///
+/// The [`World`]'s queued commands are flushed after every system so each
+/// system observes the changes queued by the systems before it.
+///
+/// With the `parallel` feature, [`Schedule::run_parallel`] runs consecutive
+/// systems whose access does not conflict at the same time.
+#[derive(Default)]
+pub struct Schedule {
+  stages:[SystemStage; 3],
+  /// The stage of the most recently added system.
+  last:Option<Stage>
+}
+
+impl Schedule {
+  pub fn new() -> Self {
+    Schedule::default()
+  }
+
+  fn stage_mut(&mut self, stage:Stage) -> &mut SystemStage {
+    &mut self.stages[stage as usize]
+  }
+
+  /// Adds a system to the end of [`Stage::Update`].
+  ///
+  /// # Panics
+  /// - Panics if the system's parameters borrow a component or resource mutably
+  ///   alongside any other borrow of the same type.
+  pub fn add_system<Marker>(&mut self, system:impl IntoSystem<Marker>) -> &mut Self {
+    self.add_system_to_stage(Stage::Update, system)
+  }
+
+  /// Adds a system which runs once, the next time the [`Schedule`] runs.
+  ///
+  /// # Panics
+  /// - Panics if the system's parameters borrow a component or resource mutably
+  ///   alongside any other borrow of the same type.
+  pub fn add_startup_system<Marker>(&mut self, system:impl IntoSystem<Marker>) -> &mut Self {
+    self.add_system_to_stage(Stage::Startup, system)
+  }
+
+  /// Adds a system to the end of `stage`.
+  ///
+  /// # Panics
+  /// - Panics if the system's parameters borrow a component or resource mutably
+  ///   alongside any other borrow of the same type.
+  pub fn add_system_to_stage<Marker>(&mut self, stage:Stage, system:impl IntoSystem<Marker>) -> &mut Self {
+    let system = system.into_system();
+    let systems = self.stage_mut(stage);
+    systems.systems.push(ScheduledSystem {
+      labels:vec![Label::new(system.name().to_string())],
+      system:Box::new(system),
+      before:Vec::new(),
+      after:Vec::new()
+    });
+    systems.built = false;
+    self.last = Some(stage);
+    self
+  }
+
+  /// Labels the most recently added system.
+  ///
+  /// # Panics
+  /// - Panics if no system has been added.
+  pub fn label<Marker>(&mut self, label:impl IntoLabel<Marker>) -> &mut Self {
+    self.last().labels.push(label.into_label());
+    self
+  }
+
+  /// Orders the most recently added system before every system in the same
+  /// stage with the label.
+  ///
+  /// # Panics
+  /// - Panics if no system has been added.
+  pub fn before<Marker>(&mut self, label:impl IntoLabel<Marker>) -> &mut Self {
+    self.last().before.push(label.into_label());
+    self
+  }
+
+  /// Orders the most recently added system after every system in the same
+  /// stage with the label.
+  ///
+  /// # Panics
+  /// - Panics if no system has been added.
+  pub fn after<Marker>(&mut self, label:impl IntoLabel<Marker>) -> &mut Self {
+    self.last().after.push(label.into_label());
+    self
+  }
+
+  fn last(&mut self) -> &mut ScheduledSystem {
+    let stage = self.last.expect("No system has been added to the schedule");
+    let systems = self.stage_mut(stage);
+    systems.built = false;
+    systems.systems.last_mut().unwrap()
+  }
+
+  /// Returns the names of the systems in the order they run.
+  ///
+  /// Startup systems are dropped once they have run. The order only reflects
+  /// the ordering constraints once the [`Schedule`] is built.
+  pub fn system_names(&self) -> impl Iterator<Item = &str> + '_ {
+    self
+      .stages
+      .iter()
+      .flat_map(|stage| &stage.systems)
+      .map(|scheduled| scheduled.system.name())
+  }
+
+  /// Sorts the systems of every stage so every ordering constraint is
+  /// satisfied.
+  ///
+  /// Systems which are not ordered relative to each other keep their
+  /// insertion order. Constraints only order systems within the same stage
+  /// and constraints naming a label no system in the stage has are ignored.
+  ///
+  /// Returns an error naming the systems involved if the constraints form a
+  /// cycle.
+  pub fn build(&mut self) -> Result<()> {
+    for stage in &mut self.stages {
+      stage.build()?;
+    }
+    Ok(())
+  }
+
+  /// Builds the [`Schedule`] if systems or constraints were added.
+  ///
+  /// # Panics
+  /// - Panics if the ordering constraints form a cycle.
+  fn build_or_panic(&mut self) {
+    if let Err(error) = self.build() {
+      panic!("{error}");
+    }
+  }
+
+  /// Runs every stage once, flushing the world's commands after each system.
+  ///
+  /// Startup systems which have not run yet run first and are then dropped.
+  /// The world's events are updated and its removal trackers cleared after
+  /// the last stage.
+  ///
+  /// # Panics
+  /// - Panics if the ordering constraints form a cycle.
+  pub fn run(&mut self, world:&mut World) {
+    self.build_or_panic();
+    for stage in Stage::ALL {
+      self.stage_mut(stage).run(world);
+    }
+    *self.stage_mut(Stage::Startup) = SystemStage::default();
+    world.update_events();
+    world.clear_trackers();
+  }
+
+  /// Runs every stage once, running consecutive systems whose access does
+  /// not conflict on rayon's thread pool.
+  ///
+  /// The systems of each stage are split into batches which preserve their
+  /// order. A system joins the current batch if its access is compatible
+  /// with every system already in it. Systems with unknown access run alone.
+  /// The world's commands are flushed after each batch and a system which
+  /// queues commands ends its batch, so the results match [`Schedule::run`].
+  ///
+  /// The components and resources borrowed by systems in the same batch must
+  /// be safe to share across threads.
+  ///
+  /// # Panics
+  /// - Panics if the ordering constraints form a cycle.
+  #[cfg(feature = "parallel")]
+  pub fn run_parallel(&mut self, world:&mut World) {
+    self.build_or_panic();
+    for stage in Stage::ALL {
+      self.stage_mut(stage).run_parallel(world);
+    }
+    *self.stage_mut(Stage::Startup) = SystemStage::default();
+    world.update_events();
+    world.clear_trackers();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{Schedule, Stage};
+  use crate::world::{
+    command_buffer::Commands,
+    query::tuple_query::TupleQuery,
+    resources::{Res, ResMut},
+    system::SystemDescriptor,
+    World
+  };
+  use std::sync::{Arc, Mutex};
+
+  struct Position(u32);
+
+  fn spawn_position(world:&World) {
+    world.commands().spawn_entity((Position(1),));
+  }
+
+  #[test]
+  fn systems_observe_commands_flushed_by_earlier_systems() {
+    let mut world = World::new();
+    world.register_component::<Position>();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+
+    let mut schedule = Schedule::new();
+    let observed = seen.clone();
+    schedule.add_system(spawn_position).add_system(move |world:&World| {
+      let count = world.query().with_component::<Position>().unwrap().run().len();
+      observed.lock().unwrap().push(count);
+    });
+
+    schedule.run(&mut world);
+    schedule.run(&mut world);
+    assert_eq!(*seen.lock().unwrap(), vec![1, 2]);
+  }
+
+  #[test]
+  fn systems_run_in_insertion_order() {
+    let mut world = World::new();
+    let order = Arc::new(Mutex::new(Vec::new()));
+
+    let mut schedule = Schedule::new();
+    for index in 0..3 {
+      let order = order.clone();
+      schedule.add_system(move |_:&World| order.lock().unwrap().push(index));
+    }
+    schedule.run(&mut world);
+
+    assert_eq!(*order.lock().unwrap(), vec![0, 1, 2]);
+    assert!(schedule.system_names().next().unwrap().contains("closure"));
+  }
+
+  struct Velocity(u32);
+  struct Gravity(u32);
+
+  fn accelerate(query:TupleQuery<(&mut Velocity,)>, gravity:Res<Gravity>) {
+    for (_, (velocity,)) in query {
+      velocity.0 += gravity.0;
+    }
+  }
+
+  fn advance(query:TupleQuery<(&mut Position, &Velocity)>) {
+    for (_, (position, velocity)) in query {
+      position.0 += velocity.0;
+    }
+  }
+
+  fn count(query:TupleQuery<(&Position,)>, mut total:ResMut<u32>) {
+    *total += query.iter().count() as u32;
+  }
+
+  fn spawn_falling(commands:Commands, gravity:Res<Gravity>) {
+    commands.spawn_entity((Position(0), Velocity(gravity.0)));
+  }
+
+  fn physics_schedule() -> Schedule {
+    let mut schedule = Schedule::new();
+    schedule
+      .add_system(accelerate)
+      .add_system(count)
+      .add_system(spawn_falling)
+      .add_system(advance)
+      .add_system(SystemDescriptor::new(|world:&World| *world.get_resource_mut::<u32>() *= 2).writes_resource::<u32>())
+      .add_system(|world:&World| world.get_resource_mut::<Gravity>().0 += 1);
+    schedule
+  }
+
+  fn physics_world() -> World {
+    let mut world = World::new();
+    world.add_resource(Gravity(1)).add_resource(0_u32);
+    world.spawn((Position(0), Velocity(0))).unwrap();
+    world
+  }
+
+  fn record(name:&'static str) -> impl FnMut(ResMut<Vec<&'static str>>) + Send {
+    move |mut order:ResMut<Vec<&'static str>>| order.push(name)
+  }
+
+  #[test]
+  fn constraints_order_systems() {
+    let mut world = World::new();
+    world.add_resource(Vec::<&'static str>::new());
+
+    let mut schedule = Schedule::new();
+    schedule
+      .add_system(record("render"))
+      .label("render")
+      .after("physics")
+      .add_system(record("physics"))
+      .label("physics")
+      .add_system(record("input"))
+      .before("physics")
+      .add_system(record("audio"));
+    schedule.run(&mut world);
+
+    assert_eq!(*world.get_resource::<Vec<&str>>(), vec!["input", "physics", "render", "audio"]);
+  }
+
+  #[test]
+  fn systems_can_be_ordered_by_name() {
+    fn read_input(_:&World) {}
+    fn apply_velocity(_:&World) {}
+
+    let mut schedule = Schedule::new();
+    schedule.add_system(apply_velocity).after(read_input).add_system(read_input);
+    schedule.build().unwrap();
+
+    let names = schedule.system_names().collect::<Vec<_>>();
+    assert!(names[0].ends_with("read_input"));
+    assert!(names[1].ends_with("apply_velocity"));
+  }
+
+  #[test]
+  fn cycles_name_the_systems_involved() {
+    let mut schedule = Schedule::new();
+    schedule
+      .add_system(record("a"))
+      .label("a")
+      .after("b")
+      .add_system(record("b"))
+      .label("b")
+      .after("a")
+      .add_system(record("c"));
+
+    let error = schedule.build().unwrap_err().to_string();
+    assert!(error.contains("cyclic"));
+    assert_eq!(error.matches("record").count(), 2);
+  }
+
+  #[test]
+  fn batches_split_on_conflicts_commands_and_unknown_access() {
+    let schedule = physics_schedule();
+    // `count` reads nothing `accelerate` writes, `spawn_falling` closes its
+    // batch, `advance` writes the `Position` the descriptor does not touch
+    assert_eq!(schedule.stages[Stage::Update as usize].batches(), vec![0..3, 3..5, 5..6]);
+  }
+
+  #[test]
+  fn startup_systems_run_once_before_the_update_stages() {
+    let mut world = World::new();
+    world.add_resource(Vec::<&'static str>::new());
+
+    let mut schedule = Schedule::new();
+    schedule
+      .add_system_to_stage(Stage::PostUpdate, record("post_update"))
+      .add_system(record("update"))
+      .add_startup_system(record("startup"));
+    schedule.run(&mut world);
+    schedule.run(&mut world);
+
+    assert_eq!(
+      *world.get_resource::<Vec<&str>>(),
+      vec!["startup", "update", "post_update", "update", "post_update"]
+    );
+    assert_eq!(schedule.system_names().count(), 2);
+  }
+
+  #[test]
+  fn later_stages_observe_commands_queued_by_earlier_stages() {
+    let mut world = World::new();
+    world.register_component::<Position>();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+
+    let mut schedule = Schedule::new();
+    let observed = seen.clone();
+    schedule
+      .add_startup_system(spawn_position)
+      .add_system_to_stage(Stage::PostUpdate, move |world:&World| {
+        let count = world.query().with_component::<Position>().unwrap().run().len();
+        observed.lock().unwrap().push(count);
+      });
+
+    schedule.run(&mut world);
+    schedule.run(&mut world);
+    assert_eq!(*seen.lock().unwrap(), vec![1, 1]);
+  }
+
+  #[cfg(feature = "parallel")]
+  #[test]
+  fn parallel_runs_match_sequential_runs() {
+    let mut sequential = physics_world();
+    let mut parallel = physics_world();
+    let mut schedule = physics_schedule();
+
+    for _ in 0..4 {
+      schedule.run(&mut sequential);
+      schedule.run_parallel(&mut parallel);
+    }
+
+    let positions = |world:&World| {
+      world
+        .query_tuple::<(&Position, &Velocity)>()
+        .unwrap()
+        .into_iter()
+        .map(|(entity, (position, velocity))| (entity, position.0, velocity.0))
+        .collect::<Vec<_>>()
+    };
+    assert_eq!(positions(&sequential), positions(&parallel));
+    assert_eq!(*sequential.get_resource::<u32>(), *parallel.get_resource::<u32>());
+    assert_eq!(sequential.get_resource::<Gravity>().0, 5);
+  }
+}
@@ -0,0 +1,436 @@
+use super::{entities::Entity, World};
+use crate::{
+  errors::EcsErrors,
+  storage::{EcsData, ErasedBox, TypeInfo, TypeMap}
+};
+use serde::{
+  de::{self, DeserializeOwned, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor},
+  ser::{self, SerializeMap, SerializeSeq},
+  Deserializer, Serialize, Serializer
+};
+use std::{borrow::Cow, collections::HashMap, fmt};
+
+/// Erased serialization shims for a component type.
+struct SerdeComponent {
+  name:Cow<'static, str>,
+  /// Views the component behind the pointer as [`erased_serde::Serialize`].
+  serialize:fn(*const u8) -> *const dyn erased_serde::Serialize,
+  /// Deserializes a component into a new [`ErasedBox`].
+  deserialize:fn(&mut dyn erased_serde::Deserializer) -> Result<ErasedBox, erased_serde::Error>
+}
+
+/// The component types registered with
+/// [`World::register_serde`](super::World::register_serde).
+#[derive(Default)]
+pub struct SerdeRegistry {
+  by_type:TypeMap<SerdeComponent>,
+  by_name:HashMap<Cow<'static, str>, TypeInfo>,
+  /// Whether unregistered components are an error instead of being skipped.
+  strict:bool
+}
+
+impl SerdeRegistry {
+  /// Returns the name `ty` was registered under.
+  pub(crate) fn name(&self, ty:&TypeInfo) -> Option<&Cow<'static, str>> {
+    self.by_type.get(ty).map(|shim| &shim.name)
+  }
+
+  /// Returns the type registered under `name`.
+  pub(crate) fn type_named(&self, name:&str) -> Option<TypeInfo> {
+    self.by_name.get(name).copied()
+  }
+
+  /// Registers `T` under `name`, replacing the previous registration of `T`.
+  fn register<T:EcsData + Serialize + DeserializeOwned>(&mut self, name:Cow<'static, str>) {
+    fn serialize<T:Serialize + 'static>(ptr:*const u8) -> *const dyn erased_serde::Serialize {
+      ptr.cast::<T>()
+    }
+
+    fn deserialize<T:DeserializeOwned + 'static>(deserializer:&mut dyn erased_serde::Deserializer) -> Result<ErasedBox, erased_serde::Error> {
+      Ok(ErasedBox::new(erased_serde::deserialize::<T>(deserializer)?))
+    }
+
+    let ty = TypeInfo::of::<T>();
+    if let Some(old) = self.by_type.get(&ty) {
+      self.by_name.remove(&old.name);
+    }
+    self.by_name.insert(name.clone(), ty);
+    self.by_type.insert(
+      ty,
+      SerdeComponent {
+        name,
+        serialize:serialize::<T>,
+        deserialize:deserialize::<T>
+      }
+    );
+  }
+}
+
+/// Serializes a single component through its shim.
+struct ComponentSer<'a> {
+  ptr:*const u8,
+  shim:&'a SerdeComponent
+}
+
+impl Serialize for ComponentSer<'_> {
+  fn serialize<S:Serializer>(&self, serializer:S) -> Result<S::Ok, S::Error> {
+    // The pointer is to a live component of the shim's type
+    erased_serde::serialize(unsafe { &*(self.shim.serialize)(self.ptr) }, serializer)
+  }
+}
+
+/// Serializes an entity as a map from component names to components.
+struct EntitySer<'a> {
+  world:&'a World,
+  entity:Entity
+}
+
+impl Serialize for EntitySer<'_> {
+  fn serialize<S:Serializer>(&self, serializer:S) -> Result<S::Ok, S::Error> {
+    let entities = &self.world.entities;
+    let registry = &self.world.serde;
+    let mut components = Vec::new();
+
+    for ty in entities.component_types(self.entity) {
+      match registry.by_type.get(&ty) {
+        Some(shim) => {
+          let id = entities.component_id(&ty).unwrap();
+          let ptr = unsafe { entities.components[id.index()].indexed_ptr::<u8>(self.entity) };
+          components.push(ComponentSer { ptr, shim });
+        }
+        None if registry.strict => return Err(ser::Error::custom(EcsErrors::SerdeNotRegistered { component:ty.name() })),
+        None => {}
+      }
+    }
+
+    let mut map = serializer.serialize_map(Some(components.len()))?;
+    for component in &components {
+      map.serialize_entry(&component.shim.name, component)?;
+    }
+    map.end()
+  }
+}
+
+/// Deserializes a single component through its shim.
+struct ComponentSeed<'a>(&'a SerdeComponent);
+
+impl<'de> DeserializeSeed<'de> for ComponentSeed<'_> {
+  type Value = ErasedBox;
+
+  fn deserialize<D:Deserializer<'de>>(self, deserializer:D) -> Result<Self::Value, D::Error> {
+    let mut deserializer = <dyn erased_serde::Deserializer>::erase(deserializer);
+    (self.0.deserialize)(&mut deserializer).map_err(de::Error::custom)
+  }
+}
+
+/// Deserializes an entity's map of component names to components.
+struct EntitySeed<'a> {
+  registry:&'a SerdeRegistry,
+  /// Whether unknown component names are an error.
+  strict:bool,
+  /// The entity's position in the serialized sequence.
+  index:usize
+}
+
+impl<'de> DeserializeSeed<'de> for EntitySeed<'_> {
+  type Value = Vec<ErasedBox>;
+
+  fn deserialize<D:Deserializer<'de>>(self, deserializer:D) -> Result<Self::Value, D::Error> {
+    deserializer.deserialize_map(self)
+  }
+}
+
+impl<'de> Visitor<'de> for EntitySeed<'_> {
+  type Value = Vec<ErasedBox>;
+
+  fn expecting(&self, formatter:&mut fmt::Formatter) -> fmt::Result {
+    formatter.write_str("a map of component names to components")
+  }
+
+  fn visit_map<A:MapAccess<'de>>(self, mut map:A) -> Result<Self::Value, A::Error> {
+    let mut components = Vec::new();
+    while let Some(name) = map.next_key::<Cow<'de, str>>()? {
+      match self.registry.by_name.get(&*name) {
+        Some(ty) => components.push(map.next_value_seed(ComponentSeed(&self.registry.by_type[ty]))?),
+        None if self.strict => {
+          return Err(de::Error::custom(EcsErrors::UnknownComponentName {
+            name:name.into_owned(),
+            entity:self.index
+          }))
+        }
+        None => {
+          map.next_value::<IgnoredAny>()?;
+        }
+      }
+    }
+    Ok(components)
+  }
+}
+
+/// Deserializes a sequence of entities.
+struct WorldSeed<'a> {
+  registry:&'a SerdeRegistry,
+  strict:bool
+}
+
+impl<'de> Visitor<'de> for WorldSeed<'_> {
+  type Value = Vec<Vec<ErasedBox>>;
+
+  fn expecting(&self, formatter:&mut fmt::Formatter) -> fmt::Result {
+    formatter.write_str("a sequence of entities")
+  }
+
+  fn visit_seq<A:SeqAccess<'de>>(self, mut seq:A) -> Result<Self::Value, A::Error> {
+    let mut entities = Vec::new();
+    while let Some(components) = seq.next_element_seed(EntitySeed {
+      registry:self.registry,
+      strict:self.strict,
+      index:entities.len()
+    })? {
+      entities.push(components);
+    }
+    Ok(entities)
+  }
+}
+
+// Serde implementation
+impl World {
+  /// Register `T` as a component which is serialized by
+  /// [`World::serialize`] under its type name.
+  ///
+  /// Also registers `T` as a component type.
+  pub fn register_serde<T:EcsData + Serialize + DeserializeOwned>(&mut self) -> &mut Self {
+    self.register_serde_named::<T>(std::any::type_name::<T>())
+  }
+
+  /// Register `T` as a component which is serialized by
+  /// [`World::serialize`] under `name`.
+  ///
+  /// Use a name which does not change between builds, unlike the type name.
+  pub fn register_serde_named<T:EcsData + Serialize + DeserializeOwned>(&mut self, name:impl Into<Cow<'static, str>>) -> &mut Self {
+    self.entities.register_component::<T>();
+    self.serde.register::<T>(name.into());
+    self
+  }
+
+  /// Sets whether serializing or deserializing a component which was not
+  /// registered with [`World::register_serde`] is an error.
+  ///
+  /// Such components are skipped by default.
+  pub fn set_serde_strict(&mut self, strict:bool) -> &mut Self {
+    self.serde.strict = strict;
+    self
+  }
+
+  /// Serializes every live entity as a sequence of maps from component
+  /// names to components.
+  ///
+  /// Resources and entity ids are not serialized.
+  ///
+  /// ```
+  /// # use nina::world::World;
+  /// let mut world = World::new();
+  /// world.register_serde_named::<String>("name");
+  /// world.spawn((String::from("player"),)).unwrap();
+  ///
+  /// let mut ron = Vec::new();
+  /// world.serialize(&mut ron::Serializer::new(&mut ron, None).unwrap()).unwrap();
+  /// assert_eq!(String::from_utf8(ron).unwrap(), r#"[{"name":"player"}]"#);
+  /// ```
+  pub fn serialize<S:Serializer>(&self, serializer:S) -> Result<S::Ok, S::Error> {
+    let mut seq = serializer.serialize_seq(None)?;
+    for entity in self.iter_entities() {
+      seq.serialize_element(&EntitySer { world:self, entity })?;
+    }
+    seq.end()
+  }
+
+  /// Spawns the entities serialized by [`World::serialize`] and returns
+  /// them in order.
+  ///
+  /// Nothing is spawned if deserialization fails.
+  pub fn deserialize<'de, D:Deserializer<'de>>(&mut self, deserializer:D) -> Result<Vec<Entity>, D::Error> {
+    let strict = self.serde.strict;
+    self.deserialize_with(deserializer, strict)
+  }
+
+  fn deserialize_with<'de, D:Deserializer<'de>>(&mut self, deserializer:D, strict:bool) -> Result<Vec<Entity>, D::Error> {
+    let seed = WorldSeed {
+      registry:&self.serde,
+      strict
+    };
+    let entities = deserializer.deserialize_seq(seed)?;
+
+    let mut spawned = Vec::with_capacity(entities.len());
+    for components in entities {
+      let entity = self.entities.create_entity();
+      for component in components {
+        self
+          .add_component_erased(entity, component.ty(), component.ptr())
+          .map_err(de::Error::custom)?;
+        // The world now owns the value
+        component.forget();
+      }
+      spawned.push(entity);
+    }
+    Ok(spawned)
+  }
+}
+
+// Scene implementation
+#[cfg(feature = "scene")]
+impl World {
+  /// Spawns the entities described by a RON scene and returns them in
+  /// order.
+  ///
+  /// A scene is a list of entities, each a map from the names components
+  /// were registered under with [`World::register_serde_named`] to their
+  /// values.
+  ///
+  /// ```
+  /// # use nina::world::World;
+  /// let mut world = World::new();
+  /// world.register_serde_named::<String>("name").register_serde_named::<Vec<f32>>("path");
+  ///
+  /// let entities = world.spawn_scene(r#"[{ "name": "hero", "path": [0.0, 1.5] }, { "name": "slime" }]"#).unwrap();
+  /// assert_eq!(world.get_component::<String>(entities[1]).unwrap(), "slime");
+  /// ```
+  ///
+  /// Returns an error naming the entity and component if the scene contains
+  /// a component name which was not registered. Nothing is spawned on error.
+  pub fn spawn_scene(&mut self, ron_str:&str) -> crate::errors::Result<Vec<Entity>> {
+    let scene_error = |error:&dyn fmt::Display| EcsErrors::InvalidScene { message:error.to_string() };
+    let mut deserializer = ron::Deserializer::from_str(ron_str).map_err(|error| scene_error(&error))?;
+    let entities = self
+      .deserialize_with(&mut deserializer, true)
+      .map_err(|error| scene_error(&deserializer.span_error(error)))?;
+    deserializer.end().map_err(|error| scene_error(&deserializer.span_error(error)))?;
+    Ok(entities)
+  }
+
+  /// Writes every live entity as a RON scene which
+  /// [`World::spawn_scene`] can load.
+  ///
+  /// Components which were not registered for serialization are skipped
+  /// unless the [`World`] is strict.
+  pub fn to_scene_string(&self) -> crate::errors::Result<String> {
+    let mut scene = Vec::new();
+    let scene_error = |error:ron::Error| EcsErrors::InvalidScene { message:error.to_string() };
+    let mut serializer = ron::Serializer::new(&mut scene, Some(ron::ser::PrettyConfig::default())).map_err(scene_error)?;
+    self.serialize(&mut serializer).map_err(scene_error)?;
+    Ok(String::from_utf8(scene).expect("RON is valid UTF-8"))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::{errors::Result, world::World};
+  use serde::{Deserialize, Serialize};
+
+  #[derive(Debug, PartialEq, Serialize, Deserialize)]
+  struct Player;
+
+  struct Unsaved;
+
+  fn register(world:&mut World) {
+    world
+      .register_serde_named::<String>("name")
+      .register_serde_named::<Vec<f32>>("path")
+      .register_serde_named::<Player>("player");
+  }
+
+  fn saved_world() -> Result<World> {
+    let mut world = World::new();
+    register(&mut world);
+    world.spawn((String::from("hero"), vec![1.0_f32, 2.5], Player, Unsaved))?;
+    world.spawn((String::from("slime"),))?;
+    world.spawn((Unsaved,))?;
+    Ok(world)
+  }
+
+  fn to_ron(world:&World) -> ron::Result<String> {
+    let mut ron = Vec::new();
+    world.serialize(&mut ron::Serializer::new(&mut ron, None)?)?;
+    Ok(String::from_utf8(ron).unwrap())
+  }
+
+  fn from_ron(world:&mut World, ron:&str) -> ron::Result<Vec<usize>> {
+    world.deserialize(&mut ron::Deserializer::from_str(ron).unwrap())
+  }
+
+  #[test]
+  fn worlds_round_trip() -> Result<()> {
+    let ron = to_ron(&saved_world()?).unwrap();
+    assert!(ron.contains(r#""name":"hero""#));
+    assert!(ron.ends_with(r#"{"name":"slime"},{}]"#));
+
+    let mut loaded = World::new();
+    register(&mut loaded);
+    let entities = from_ron(&mut loaded, &ron).unwrap();
+
+    assert_eq!(entities.len(), 3);
+    assert_eq!(loaded.get_component::<String>(entities[0])?, "hero");
+    assert_eq!(loaded.get_component::<Vec<f32>>(entities[0])?, &vec![1.0, 2.5]);
+    assert_eq!(loaded.get_component::<Player>(entities[0])?, &Player);
+    assert_eq!(loaded.get_component::<String>(entities[1])?, "slime");
+    assert!(loaded.get_component::<Player>(entities[1]).is_err());
+    assert!(loaded.entities.component_types(entities[2]).is_empty());
+    Ok(())
+  }
+
+  #[test]
+  fn strict_mode_rejects_unregistered_components() -> Result<()> {
+    let mut world = saved_world()?;
+    world.set_serde_strict(true);
+    let error = to_ron(&world).unwrap_err().to_string();
+    assert!(error.contains("Unsaved"));
+
+    let mut loaded = World::new();
+    loaded.register_serde_named::<String>("name");
+    let ron = r#"[{"name":"hero"},{"name":"slime","speed":2}]"#;
+
+    // Unknown names are skipped unless the world is strict
+    assert_eq!(from_ron(&mut loaded, ron).unwrap().len(), 2);
+    loaded.set_serde_strict(true);
+    let error = from_ron(&mut loaded, ron).unwrap_err().to_string();
+    assert!(error.contains("Entity 1 has the unknown component \"speed\""));
+    Ok(())
+  }
+
+  #[cfg(feature = "scene")]
+  #[test]
+  fn scenes_round_trip_through_ron() -> Result<()> {
+    let mut world = World::new();
+    register(&mut world);
+    let scene = r#"[
+      { "name": "hero", "path": [1.0, 2.5], "player": () },
+      { "name": "slime" },
+      {},
+    ]"#;
+    let entities = world.spawn_scene(scene)?;
+    assert_eq!(entities.len(), 3);
+    assert_eq!(world.get_component::<Player>(entities[0])?, &Player);
+
+    // Edit, save and reload
+    *world.get_component_mut::<String>(entities[1])? = String::from("king slime");
+    let saved = world.to_scene_string()?;
+    let mut reloaded = World::new();
+    register(&mut reloaded);
+    let entities = reloaded.spawn_scene(&saved)?;
+    assert_eq!(reloaded.get_component::<Vec<f32>>(entities[0])?, &vec![1.0, 2.5]);
+    assert_eq!(reloaded.get_component::<String>(entities[1])?, "king slime");
+    Ok(())
+  }
+
+  #[cfg(feature = "scene")]
+  #[test]
+  fn scenes_with_unregistered_components_fail() {
+    let mut world = World::new();
+    world.register_serde_named::<String>("name");
+    let scene = r#"[{ "name": "hero" }, { "name": "slime" }, { "name": "bat", "wings": 2 }]"#;
+
+    let error = world.spawn_scene(scene).unwrap_err().to_string();
+    assert!(error.contains("Entity 2 has the unknown component \"wings\""), "{error}");
+    assert_eq!(world.iter_entities().count(), 0);
+  }
+}
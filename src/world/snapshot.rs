@@ -0,0 +1,346 @@
+use super::{entities::Entity, resources::Resources, Entities};
+use crate::storage::TypeInfo;
+use std::{borrow::Cow, slice};
+
+/// A deep copy of a [`World`](super::World)'s entities, cloneable components
+/// and cloneable resources.
+///
+/// Created by [`World::snapshot`](super::World::snapshot) and applied with
+/// [`World::restore`](super::World::restore).
+pub struct WorldSnapshot {
+  pub(crate) entities:Entities,
+  pub(crate) resources:Resources,
+  /// The stable name of each component type indexed by
+  /// [`ComponentId`](super::entities::ComponentId).
+  pub(crate) names:Vec<Cow<'static, str>>
+}
+
+impl WorldSnapshot {
+  /// Returns the changes which turn this snapshot's entities into `other`'s.
+  ///
+  /// Only components registered with
+  /// [`World::register_component_copy`](super::World::register_component_copy)
+  /// are compared. Components are compared bytewise, so types with padding
+  /// may be reported as changed when their fields are equal. Resources are
+  /// not diffed.
+  ///
+  /// Components are keyed by the name they were registered with through
+  /// [`World::register_serde_named`](super::World::register_serde_named), or
+  /// by their type name, so the diff can be applied to a
+  /// [`World`](super::World) in another process. Apply it with
+  /// [`World::apply_diff`](super::World::apply_diff).
+  pub fn diff(&self, other:&WorldSnapshot) -> SnapshotDiff {
+    let (from, to) = (&self.entities, &other.entities);
+    let len = from.map.len().max(to.map.len());
+
+    let mut diff = SnapshotDiff::default();
+    for entity in 0..len {
+      match (from.is_alive(entity), to.is_alive(entity)) {
+        (false, true) => diff.spawned.push(entity),
+        (true, false) => diff.despawned.push(entity),
+        _ => {}
+      }
+    }
+
+    // Types only registered in `from` can still have been removed
+    let mut tys = Vec::new();
+    for ty in to.components.iter().chain(&from.components).map(|components| components.ty()) {
+      if ty.is_copy() && !tys.contains(&ty) {
+        tys.push(ty);
+      }
+    }
+
+    for ty in tys {
+      // Despawned entities lose their components with the entity
+      for entity in (0..len).filter(|entity| to.is_alive(*entity)) {
+        let old = component_bytes(from, entity, &ty);
+        let change = match (old, component_bytes(to, entity, &ty)) {
+          (None, Some(new)) => ComponentChange::Added(new),
+          (Some(old), Some(new)) if old != new => ComponentChange::Changed(new),
+          (Some(_), None) => ComponentChange::Removed,
+          _ => continue
+        };
+        diff.changes.push(ComponentDiff {
+          entity,
+          name:other.name(&ty).or_else(|| self.name(&ty)).unwrap().clone(),
+          change
+        });
+      }
+    }
+    diff
+  }
+
+  /// Returns the stable name of a component type registered when the
+  /// snapshot was taken.
+  fn name(&self, ty:&TypeInfo) -> Option<&Cow<'static, str>> {
+    self.entities.component_id(ty).map(|id| &self.names[id.index()])
+  }
+}
+
+/// Returns a copy of the bytes of the entity's component of type `ty`, or
+/// `None` if the entity is dead or lacks the component.
+fn component_bytes(entities:&Entities, entity:Entity, ty:&TypeInfo) -> Option<Vec<u8>> {
+  if !entities.is_alive(entity) || !entities.has_component_erased(entity, ty).ok()? {
+    return None;
+  }
+
+  let components = entities.column(ty)?;
+  // The slot is filled and `ty` is Copy so its bytes can be read directly
+  let bytes = unsafe { slice::from_raw_parts(components.indexed_ptr::<u8>(entity), ty.size()) };
+  Some(bytes.to_vec())
+}
+
+/// The changes between two [`WorldSnapshot`]s, created by
+/// [`WorldSnapshot::diff`].
+///
+/// With the `serde` feature the diff can be serialized and applied to
+/// another [`World`](super::World) which registered the same component names.
+#[derive(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SnapshotDiff {
+  /// Entities alive in the newer snapshot but not the older one.
+  spawned:Vec<Entity>,
+  /// Entities alive in the older snapshot but not the newer one.
+  despawned:Vec<Entity>,
+  /// Component changes on entities alive in the newer snapshot.
+  changes:Vec<ComponentDiff>
+}
+
+impl SnapshotDiff {
+  pub fn spawned(&self) -> &[Entity] {
+    &self.spawned
+  }
+
+  pub fn despawned(&self) -> &[Entity] {
+    &self.despawned
+  }
+
+  pub fn changes(&self) -> &[ComponentDiff] {
+    &self.changes
+  }
+
+  /// Returns `true` if the snapshots' entities and copy components are equal.
+  pub fn is_empty(&self) -> bool {
+    self.spawned.is_empty() && self.despawned.is_empty() && self.changes.is_empty()
+  }
+}
+
+/// A change to one component of one entity.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ComponentDiff {
+  pub entity:Entity,
+  /// The component's serde name, or its type name if it was not registered
+  /// for serialization.
+  pub name:Cow<'static, str>,
+  pub change:ComponentChange
+}
+
+/// How a component changed between two snapshots.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ComponentChange {
+  /// The component was added, holding these bytes.
+  Added(Vec<u8>),
+  /// The component's bytes changed to these.
+  Changed(Vec<u8>),
+  Removed
+}
+
+#[cfg(test)]
+mod tests {
+  #[cfg(feature = "serde")]
+  use super::SnapshotDiff;
+  use crate::errors::Result;
+  use crate::world::World;
+  use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc
+  };
+
+  #[test]
+  fn restore_world_from_snapshot() -> Result<()> {
+    let mut world = World::new();
+    world.register_component_cloneable::<Name>().register_component_cloneable::<Path>();
+    world.add_resource_cloneable(Score(10));
+
+    world.create_entity().with_components((Name("a".to_string()), Path(vec![1.0, 2.0])))?;
+    world.create_entity().with_component(Name("b".to_string()))?;
+
+    let snapshot = world.snapshot();
+
+    // Mutate the world after taking the snapshot
+    world.get_component_mut::<Path>(0)?.0.push(3.0);
+    world.get_component_mut::<Name>(1)?.0.push('c');
+    world.delete_entity(0)?;
+    world.create_entity().with_component(Name("d".to_string()))?;
+    world.create_entity().with_component(Path(Vec::new()))?;
+    world.get_resource_mut::<Score>().0 = 20;
+
+    world.restore(&snapshot);
+
+    assert_eq!(world.iter_entities().collect::<Vec<_>>(), vec![0, 1]);
+    assert_eq!(world.get_component::<Name>(0)?.0, "a");
+    assert_eq!(world.get_component::<Path>(0)?.0, vec![1.0, 2.0]);
+    assert_eq!(world.get_component::<Name>(1)?.0, "b");
+    assert!(world.get_component::<Path>(1).is_err());
+    assert_eq!(world.get_resource::<Score>().0, 10);
+
+    // The snapshot can be restored more than once
+    world.get_component_mut::<Name>(0)?.0.push('e');
+    world.restore(&snapshot);
+    assert_eq!(world.get_component::<Name>(0)?.0, "a");
+    Ok(())
+  }
+
+  #[test]
+  fn restore_drops_the_current_components() -> Result<()> {
+    let drops = Arc::new(AtomicU32::new(0));
+
+    let mut world = World::new();
+    world.register_component_cloneable::<DropCounter>();
+    world.create_entity().with_component(DropCounter(drops.clone()))?;
+
+    let snapshot = world.snapshot();
+    world.create_entity().with_component(DropCounter(drops.clone()))?;
+    assert_eq!(drops.load(Ordering::Relaxed), 0);
+
+    // Both live components are dropped, the snapshot's copy is cloned in
+    world.restore(&snapshot);
+    assert_eq!(drops.load(Ordering::Relaxed), 2);
+
+    drop(world);
+    assert_eq!(drops.load(Ordering::Relaxed), 3);
+    drop(snapshot);
+    assert_eq!(drops.load(Ordering::Relaxed), 4);
+    Ok(())
+  }
+
+  #[test]
+  fn components_which_are_not_cloneable_are_skipped() -> Result<()> {
+    let mut world = World::new();
+    world.register_component_cloneable::<Name>();
+    world.add_resource(Score(1));
+
+    world.create_entity().with_components((Name("a".to_string()), Opaque(1)))?;
+
+    let snapshot = world.snapshot();
+    world.get_resource_mut::<Score>().0 = 2;
+    world.restore(&snapshot);
+
+    assert_eq!(world.get_component::<Name>(0)?.0, "a");
+    assert!(world.get_component::<Opaque>(0).is_err());
+    assert_eq!(world.get_resource::<Score>().0, 2);
+    Ok(())
+  }
+
+  #[test]
+  fn applying_a_diff_to_the_older_snapshot_matches_the_newer() -> Result<()> {
+    let mut world = World::new();
+    world.register_component_copy::<Position>().register_component_copy::<Health>();
+    world.register_component_cloneable::<Name>();
+
+    world.create_entity().with_components((Position(0.0, 0.0), Health(10)))?;
+    world.create_entity().with_components((Position(1.0, 1.0), Name("b".to_string())))?;
+    world.create_entity().with_component(Health(3))?;
+    let a = world.snapshot();
+
+    world.get_component_mut::<Position>(0)?.0 = 5.0;
+    world.delete_component::<Health>(0)?;
+    world.add_component(1, Health(7))?;
+    world.get_component_mut::<Name>(1)?.0.push('c');
+    world.delete_entity(2)?;
+    world.create_entity().with_component(Position(2.0, 2.0))?;
+    world.create_entity().with_component(Health(1))?;
+    let b = world.snapshot();
+
+    let diff = a.diff(&b);
+    assert_eq!(diff.spawned(), &[3]);
+    assert!(diff.despawned().is_empty());
+    assert!(b.diff(&b).is_empty());
+
+    let mut rolled_back = World::new();
+    rolled_back.restore(&a);
+    rolled_back.apply_diff(&diff)?;
+    let mut expected = World::new();
+    expected.restore(&b);
+
+    assert_eq!(
+      rolled_back.iter_entities().collect::<Vec<_>>(),
+      expected.iter_entities().collect::<Vec<_>>()
+    );
+    for entity in expected.iter_entities() {
+      assert_eq!(
+        rolled_back.get_component::<Position>(entity).ok(),
+        expected.get_component::<Position>(entity).ok()
+      );
+      assert_eq!(
+        rolled_back.get_component::<Health>(entity).ok(),
+        expected.get_component::<Health>(entity).ok()
+      );
+    }
+
+    // Non-copy components are not diffed
+    assert_eq!(rolled_back.get_component::<Name>(1)?.0, "b");
+    Ok(())
+  }
+
+  #[cfg(feature = "serde")]
+  #[test]
+  fn serialized_diffs_apply_to_another_world() -> Result<()> {
+    let mut world = World::new();
+    world.register_component_copy::<Position>().register_component_copy::<Health>();
+    world.register_serde_named::<Health>("health");
+
+    world.create_entity().with_components((Position(0.0, 0.0), Health(10)))?;
+    let a = world.snapshot();
+    world.get_component_mut::<Position>(0)?.0 = 5.0;
+    world.delete_component::<Health>(0)?;
+    world.create_entity().with_components((Position(2.0, 2.0), Health(4)))?;
+
+    let ron = ron::to_string(&a.diff(&world.snapshot())).unwrap();
+    let diff:SnapshotDiff = ron::from_str(&ron).unwrap();
+    assert!(ron.contains("\"health\""));
+
+    // The other world assigns the components different ids
+    let mut other = World::new();
+    other.register_component_copy::<Health>().register_component_copy::<Position>();
+    other.register_serde_named::<Health>("health");
+    other.create_entity().with_components((Position(0.0, 0.0), Health(10)))?;
+
+    other.apply_diff(&diff)?;
+    assert_eq!(other.iter_entities().collect::<Vec<_>>(), vec![0, 1]);
+    assert_eq!(*other.get_component::<Position>(0)?, Position(5.0, 0.0));
+    assert!(other.get_component::<Health>(0).is_err());
+    assert_eq!(*other.get_component::<Position>(1)?, Position(2.0, 2.0));
+    assert_eq!(*other.get_component::<Health>(1)?, Health(4));
+
+    // Bytes are only moved into copy components of the same size
+    let mut unregistered = World::new();
+    unregistered.register_serde_named::<u8>("health");
+    unregistered.create_entity().with_component(0_u8)?;
+    assert!(unregistered.apply_diff(&diff).is_err());
+    Ok(())
+  }
+
+  #[derive(Clone, Copy, Debug, PartialEq)]
+  struct Position(f32, f32);
+  #[derive(Clone, Copy, Debug, PartialEq)]
+  #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+  struct Health(u32);
+  #[derive(Clone)]
+  struct Name(String);
+  #[derive(Clone)]
+  struct Path(Vec<f32>);
+  struct Opaque(u32);
+  #[derive(Clone)]
+  struct Score(u32);
+
+  #[derive(Clone)]
+  struct DropCounter(Arc<AtomicU32>);
+
+  impl Drop for DropCounter {
+    fn drop(&mut self) {
+      self.0.fetch_add(1, Ordering::Relaxed);
+    }
+  }
+}
@@ -0,0 +1,390 @@
+use super::{access::SystemAccess, system_param::SystemParam, World};
+use crate::storage::{EcsData, TypeInfo};
+use std::marker::PhantomData;
+
+/// A unit of logic run against the [`World`] by a
+/// [`Schedule`](super::schedule::Schedule).
+///
+/// Implemented for every closure and function taking `&World`. Functions
+/// whose parameters are all [`SystemParam`]s become systems through
+/// [`IntoSystem`].
+///
+/// Systems borrow the [`World`] immutably. Structural changes are queued
+/// through [`World::commands`] and applied when the schedule flushes.
+pub trait System: Send {
+  /// Returns the system's name, used to identify it in diagnostics.
+  fn name(&self) -> &str;
+
+  /// Runs the system once.
+  fn run(&mut self, world:&World);
+
+  /// Returns the components and resources the system reads and writes, or
+  /// `None` if they are unknown.
+  ///
+  /// Systems with unknown access never run alongside other systems.
+  fn access(&self) -> Option<&SystemAccess> {
+    None
+  }
+}
+
+impl<F:FnMut(&World) + Send> System for F {
+  fn name(&self) -> &str {
+    std::any::type_name::<F>()
+  }
+
+  fn run(&mut self, world:&World) {
+    self(world)
+  }
+}
+
+/// Conversion into a [`System`].
+///
+/// `Marker` distinguishes the implementations for [`System`]s and for
+/// functions of [`SystemParam`]s so both can be passed to
+/// [`Schedule::add_system`](super::schedule::Schedule::add_system).
+pub trait IntoSystem<Marker> {
+  type System: System + 'static;
+
+  /// Converts the value into a [`System`].
+  ///
+  /// # Panics
+  /// - Panics if the system's parameters borrow a component or resource mutably
+  ///   alongside any other borrow of the same type.
+  fn into_system(self) -> Self::System;
+}
+
+impl<S:System + 'static> IntoSystem<()> for S {
+  type System = S;
+
+  fn into_system(self) -> Self::System {
+    self
+  }
+}
+
+/// A function whose parameters are all [`SystemParam`]s.
+///
+/// Implemented for functions of up to eight parameters. `Params` is the
+/// function pointer type of the function, `fn(A, B) -> Out`.
+pub trait SystemParamFunction<Params>: 'static {
+  /// The function's return value.
+  type Out;
+
+  /// Records the components and resources the parameters read and write.
+  fn access(access:&mut SystemAccess) -> crate::errors::Result<()>;
+
+  /// Fetches every parameter from the [`World`] and calls the function.
+  ///
+  /// # Panics
+  /// - Panics if a parameter cannot be fetched.
+  fn run(&mut self, world:&World, name:&str) -> Self::Out;
+}
+
+/// Records the access of the [`SystemParamFunction`] `F`.
+///
+/// # Panics
+/// - Panics if the parameters borrow a component or resource mutably alongside
+///   any other borrow of the same type.
+fn function_access<F:SystemParamFunction<Params>, Params>(name:&str) -> SystemAccess {
+  let mut access = SystemAccess::new();
+  if let Err(error) = F::access(&mut access) {
+    panic!("System `{name}` has conflicting parameters: {error}");
+  }
+  access
+}
+
+/// Marks [`IntoSystem`] implementations for [`SystemParamFunction`]s.
+pub struct FunctionMarker;
+
+/// A [`System`] which fetches its parameters from the [`World`] and passes
+/// them to a function.
+pub struct FunctionSystem<F, Params> {
+  f:F,
+  name:&'static str,
+  access:SystemAccess,
+  _params:PhantomData<fn() -> Params>
+}
+
+impl<F:SystemParamFunction<Params, Out = ()> + Send, Params:'static> System for FunctionSystem<F, Params> {
+  fn name(&self) -> &str {
+    self.name
+  }
+
+  fn run(&mut self, world:&World) {
+    self.f.run(world, self.name);
+  }
+
+  fn access(&self) -> Option<&SystemAccess> {
+    Some(&self.access)
+  }
+}
+
+impl<F:SystemParamFunction<Params, Out = ()> + Send, Params:'static> IntoSystem<(FunctionMarker, Params)> for F {
+  type System = FunctionSystem<F, Params>;
+
+  fn into_system(self) -> Self::System {
+    let name = std::any::type_name::<F>();
+    FunctionSystem {
+      f:self,
+      name,
+      access:function_access::<F, Params>(name),
+      _params:PhantomData
+    }
+  }
+}
+
+/// A system which can be run once through
+/// [`World::run_system`](super::World::run_system).
+///
+/// Implemented for every [`System`] and for [`SystemParamFunction`]s of any
+/// return type.
+pub trait RunSystem<Marker> {
+  /// The value returned by the system.
+  type Out;
+
+  /// Runs the system once without flushing the world's commands.
+  ///
+  /// # Panics
+  /// - Panics if the system's parameters conflict or cannot be fetched.
+  fn run_once(self, world:&World) -> Self::Out;
+}
+
+impl<S:System> RunSystem<()> for S {
+  type Out = ();
+
+  fn run_once(mut self, world:&World) {
+    self.run(world);
+  }
+}
+
+impl<F:SystemParamFunction<Params>, Params> RunSystem<(FunctionMarker, Params)> for F {
+  type Out = F::Out;
+
+  fn run_once(mut self, world:&World) -> Self::Out {
+    let name = std::any::type_name::<F>();
+    function_access::<F, Params>(name);
+    self.run(world, name)
+  }
+}
+
+/// Wraps a [`System`] with manually declared access so it can run alongside
+/// other systems.
+///
+/// ```
+/// # use nina::world::{schedule::Schedule, system::SystemDescriptor, World};
+/// struct Health(u32);
+///
+/// let mut schedule = Schedule::new();
+/// schedule.add_system(
+///   SystemDescriptor::new(|world:&World| {
+///     world.get_component_mut::<Health>(0).unwrap().0 -= 1;
+///   })
+///   .writes::<Health>()
+/// );
+/// ```
+///
+/// # Warning
+/// - The declaration must cover everything the system borrows. Undeclared
+///   borrows may race with systems run alongside it.
+pub struct SystemDescriptor<S> {
+  system:S,
+  access:SystemAccess
+}
+
+impl<S:System> SystemDescriptor<S> {
+  /// Wraps `system`, declaring that it accesses nothing.
+  pub fn new(system:S) -> Self {
+    SystemDescriptor {
+      system,
+      access:SystemAccess::new()
+    }
+  }
+
+  /// Declares that the system reads the component `T`.
+  ///
+  /// # Panics
+  /// - Panics if the system already writes `T`.
+  pub fn reads<T:EcsData>(mut self) -> Self {
+    let result = self.access.components.add_read(TypeInfo::of::<T>());
+    self.check(result)
+  }
+
+  /// Declares that the system writes the component `T`.
+  ///
+  /// # Panics
+  /// - Panics if the system already reads or writes `T`.
+  pub fn writes<T:EcsData>(mut self) -> Self {
+    let result = self.access.components.add_write(TypeInfo::of::<T>());
+    self.check(result)
+  }
+
+  /// Declares that the system reads the resource `T`.
+  ///
+  /// # Panics
+  /// - Panics if the system already writes `T`.
+  pub fn reads_resource<T:EcsData>(mut self) -> Self {
+    let result = self.access.resources.add_read(TypeInfo::of::<T>());
+    self.check(result)
+  }
+
+  /// Declares that the system writes the resource `T`.
+  ///
+  /// # Panics
+  /// - Panics if the system already reads or writes `T`.
+  pub fn writes_resource<T:EcsData>(mut self) -> Self {
+    let result = self.access.resources.add_write(TypeInfo::of::<T>());
+    self.check(result)
+  }
+
+  /// Declares that the system queues commands.
+  pub fn uses_commands(mut self) -> Self {
+    self.access.commands = true;
+    self
+  }
+
+  fn check(self, result:crate::errors::Result<()>) -> Self {
+    if let Err(error) = result {
+      panic!("System `{}` has conflicting access: {error}", self.system.name());
+    }
+    self
+  }
+}
+
+impl<S:System> System for SystemDescriptor<S> {
+  fn name(&self) -> &str {
+    self.system.name()
+  }
+
+  fn run(&mut self, world:&World) {
+    self.system.run(world);
+  }
+
+  fn access(&self) -> Option<&SystemAccess> {
+    Some(&self.access)
+  }
+}
+
+macro_rules! impl_system_function {
+  ($($param:ident),*) => {
+    #[allow(non_snake_case, unused_variables)]
+    impl<Func, Out, $($param:SystemParam + 'static),*> SystemParamFunction<fn($($param,)*) -> Out> for Func
+    where
+      Func: 'static,
+      for<'a> &'a mut Func: FnMut($($param),*) -> Out + FnMut($($param::Item<'_>),*) -> Out {
+      type Out = Out;
+
+      fn access(access:&mut SystemAccess) -> crate::errors::Result<()> {
+        $($param::access(access)?;)*
+        Ok(())
+      }
+
+      fn run(&mut self, world:&World, name:&str) -> Out {
+        // Calling through a helper picks the `FnMut` impl taking the items
+        #[allow(clippy::too_many_arguments)]
+        fn call_inner<Out, $($param),*>(mut f:impl FnMut($($param),*) -> Out, $($param:$param),*) -> Out {
+          f($($param),*)
+        }
+
+        $(
+          let $param = $param::fetch(world)
+            .unwrap_or_else(|error| panic!("System `{name}` could not fetch its parameters: {error}"));
+        )*
+        call_inner(self, $($param),*)
+      }
+    }
+  };
+}
+
+impl_system_function!();
+impl_system_function!(A);
+impl_system_function!(A, B);
+impl_system_function!(A, B, C);
+impl_system_function!(A, B, C, D);
+impl_system_function!(A, B, C, D, E);
+impl_system_function!(A, B, C, D, E, F);
+impl_system_function!(A, B, C, D, E, F, G);
+impl_system_function!(A, B, C, D, E, F, G, H);
+
+#[cfg(test)]
+mod tests {
+  use crate::world::{
+    command_buffer::Commands,
+    query::tuple_query::TupleQuery,
+    resources::{Res, ResMut},
+    schedule::Schedule,
+    World
+  };
+
+  struct Pos(f32);
+  struct Vel(f32);
+  struct Time(f32);
+  struct Spawned(u32);
+
+  fn movement(query:TupleQuery<(&mut Pos, &Vel)>, time:Res<Time>) {
+    for (_, (pos, vel)) in query {
+      pos.0 += vel.0 * time.0;
+    }
+  }
+
+  fn count_spawned(query:TupleQuery<(&Spawned,)>, mut spawned:ResMut<u32>) {
+    *spawned = query.iter().count() as u32;
+  }
+
+  fn spawn(commands:Commands) {
+    commands.spawn_entity((Spawned(1),));
+  }
+
+  #[test]
+  fn function_systems_fetch_their_parameters() {
+    let mut world = World::new();
+    world.add_resource(Time(0.5)).add_resource(0_u32);
+    world.register_component::<Spawned>();
+    let entity = world.spawn((Pos(1.0), Vel(4.0))).unwrap();
+
+    let mut schedule = Schedule::new();
+    schedule.add_system(movement).add_system(spawn).add_system(count_spawned);
+    schedule.run(&mut world);
+    schedule.run(&mut world);
+
+    assert_eq!(world.get_component::<Pos>(entity).unwrap().0, 5.0);
+    assert_eq!(*world.get_resource::<u32>(), 2);
+  }
+
+  #[test]
+  #[should_panic(expected = "has conflicting parameters")]
+  fn conflicting_parameters_panic_on_registration() {
+    fn conflicting(_:ResMut<Time>, _:ResMut<Time>) {}
+    Schedule::new().add_system(conflicting);
+  }
+
+  #[test]
+  fn one_shot_systems_flush_their_commands() {
+    let mut world = World::new();
+    world.register_component::<Spawned>();
+
+    world.run_system(spawn);
+    let count = world.run_system(|query:TupleQuery<(&Spawned,)>| query.iter().count());
+    assert_eq!(count, 1);
+
+    world.run_system(|world:&World| {
+      world.commands().spawn_entity((Spawned(2),));
+    });
+    assert_eq!(world.query_tuple::<(&Spawned,)>().unwrap().iter().count(), 2);
+  }
+
+  #[test]
+  #[should_panic(expected = "has conflicting parameters")]
+  fn conflicting_one_shot_systems_panic() {
+    let mut world = World::new();
+    world.add_resource(Time(1.0));
+    world.run_system(|_:Res<Time>, _:ResMut<Time>| {});
+  }
+
+  #[test]
+  #[should_panic(expected = "could not fetch its parameters")]
+  fn missing_resources_panic_on_run() {
+    let mut world = World::new();
+    let mut schedule = Schedule::new();
+    schedule.add_system(|_:Res<Time>| {});
+    schedule.run(&mut world);
+  }
+}
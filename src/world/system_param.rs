@@ -0,0 +1,81 @@
+use super::{
+  access::SystemAccess,
+  command_buffer::Commands,
+  query::{fetch::Fetch, tuple_query::TupleQuery},
+  resources::{Res, ResMut},
+  World
+};
+use crate::{
+  errors::Result,
+  storage::{EcsData, TypeInfo}
+};
+
+/// Types which a function system can take as a parameter.
+///
+/// Implemented for [`TupleQuery`], [`Res`], [`ResMut`] and [`Commands`].
+pub trait SystemParam {
+  /// The parameter handed to the system for a single run.
+  type Item<'w>;
+
+  /// Records the components and resources the parameter reads and writes.
+  ///
+  /// Returns an error if the parameter borrows a type mutably alongside any
+  /// other borrow of the same type.
+  fn access(access:&mut SystemAccess) -> Result<()>;
+
+  /// Fetches the parameter from the [`World`].
+  ///
+  /// Returns an error if a required component was never registered, or a
+  /// required resource has not been added or is already borrowed.
+  fn fetch(world:&World) -> Result<Self::Item<'_>>;
+}
+
+impl<Q:Fetch> SystemParam for TupleQuery<'_, Q> {
+  type Item<'w> = TupleQuery<'w, Q>;
+
+  fn access(access:&mut SystemAccess) -> Result<()> {
+    Q::access(&mut access.components)
+  }
+
+  fn fetch(world:&World) -> Result<Self::Item<'_>> {
+    TupleQuery::new(&world.entities)
+  }
+}
+
+impl<T:EcsData> SystemParam for Res<'_, T> {
+  type Item<'w> = Res<'w, T>;
+
+  fn access(access:&mut SystemAccess) -> Result<()> {
+    access.resources.add_read(TypeInfo::of::<T>())
+  }
+
+  fn fetch(world:&World) -> Result<Self::Item<'_>> {
+    world.resources.try_borrow::<T>()
+  }
+}
+
+impl<T:EcsData> SystemParam for ResMut<'_, T> {
+  type Item<'w> = ResMut<'w, T>;
+
+  fn access(access:&mut SystemAccess) -> Result<()> {
+    access.resources.add_write(TypeInfo::of::<T>())
+  }
+
+  fn fetch(world:&World) -> Result<Self::Item<'_>> {
+    world.resources.try_borrow_mut::<T>()
+  }
+}
+
+impl SystemParam for Commands<'_> {
+  type Item<'w> = Commands<'w>;
+
+  // Commands only lock the world's buffer while queuing
+  fn access(access:&mut SystemAccess) -> Result<()> {
+    access.commands = true;
+    Ok(())
+  }
+
+  fn fetch(world:&World) -> Result<Self::Item<'_>> {
+    Ok(world.commands())
+  }
+}
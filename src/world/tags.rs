@@ -0,0 +1,145 @@
+use super::entities::Entity;
+use hashbrown::HashMap;
+use smallvec::SmallVec;
+
+/// Groups of entities keyed by a `u64` tag, such as a spawn group or a
+/// trigger volume.
+///
+/// Each bucket keeps its entities in the order they were tagged. The tags of
+/// each entity are tracked too so deleting an entity only visits its own
+/// buckets.
+#[derive(Default)]
+pub struct Tags {
+  buckets:HashMap<u64, SmallVec<[Entity; 4]>>,
+  tags:HashMap<Entity, SmallVec<[u64; 2]>>
+}
+
+impl Tags {
+  /// Tags the entity with `tag`. Does nothing if it is already tagged.
+  pub fn insert(&mut self, entity:Entity, tag:u64) {
+    let tags = self.tags.entry(entity).or_default();
+    if !tags.contains(&tag) {
+      tags.push(tag);
+      self.buckets.entry(tag).or_default().push(entity);
+    }
+  }
+
+  /// Removes `tag` from the entity. Returns `false` if the entity was not
+  /// tagged with it.
+  pub fn remove(&mut self, entity:Entity, tag:u64) -> bool {
+    let Some(tags) = self.tags.get_mut(&entity) else {
+      return false;
+    };
+    let Some(index) = tags.iter().position(|tagged| *tagged == tag) else {
+      return false;
+    };
+    tags.swap_remove(index);
+    if tags.is_empty() {
+      self.tags.remove(&entity);
+    }
+    self.remove_from_bucket(entity, tag);
+    true
+  }
+
+  /// Removes the entity from every bucket.
+  pub fn remove_entity(&mut self, entity:Entity) {
+    for tag in self.tags.remove(&entity).unwrap_or_default() {
+      self.remove_from_bucket(entity, tag);
+    }
+  }
+
+  /// Returns the entities tagged with `tag` in the order they were tagged.
+  pub fn get(&self, tag:u64) -> &[Entity] {
+    self.buckets.get(&tag).map_or(&[], |bucket| bucket.as_slice())
+  }
+
+  /// Returns the tags of the entity in no particular order.
+  pub fn of(&self, entity:Entity) -> &[u64] {
+    self.tags.get(&entity).map_or(&[], |tags| tags.as_slice())
+  }
+
+  pub fn clear(&mut self) {
+    self.buckets.clear();
+    self.tags.clear();
+  }
+
+  fn remove_from_bucket(&mut self, entity:Entity, tag:u64) {
+    let bucket = self.buckets.get_mut(&tag).unwrap();
+    // Keep the bucket in tagging order
+    bucket.retain(|tagged| *tagged != entity);
+    if bucket.is_empty() {
+      self.buckets.remove(&tag);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::{
+    errors::{EcsErrors, NinaError, Result},
+    world::World
+  };
+
+  const WAVE_1:u64 = 1;
+  const WAVE_2:u64 = 2;
+  const TRIGGER:u64 = 0xfeed;
+
+  struct Enemy;
+
+  #[test]
+  fn tag_and_untag_entities() -> Result<()> {
+    let mut world = World::new();
+    let first = world.spawn((Enemy,))?;
+    let second = world.spawn((Enemy,))?;
+    let third = world.spawn((Enemy,))?;
+
+    world.tag(second, WAVE_1)?;
+    world.tag(first, WAVE_1)?;
+    world.tag(first, WAVE_1)?;
+    world.tag(third, WAVE_2)?;
+    world.tag(first, TRIGGER)?;
+
+    assert_eq!(world.tagged(WAVE_1), [second, first]);
+    assert_eq!(world.tagged(WAVE_2), [third]);
+    assert!(world.tagged(42).is_empty());
+
+    assert!(world.untag(second, WAVE_1));
+    assert!(!world.untag(second, WAVE_1));
+    assert!(!world.untag(third, WAVE_1));
+    assert_eq!(world.tagged(WAVE_1), [first]);
+    assert_eq!(world.tagged(TRIGGER), [first]);
+
+    assert!(matches!(
+      world.tag(99, WAVE_1),
+      Err(NinaError::Ecs(EcsErrors::EntityDoesNotExist { entity:99 }))
+    ));
+    Ok(())
+  }
+
+  #[test]
+  fn deleted_entities_leave_every_bucket() -> Result<()> {
+    let mut world = World::new();
+    let first = world.spawn((Enemy,))?;
+    let second = world.spawn((Enemy,))?;
+    let third = world.spawn((Enemy,))?;
+    for entity in [first, second, third] {
+      world.tag(entity, WAVE_1)?;
+    }
+    world.tag(second, TRIGGER)?;
+
+    world.delete_entity(second)?;
+    assert_eq!(world.tagged(WAVE_1), [first, third]);
+    assert!(world.tagged(TRIGGER).is_empty());
+
+    // Reusing the slot does not bring the tags back
+    let reused = world.spawn((Enemy,))?;
+    assert_eq!(reused, second);
+    assert_eq!(world.tagged(WAVE_1), [first, third]);
+
+    world.commands().delete_entity(first);
+    world.flush();
+    world.despawn_take(third)?;
+    assert!(world.tagged(WAVE_1).is_empty());
+    Ok(())
+  }
+}